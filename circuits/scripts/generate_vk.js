@@ -78,6 +78,9 @@ try {
     process.exit(1);
 }
 
+// On-chain layout, as `vk_from_snarkjs` writes it:
+// nr_pubinputs (u32 LE) | alpha_g1 | beta_g2 | gamma_g2 | delta_g2 | IC, big-endian.
+// snarkjs lists each G2 coordinate as [c0, c1]; groth16-solana expects (c1, c0).
 function convertToBytes(vk) {
     // Convert field elements to byte arrays (big-endian)
     const toBytes = (num) => {
@@ -92,22 +95,22 @@ function convertToBytes(vk) {
             ...toBytes(vk.alpha_g1[1])
         ],
         beta_g2: [
-            ...toBytes(vk.beta_g2[0][0]),
             ...toBytes(vk.beta_g2[0][1]),
-            ...toBytes(vk.beta_g2[1][0]),
-            ...toBytes(vk.beta_g2[1][1])
+            ...toBytes(vk.beta_g2[0][0]),
+            ...toBytes(vk.beta_g2[1][1]),
+            ...toBytes(vk.beta_g2[1][0])
         ],
         gamma_g2: [
-            ...toBytes(vk.gamma_g2[0][0]),
             ...toBytes(vk.gamma_g2[0][1]),
-            ...toBytes(vk.gamma_g2[1][0]),
-            ...toBytes(vk.gamma_g2[1][1])
+            ...toBytes(vk.gamma_g2[0][0]),
+            ...toBytes(vk.gamma_g2[1][1]),
+            ...toBytes(vk.gamma_g2[1][0])
         ],
         delta_g2: [
-            ...toBytes(vk.delta_g2[0][0]),
             ...toBytes(vk.delta_g2[0][1]),
-            ...toBytes(vk.delta_g2[1][0]),
-            ...toBytes(vk.delta_g2[1][1])
+            ...toBytes(vk.delta_g2[0][0]),
+            ...toBytes(vk.delta_g2[1][1]),
+            ...toBytes(vk.delta_g2[1][0])
         ],
         ic: vk.ic.flatMap(point => [
             ...toBytes(point[0]),
@@ -115,8 +118,12 @@ function convertToBytes(vk) {
         ])
     };
     
+    const nrPubinputs = Buffer.alloc(4);
+    nrPubinputs.writeUInt32LE(vk.nPublic);
+    
     // Flatten to single byte array
     return [
+        ...nrPubinputs,
         ...result.alpha_g1,
         ...result.beta_g2,
         ...result.gamma_g2,
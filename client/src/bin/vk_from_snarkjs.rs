@@ -0,0 +1,40 @@
+//! Converts a snarkjs `verification_key.json` for the pool
+//!
+//! Usage: vk_from_snarkjs <verification_key.json> [--rust NAME]
+//!
+//! Prints the verifying key bytes for `initialize` / `propose_verifying_key` as a JSON
+//! array, or with `--rust` a `Groth16Verifyingkey` constant named NAME.
+
+use std::process::exit;
+
+use tornado_solana_client::verifying_key::{verifying_key_bytes, verifying_key_constant};
+
+const USAGE: &str = "usage: vk_from_snarkjs <verification_key.json> [--rust NAME]";
+
+fn main() {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let (path, constant_name) = match args.as_slice() {
+        [path] => (path, None),
+        [path, flag, name] if flag == "--rust" => (path, Some(name)),
+        _ => {
+            eprintln!("{}", USAGE);
+            exit(2);
+        }
+    };
+
+    let json = std::fs::read_to_string(path).unwrap_or_else(|error| {
+        eprintln!("error: cannot read {}: {}", path, error);
+        exit(1);
+    });
+    let output = verifying_key_bytes(&json).and_then(|bytes| match constant_name {
+        Some(name) => verifying_key_constant(&bytes, name),
+        None => Ok(serde_json::to_string(&bytes).expect("byte arrays serialize")),
+    });
+    match output {
+        Ok(output) => println!("{}", output),
+        Err(error) => {
+            eprintln!("error: {}: {}", path, error);
+            exit(1);
+        }
+    }
+}
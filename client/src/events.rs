@@ -0,0 +1,103 @@
+//! Deposit events from transaction logs
+//!
+//! Anchor emits events as `Program data: <base64>` log lines. Only lines logged while
+//! this program is the executing program are read, so another program invoked in the
+//! same transaction cannot inject deposits. `DepositEvent` does not name its pool: feed
+//! logs of transactions that touched one pool's state account, and let
+//! `DepositTree::check_against` confirm the result.
+
+use anchor_lang::{AnchorDeserialize, Discriminator};
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use tornado_solana::DepositEvent;
+
+const PROGRAM_DATA: &str = "Program data: ";
+
+/// Deposit events logged by this program, in log order
+pub fn deposit_events_from_logs<S: AsRef<str>>(logs: &[S]) -> Vec<DepositEvent> {
+    let program_id = crate::PROGRAM_ID.to_string();
+    let mut invoked: Vec<&str> = Vec::new();
+    let mut events = Vec::new();
+
+    for line in logs.iter().map(AsRef::as_ref) {
+        if let Some(data) = line.strip_prefix(PROGRAM_DATA) {
+            if invoked.last() == Some(&program_id.as_str()) {
+                events.extend(decode_deposit_event(data));
+            }
+            continue;
+        }
+
+        // "Program <id> invoke [depth]" / "Program <id> success" / "Program <id> failed: ..."
+        let mut words = line.split(' ');
+        if let (Some("Program"), Some(id), Some(status)) = (words.next(), words.next(), words.next()) {
+            match status {
+                "invoke" => invoked.push(id),
+                "success" | "failed:" => {
+                    invoked.pop();
+                }
+                _ => {}
+            }
+        }
+    }
+    events
+}
+
+/// Decode one `Program data:` payload if it is a DepositEvent
+pub fn decode_deposit_event(data: &str) -> Option<DepositEvent> {
+    let bytes = STANDARD.decode(data).ok()?;
+    let payload = bytes.strip_prefix(&DepositEvent::DISCRIMINATOR[..])?;
+    DepositEvent::try_from_slice(payload).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anchor_lang::Event;
+
+    fn data_line(event: &DepositEvent) -> String {
+        format!("{}{}", PROGRAM_DATA, STANDARD.encode(event.data()))
+    }
+
+    fn deposit(leaf_index: u32) -> DepositEvent {
+        DepositEvent { commitment: [leaf_index as u8 + 1; 32], leaf_index, timestamp: 1_700_000_000 }
+    }
+
+    #[test]
+    fn test_only_this_programs_events_are_read() {
+        let program = crate::PROGRAM_ID.to_string();
+        let other = anchor_lang::prelude::Pubkey::new_unique().to_string();
+        let logs = vec![
+            format!("Program {} invoke [1]", program),
+            "Program log: Instruction: DepositBatch".to_string(),
+            data_line(&deposit(0)),
+            format!("Program {} invoke [2]", other),
+            data_line(&deposit(7)),
+            format!("Program {} success", other),
+            data_line(&deposit(1)),
+            "Program data: not base64!".to_string(),
+            format!("Program {} consumed 50000 of 200000 compute units", program),
+            format!("Program {} success", program),
+            format!("Program {} invoke [1]", other),
+            data_line(&deposit(8)),
+            format!("Program {} failed: custom program error: 0x1", other),
+        ];
+
+        let events = deposit_events_from_logs(&logs);
+        let indices: Vec<u32> = events.iter().map(|event| event.leaf_index).collect();
+        assert_eq!(indices, vec![0, 1]);
+        assert_eq!(events[1].commitment, [2u8; 32]);
+        assert_eq!(events[1].timestamp, 1_700_000_000);
+    }
+
+    #[test]
+    fn test_other_events_ignored() {
+        let withdrawal = tornado_solana::WithdrawalEvent {
+            to: anchor_lang::prelude::Pubkey::new_unique(),
+            nullifier_hash: [3u8; 32],
+            relayer: None,
+            fee: 0,
+        };
+        assert!(decode_deposit_event(&STANDARD.encode(withdrawal.data())).is_none());
+        assert_eq!(decode_deposit_event(&STANDARD.encode(deposit(4).data())).unwrap().leaf_index, 4);
+    }
+}
@@ -0,0 +1,394 @@
+//! Typed instruction builders
+//!
+//! Each builder derives the pool's PDAs from its denomination (and mint, for token pools) and serializes the
+//! program's own Anchor instruction and accounts types, so it stays in step with the IDL.
+
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::instruction::Instruction;
+use anchor_lang::{system_program, InstructionData};
+use tornado_solana::{accounts, instruction, ID};
+
+use crate::pda::{
+    nullifier_address, relayer_record_address, state_address, token_state_address, token_vault_address,
+    vault_address, verifying_key_address,
+};
+
+/// Pool parameters of `initialize`; see the program's `initialize` docs for their bounds
+#[derive(Clone, Debug)]
+pub struct InitializeParams {
+    pub denomination: u64,
+    pub verifying_key: Vec<u8>,
+    pub merkle_tree_height: u32,
+    pub root_history_size: u32,
+    pub max_fee_bps: u16,
+    pub max_fee_amount: u64,
+}
+
+/// Public inputs and payees of a `withdraw`, matching the proof being submitted
+#[derive(Clone, Debug)]
+pub struct WithdrawParams {
+    pub denomination: u64,
+    pub proof: Vec<u8>,
+    pub root: [u8; 32],
+    pub nullifier_hash: [u8; 32],
+    pub recipient: Pubkey,
+    pub relayer: Option<Pubkey>,
+    pub fee: u64,
+    pub refund: u64,
+    /// Pass the relayer's RelayerRecord, required by pools that only pay registered relayers
+    pub use_relayer_record: bool,
+}
+
+/// SPL token pool, identified by its mint and denomination
+#[derive(Clone, Copy, Debug)]
+pub struct TokenPool {
+    pub mint: Pubkey,
+    /// In the mint's base units
+    pub denomination: u64,
+    /// Token or Token-2022, whichever owns the mint
+    pub token_program: Pubkey,
+}
+
+/// Public inputs and payees of a `withdraw_token`, matching the proof being submitted
+/// The proof binds wallets; the token accounts must belong to `recipient` / `relayer`
+#[derive(Clone, Debug)]
+pub struct WithdrawTokenParams {
+    pub pool: TokenPool,
+    pub proof: Vec<u8>,
+    pub root: [u8; 32],
+    pub nullifier_hash: [u8; 32],
+    pub recipient: Pubkey,
+    pub recipient_token_account: Pubkey,
+    pub relayer: Option<Pubkey>,
+    /// Receives the fee; required when `fee` is non-zero
+    pub relayer_token_account: Option<Pubkey>,
+    pub fee: u64,
+    pub refund: u64,
+    /// Pass the relayer's RelayerRecord, required by pools that only pay registered relayers
+    pub use_relayer_record: bool,
+}
+
+/// Create a SOL pool, its verifying key account and its vault, paid for by `authority`
+pub fn initialize(authority: &Pubkey, params: InitializeParams) -> Instruction {
+    let (tornado_state, _) = state_address(params.denomination);
+
+    Instruction {
+        program_id: ID,
+        accounts: accounts::Initialize {
+            tornado_state,
+            verifying_key_account: verifying_key_address(&tornado_state).0,
+            vault: vault_address(&tornado_state).0,
+            authority: *authority,
+            system_program: system_program::ID,
+        }
+        .to_account_metas(None),
+        data: instruction::Initialize {
+            denomination: params.denomination,
+            verifying_key: params.verifying_key,
+            merkle_tree_height: params.merkle_tree_height,
+            root_history_size: params.root_history_size,
+            max_fee_bps: params.max_fee_bps,
+            max_fee_amount: params.max_fee_amount,
+        }
+        .data(),
+    }
+}
+
+/// Deposit one note's commitment into the pool of `denomination`
+pub fn deposit(depositor: &Pubkey, denomination: u64, commitment: [u8; 32]) -> Instruction {
+    let (tornado_state, _) = state_address(denomination);
+
+    Instruction {
+        program_id: ID,
+        accounts: accounts::Deposit {
+            tornado_state,
+            vault: vault_address(&tornado_state).0,
+            depositor: *depositor,
+            system_program: system_program::ID,
+        }
+        .to_account_metas(None),
+        data: instruction::Deposit { commitment }.data(),
+    }
+}
+
+/// Withdraw a note; `payer` funds the nullifier PDA and any refund
+pub fn withdraw(payer: &Pubkey, params: WithdrawParams) -> Instruction {
+    let (tornado_state, _) = state_address(params.denomination);
+    let relayer_record = match (params.use_relayer_record, params.relayer) {
+        (true, Some(relayer)) => Some(relayer_record_address(&tornado_state, &relayer).0),
+        _ => None,
+    };
+
+    Instruction {
+        program_id: ID,
+        accounts: accounts::Withdraw {
+            tornado_state,
+            nullifier: nullifier_address(&tornado_state, &params.nullifier_hash).0,
+            verifying_key_account: verifying_key_address(&tornado_state).0,
+            vault: vault_address(&tornado_state).0,
+            recipient: params.recipient,
+            relayer: params.relayer,
+            payer: *payer,
+            system_program: system_program::ID,
+            relayer_record,
+        }
+        .to_account_metas(None),
+        data: instruction::Withdraw {
+            proof: params.proof,
+            root: params.root,
+            nullifier_hash: params.nullifier_hash,
+            recipient: params.recipient,
+            relayer: params.relayer,
+            fee: params.fee,
+            refund: params.refund,
+        }
+        .data(),
+    }
+}
+
+/// Deposit one note's commitment into a token pool, paid from `depositor_token_account`
+pub fn deposit_token(
+    depositor: &Pubkey,
+    depositor_token_account: &Pubkey,
+    pool: &TokenPool,
+    commitment: [u8; 32],
+) -> Instruction {
+    let (tornado_state, _) = token_state_address(&pool.mint, pool.denomination);
+
+    Instruction {
+        program_id: ID,
+        accounts: accounts::DepositToken {
+            tornado_state,
+            mint: pool.mint,
+            token_vault: token_vault_address(&tornado_state).0,
+            depositor_token_account: *depositor_token_account,
+            depositor: *depositor,
+            token_program: pool.token_program,
+        }
+        .to_account_metas(None),
+        data: instruction::DepositToken { commitment }.data(),
+    }
+}
+
+/// Withdraw a note from a token pool; `payer` funds the nullifier PDA and any refund
+pub fn withdraw_token(payer: &Pubkey, params: WithdrawTokenParams) -> Instruction {
+    let (tornado_state, _) = token_state_address(&params.pool.mint, params.pool.denomination);
+    let relayer_record = match (params.use_relayer_record, params.relayer) {
+        (true, Some(relayer)) => Some(relayer_record_address(&tornado_state, &relayer).0),
+        _ => None,
+    };
+
+    Instruction {
+        program_id: ID,
+        accounts: accounts::WithdrawToken {
+            tornado_state,
+            nullifier: nullifier_address(&tornado_state, &params.nullifier_hash).0,
+            verifying_key_account: verifying_key_address(&tornado_state).0,
+            mint: params.pool.mint,
+            token_vault: token_vault_address(&tornado_state).0,
+            recipient: params.recipient,
+            recipient_token_account: params.recipient_token_account,
+            relayer_token_account: params.relayer_token_account,
+            payer: *payer,
+            token_program: params.pool.token_program,
+            system_program: system_program::ID,
+            relayer_record,
+        }
+        .to_account_metas(None),
+        data: instruction::WithdrawToken {
+            proof: params.proof,
+            root: params.root,
+            nullifier_hash: params.nullifier_hash,
+            recipient: params.recipient,
+            relayer: params.relayer,
+            fee: params.fee,
+            refund: params.refund,
+        }
+        .data(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anchor_lang::Discriminator;
+
+    const DENOMINATION: u64 = 1_000_000_000;
+
+    fn withdraw_params(relayer: Option<Pubkey>, use_relayer_record: bool) -> WithdrawParams {
+        WithdrawParams {
+            denomination: DENOMINATION,
+            proof: vec![7u8; 256],
+            root: [1u8; 32],
+            nullifier_hash: [2u8; 32],
+            recipient: Pubkey::new_unique(),
+            relayer,
+            fee: 5_000,
+            refund: 0,
+            use_relayer_record,
+        }
+    }
+
+    #[test]
+    fn test_deposit_instruction() {
+        let depositor = Pubkey::new_unique();
+        let ix = deposit(&depositor, DENOMINATION, [9u8; 32]);
+        let (tornado_state, _) = state_address(DENOMINATION);
+
+        assert_eq!(ix.program_id, ID);
+        let keys: Vec<Pubkey> = ix.accounts.iter().map(|meta| meta.pubkey).collect();
+        assert_eq!(
+            keys,
+            vec![tornado_state, vault_address(&tornado_state).0, depositor, system_program::ID]
+        );
+        assert!(ix.accounts[2].is_signer && ix.accounts[2].is_writable);
+
+        assert_eq!(ix.data[..8], instruction::Deposit::DISCRIMINATOR);
+        assert_eq!(ix.data[8..], [9u8; 32]);
+    }
+
+    #[test]
+    fn test_withdraw_instruction_accounts() {
+        let payer = Pubkey::new_unique();
+        let relayer = Pubkey::new_unique();
+        let params = withdraw_params(Some(relayer), true);
+        let ix = withdraw(&payer, params.clone());
+        let (tornado_state, _) = state_address(DENOMINATION);
+
+        let keys: Vec<Pubkey> = ix.accounts.iter().map(|meta| meta.pubkey).collect();
+        assert_eq!(
+            keys,
+            vec![
+                tornado_state,
+                nullifier_address(&tornado_state, &params.nullifier_hash).0,
+                verifying_key_address(&tornado_state).0,
+                vault_address(&tornado_state).0,
+                params.recipient,
+                relayer,
+                payer,
+                system_program::ID,
+                relayer_record_address(&tornado_state, &relayer).0,
+            ]
+        );
+
+        let data = instruction::Withdraw {
+            proof: params.proof,
+            root: params.root,
+            nullifier_hash: params.nullifier_hash,
+            recipient: params.recipient,
+            relayer: params.relayer,
+            fee: params.fee,
+            refund: params.refund,
+        }
+        .data();
+        assert_eq!(ix.data, data);
+    }
+
+    #[test]
+    fn test_withdraw_without_relayer_uses_placeholders() {
+        // Anchor marks omitted optional accounts with the program ID
+        let ix = withdraw(&Pubkey::new_unique(), withdraw_params(None, true));
+        assert_eq!(ix.accounts.len(), 9);
+        assert_eq!(ix.accounts[5].pubkey, ID);
+        assert_eq!(ix.accounts[8].pubkey, ID);
+    }
+
+    fn token_pool() -> TokenPool {
+        TokenPool {
+            mint: Pubkey::new_unique(),
+            denomination: 100_000_000,
+            token_program: Pubkey::new_unique(),
+        }
+    }
+
+    #[test]
+    fn test_token_pools_are_keyed_by_mint_and_denomination() {
+        let pool = token_pool();
+        let (tornado_state, _) = token_state_address(&pool.mint, pool.denomination);
+
+        assert_ne!(tornado_state, token_state_address(&Pubkey::new_unique(), pool.denomination).0);
+        assert_ne!(tornado_state, token_state_address(&pool.mint, pool.denomination * 10).0);
+        assert_ne!(tornado_state, state_address(pool.denomination).0);
+        assert_ne!(token_vault_address(&tornado_state).0, vault_address(&tornado_state).0);
+    }
+
+    #[test]
+    fn test_deposit_token_instruction() {
+        let pool = token_pool();
+        let depositor = Pubkey::new_unique();
+        let depositor_token_account = Pubkey::new_unique();
+        let ix = deposit_token(&depositor, &depositor_token_account, &pool, [9u8; 32]);
+        let (tornado_state, _) = token_state_address(&pool.mint, pool.denomination);
+
+        assert_eq!(ix.program_id, ID);
+        let keys: Vec<Pubkey> = ix.accounts.iter().map(|meta| meta.pubkey).collect();
+        assert_eq!(
+            keys,
+            vec![
+                tornado_state,
+                pool.mint,
+                token_vault_address(&tornado_state).0,
+                depositor_token_account,
+                depositor,
+                pool.token_program,
+            ]
+        );
+        assert!(ix.accounts[2].is_writable && ix.accounts[3].is_writable);
+        assert!(ix.accounts[4].is_signer);
+
+        assert_eq!(ix.data[..8], instruction::DepositToken::DISCRIMINATOR);
+        assert_eq!(ix.data[8..], [9u8; 32]);
+    }
+
+    #[test]
+    fn test_withdraw_token_instruction_accounts() {
+        let payer = Pubkey::new_unique();
+        let relayer = Pubkey::new_unique();
+        let params = WithdrawTokenParams {
+            pool: token_pool(),
+            proof: vec![7u8; 256],
+            root: [1u8; 32],
+            nullifier_hash: [2u8; 32],
+            recipient: Pubkey::new_unique(),
+            recipient_token_account: Pubkey::new_unique(),
+            relayer: Some(relayer),
+            relayer_token_account: Some(Pubkey::new_unique()),
+            fee: 5_000,
+            refund: 0,
+            use_relayer_record: true,
+        };
+        let ix = withdraw_token(&payer, params.clone());
+        let (tornado_state, _) = token_state_address(&params.pool.mint, params.pool.denomination);
+
+        let keys: Vec<Pubkey> = ix.accounts.iter().map(|meta| meta.pubkey).collect();
+        assert_eq!(
+            keys,
+            vec![
+                tornado_state,
+                nullifier_address(&tornado_state, &params.nullifier_hash).0,
+                verifying_key_address(&tornado_state).0,
+                params.pool.mint,
+                token_vault_address(&tornado_state).0,
+                params.recipient,
+                params.recipient_token_account,
+                params.relayer_token_account.unwrap(),
+                payer,
+                params.pool.token_program,
+                system_program::ID,
+                relayer_record_address(&tornado_state, &relayer).0,
+            ]
+        );
+
+        let data = instruction::WithdrawToken {
+            proof: params.proof,
+            root: params.root,
+            nullifier_hash: params.nullifier_hash,
+            recipient: params.recipient,
+            relayer: params.relayer,
+            fee: params.fee,
+            refund: params.refund,
+        }
+        .data();
+        assert_eq!(ix.data, data);
+    }
+}
@@ -0,0 +1,25 @@
+//! Rust client for the tornado-solana program
+//!
+//! - [`note`]: create notes and compute their commitment and nullifier hash
+//! - [`note_string`]: the canonical, checksummed text form of a note
+//! - [`pda`]: derive SOL and token pool, vault, verifying key and nullifier addresses
+//! - [`instructions`]: typed builders for `initialize`, `deposit`, `withdraw`, `deposit_token` and `withdraw_token`
+//! - [`events`], [`tree`]: rebuild a pool's Merkle tree from its deposit logs for withdrawal paths
+//! - [`verifying_key`]: convert a snarkjs `verification_key.json` into the pool's verifying key bytes
+//!
+//! Hashing reuses the program's own `MerkleTree::hash_left_right`/`hash_leaf`, so client
+//! commitments always match what the pool and the circuit compute.
+
+pub mod events;
+pub mod instructions;
+pub mod note;
+pub mod note_string;
+pub mod pda;
+pub mod tree;
+pub mod verifying_key;
+
+pub use note::Note;
+pub use note_string::{NoteStringError, PoolNote};
+pub use tree::{DepositTree, MerklePath, TreeError};
+pub use verifying_key::{verifying_key_bytes, SnarkjsVerifyingKey, VerifyingKeyError};
+pub use tornado_solana::ID as PROGRAM_ID;
@@ -0,0 +1,114 @@
+//! Deposit notes
+//!
+//! A note is the `(nullifier, secret)` pair a depositor keeps to withdraw later:
+//! - commitment = Poseidon(nullifier, secret), inserted into the pool's tree on deposit
+//! - nullifierHash = Poseidon(nullifier), revealed on withdrawal to mark the note spent
+
+use anchor_lang::prelude::*;
+use rand::rngs::OsRng;
+use rand::{CryptoRng, RngCore};
+use tornado_solana::merkle_tree::MerkleTree;
+
+// Random bytes per note value; 31 bytes always fit below the BN254 scalar field modulus
+const NOTE_VALUE_BYTES: usize = 31;
+
+/// Private values of a deposit, as 32-byte big-endian field elements
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Note {
+    pub nullifier: [u8; 32],
+    pub secret: [u8; 32],
+}
+
+impl Note {
+    /// Fresh note from the operating system's RNG
+    pub fn generate() -> Self {
+        Self::generate_with(&mut OsRng)
+    }
+
+    /// Fresh note from a caller-provided cryptographic RNG
+    pub fn generate_with<R: RngCore + CryptoRng>(rng: &mut R) -> Self {
+        Self {
+            nullifier: random_field_element(rng),
+            secret: random_field_element(rng),
+        }
+    }
+
+    /// Leaf to pass to `deposit`
+    /// Fails with HashFailure if either value is not a canonical field element
+    pub fn commitment(&self) -> Result<[u8; 32]> {
+        MerkleTree::hash_left_right(&self.nullifier, &self.secret)
+    }
+
+    /// Public nullifier hash of the withdrawal proof; also seeds the nullifier PDA
+    pub fn nullifier_hash(&self) -> Result<[u8; 32]> {
+        MerkleTree::hash_leaf(&self.nullifier)
+    }
+}
+
+fn random_field_element<R: RngCore + CryptoRng>(rng: &mut R) -> [u8; 32] {
+    let mut value = [0u8; 32];
+    rng.fill_bytes(&mut value[32 - NOTE_VALUE_BYTES..]);
+    value
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn field_element(low: u16) -> [u8; 32] {
+        let mut value = [0u8; 32];
+        value[30..].copy_from_slice(&low.to_be_bytes());
+        value
+    }
+
+    #[test]
+    fn test_hashes_match_circomlibjs() {
+        // Vectors from poseidon_test.rs, produced by circomlibjs
+        let note = Note {
+            nullifier: field_element(1),
+            secret: field_element(2),
+        };
+        assert_eq!(
+            hex::encode(note.commitment().unwrap()),
+            "115cc0f5e7d690413df64c6b9662e9cf2a3617f2743245519e19607a4417189a"
+        );
+
+        let note = Note {
+            nullifier: [
+                0x12, 0x34, 0x56, 0x78, 0x90, 0xab, 0xcd, 0xef,
+                0x12, 0x34, 0x56, 0x78, 0x90, 0xab, 0xcd, 0xef,
+                0x12, 0x34, 0x56, 0x78, 0x90, 0xab, 0xcd, 0xef,
+                0x12, 0x34, 0x56, 0x78, 0x90, 0xab, 0xcd, 0xef,
+            ],
+            secret: [0u8; 32],
+        };
+        assert_eq!(
+            hex::encode(note.nullifier_hash().unwrap()),
+            "239edbf1e6b4f5646471d24e63b1ab7992897e0ecefa6b565302f64fe1e49117"
+        );
+    }
+
+    #[test]
+    fn test_generated_notes_are_distinct_and_hashable() {
+        let a = Note::generate();
+        let b = Note::generate();
+        assert_ne!(a, b);
+        assert_ne!(a.nullifier, a.secret);
+
+        // Leading byte stays zero, so the values are always in the field
+        assert_eq!(a.nullifier[0], 0);
+        assert_eq!(a.secret[0], 0);
+        assert!(a.commitment().is_ok());
+        assert!(a.nullifier_hash().is_ok());
+    }
+
+    #[test]
+    fn test_non_canonical_note_rejected() {
+        let note = Note {
+            nullifier: [0xff; 32],
+            secret: field_element(1),
+        };
+        assert!(note.commitment().is_err());
+        assert!(note.nullifier_hash().is_err());
+    }
+}
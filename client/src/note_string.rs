@@ -0,0 +1,314 @@
+//! Canonical note string
+//!
+//! `tornado-sol-v1-<program_id>-<pool>-<denomination>-<nullifier><secret>-<checksum>`
+//!
+//! - `program_id`, `pool`: base58 addresses; `pool` is the pool's TornadoState account
+//! - `denomination`: decimal base units, no sign or leading zeros
+//! - `nullifier`, `secret`: 32-byte big-endian field elements, 64 lowercase hex digits each
+//! - `checksum`: first 4 bytes of SHA-256 over everything before `-<checksum>`, 8 lowercase hex digits
+//!
+//! Golden vectors for other implementations live in `client/test_vectors/note_strings.json`.
+
+use std::fmt;
+use std::str::FromStr;
+
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::hash::hash;
+use tornado_solana::BN254_SCALAR_FIELD_MODULUS;
+
+use crate::note::Note;
+use crate::pda::state_address;
+
+pub const NOTE_PREFIX: &str = "tornado-sol";
+pub const NOTE_VERSION: &str = "v1";
+const CHECKSUM_LEN: usize = 4;
+
+/// Why a note string was rejected
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum NoteStringError {
+    /// Not `tornado-sol-...` or not the expected number of `-` separated fields
+    InvalidFormat,
+    UnsupportedVersion(String),
+    InvalidProgramId,
+    InvalidPool,
+    InvalidDenomination,
+    /// Secret part is not 128 lowercase hex digits
+    InvalidSecretEncoding,
+    /// Nullifier or secret is not below the BN254 scalar field modulus
+    NotInField,
+    InvalidChecksumEncoding,
+    ChecksumMismatch,
+}
+
+impl fmt::Display for NoteStringError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::InvalidFormat => write!(f, "not a {}-{} note string", NOTE_PREFIX, NOTE_VERSION),
+            Self::UnsupportedVersion(version) => write!(f, "unsupported note version {:?}", version),
+            Self::InvalidProgramId => write!(f, "invalid program ID"),
+            Self::InvalidPool => write!(f, "invalid pool address"),
+            Self::InvalidDenomination => write!(f, "invalid denomination"),
+            Self::InvalidSecretEncoding => write!(f, "nullifier and secret must be 128 lowercase hex digits"),
+            Self::NotInField => write!(f, "nullifier or secret is outside the BN254 scalar field"),
+            Self::InvalidChecksumEncoding => write!(f, "checksum must be 8 lowercase hex digits"),
+            Self::ChecksumMismatch => write!(f, "checksum mismatch"),
+        }
+    }
+}
+
+impl std::error::Error for NoteStringError {}
+
+/// A note together with the pool it was deposited into
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct PoolNote {
+    pub program_id: Pubkey,
+    pub pool: Pubkey,
+    pub denomination: u64,
+    pub note: Note,
+}
+
+impl PoolNote {
+    /// Note for this program's SOL pool of `denomination`
+    pub fn new(denomination: u64, note: Note) -> Self {
+        Self {
+            program_id: crate::PROGRAM_ID,
+            pool: state_address(denomination).0,
+            denomination,
+            note,
+        }
+    }
+
+    pub fn encode(&self) -> String {
+        let body = format!(
+            "{}-{}-{}-{}-{}-{}{}",
+            NOTE_PREFIX,
+            NOTE_VERSION,
+            self.program_id,
+            self.pool,
+            self.denomination,
+            to_hex(&self.note.nullifier),
+            to_hex(&self.note.secret),
+        );
+        let checksum = to_hex(&checksum(&body));
+        format!("{}-{}", body, checksum)
+    }
+
+    /// Strict inverse of `encode`: any string `encode` cannot produce is rejected
+    pub fn decode(encoded: &str) -> std::result::Result<Self, NoteStringError> {
+        let (body, checksum_hex) = encoded.rsplit_once('-').ok_or(NoteStringError::InvalidFormat)?;
+        let fields: Vec<&str> = body.split('-').collect();
+        let [tornado, sol, version, program_id, pool, denomination, secrets] = fields[..] else {
+            return Err(NoteStringError::InvalidFormat);
+        };
+        if format!("{}-{}", tornado, sol) != NOTE_PREFIX {
+            return Err(NoteStringError::InvalidFormat);
+        }
+        if version != NOTE_VERSION {
+            return Err(NoteStringError::UnsupportedVersion(version.to_string()));
+        }
+
+        let expected: [u8; CHECKSUM_LEN] =
+            from_hex(checksum_hex).ok_or(NoteStringError::InvalidChecksumEncoding)?;
+        if expected != checksum(body) {
+            return Err(NoteStringError::ChecksumMismatch);
+        }
+
+        let program_id = parse_pubkey(program_id).ok_or(NoteStringError::InvalidProgramId)?;
+        let pool = parse_pubkey(pool).ok_or(NoteStringError::InvalidPool)?;
+        let denomination = parse_denomination(denomination).ok_or(NoteStringError::InvalidDenomination)?;
+
+        if secrets.len() != 128 {
+            return Err(NoteStringError::InvalidSecretEncoding);
+        }
+        let (nullifier, secret) = secrets.split_at(64);
+        let note = Note {
+            nullifier: from_hex(nullifier).ok_or(NoteStringError::InvalidSecretEncoding)?,
+            secret: from_hex(secret).ok_or(NoteStringError::InvalidSecretEncoding)?,
+        };
+        if !is_in_field(&note.nullifier) || !is_in_field(&note.secret) {
+            return Err(NoteStringError::NotInField);
+        }
+
+        Ok(Self {
+            program_id,
+            pool,
+            denomination,
+            note,
+        })
+    }
+}
+
+impl fmt::Display for PoolNote {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.encode())
+    }
+}
+
+impl FromStr for PoolNote {
+    type Err = NoteStringError;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        Self::decode(s)
+    }
+}
+
+fn checksum(body: &str) -> [u8; CHECKSUM_LEN] {
+    let mut checksum = [0u8; CHECKSUM_LEN];
+    checksum.copy_from_slice(&hash(body.as_bytes()).to_bytes()[..CHECKSUM_LEN]);
+    checksum
+}
+
+fn is_in_field(value: &[u8; 32]) -> bool {
+    *value < BN254_SCALAR_FIELD_MODULUS
+}
+
+/// Base58 address that re-encodes to the same text
+fn parse_pubkey(text: &str) -> Option<Pubkey> {
+    let pubkey = Pubkey::from_str(text).ok()?;
+    (pubkey.to_string() == text).then_some(pubkey)
+}
+
+/// Non-zero decimal that re-encodes to the same text
+fn parse_denomination(text: &str) -> Option<u64> {
+    let denomination: u64 = text.parse().ok()?;
+    (denomination > 0 && denomination.to_string() == text).then_some(denomination)
+}
+
+fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{:02x}", byte)).collect()
+}
+
+/// Lowercase hex of exactly N bytes
+fn from_hex<const N: usize>(text: &str) -> Option<[u8; N]> {
+    if text.len() != 2 * N || !text.bytes().all(|c| matches!(c, b'0'..=b'9' | b'a'..=b'f')) {
+        return None;
+    }
+    let mut bytes = [0u8; N];
+    for (byte, pair) in bytes.iter_mut().zip(text.as_bytes().chunks(2)) {
+        *byte = u8::from_str_radix(std::str::from_utf8(pair).ok()?, 16).ok()?;
+    }
+    Some(bytes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample() -> PoolNote {
+        let mut nullifier = [0u8; 32];
+        let mut secret = [0u8; 32];
+        nullifier[1..].copy_from_slice(&[0x11; 31]);
+        secret[1..].copy_from_slice(&[0x22; 31]);
+        PoolNote {
+            program_id: Pubkey::new_from_array([1u8; 32]),
+            pool: Pubkey::new_from_array([2u8; 32]),
+            denomination: 1_000_000_000,
+            note: Note { nullifier, secret },
+        }
+    }
+
+    /// Re-encode `encoded` with a body edit applied and a freshly computed checksum
+    fn with_body(encoded: &str, edit: impl Fn(&str) -> String) -> String {
+        let (body, _) = encoded.rsplit_once('-').unwrap();
+        let body = edit(body);
+        let checksum = to_hex(&checksum(&body));
+        format!("{}-{}", body, checksum)
+    }
+
+    #[test]
+    fn test_golden_vectors() {
+        let vectors: serde_json::Value =
+            serde_json::from_str(include_str!("../test_vectors/note_strings.json")).unwrap();
+
+        for vector in vectors["vectors"].as_array().unwrap() {
+            let text = |key: &str| vector[key].as_str().unwrap().to_string();
+            let note = PoolNote {
+                program_id: Pubkey::from_str(&text("program_id")).unwrap(),
+                pool: Pubkey::from_str(&text("pool")).unwrap(),
+                denomination: text("denomination").parse().unwrap(),
+                note: Note {
+                    nullifier: from_hex(&text("nullifier")).unwrap(),
+                    secret: from_hex(&text("secret")).unwrap(),
+                },
+            };
+
+            assert_eq!(note.encode(), text("note"));
+            assert_eq!(PoolNote::decode(&text("note")).unwrap(), note);
+        }
+    }
+
+    #[test]
+    fn test_round_trip() {
+        for note in [sample(), PoolNote::new(100_000_000, Note::generate())] {
+            let encoded = note.to_string();
+            assert!(encoded.starts_with("tornado-sol-v1-"));
+            assert_eq!(encoded.parse::<PoolNote>().unwrap(), note);
+        }
+    }
+
+    #[test]
+    fn test_malformed_strings_rejected() {
+        let encoded = sample().encode();
+        let decode = |text: &str| PoolNote::decode(text).unwrap_err();
+
+        assert_eq!(decode(""), NoteStringError::InvalidFormat);
+        assert_eq!(decode(&encoded.replacen("tornado-sol", "tornado-eth", 1)), NoteStringError::InvalidFormat);
+        assert_eq!(decode(&format!("x{}", encoded)), NoteStringError::InvalidFormat);
+        assert_eq!(decode(&format!("{}-00", encoded)), NoteStringError::InvalidFormat);
+        assert_eq!(
+            decode(&encoded.replacen("-v1-", "-v2-", 1)),
+            NoteStringError::UnsupportedVersion("v2".to_string())
+        );
+
+        // Any edit to the body breaks the checksum
+        assert_eq!(decode(&encoded.replacen("1000000000", "1000000001", 1)), NoteStringError::ChecksumMismatch);
+        let mut flipped = encoded.clone().into_bytes();
+        let last = flipped.len() - 1;
+        flipped[last] = if flipped[last] == b'0' { b'1' } else { b'0' };
+        assert_eq!(decode(&String::from_utf8(flipped).unwrap()), NoteStringError::ChecksumMismatch);
+        let (body, _) = encoded.rsplit_once('-').unwrap();
+        assert_eq!(decode(&format!("{}-ABCDEF12", body)), NoteStringError::InvalidChecksumEncoding);
+        assert_eq!(decode(&encoded[..encoded.len() - 1]), NoteStringError::InvalidChecksumEncoding);
+    }
+
+    #[test]
+    fn test_fields_validated_behind_valid_checksum() {
+        let encoded = sample().encode();
+        let decode = |text: String| PoolNote::decode(&text).unwrap_err();
+        let program_id = Pubkey::new_from_array([1u8; 32]).to_string();
+
+        assert_eq!(
+            decode(with_body(&encoded, |body| body.replacen(&program_id, "0OIl", 1))),
+            NoteStringError::InvalidProgramId
+        );
+        assert_eq!(
+            decode(with_body(&encoded, |body| body.replacen(&program_id, "1", 1))),
+            NoteStringError::InvalidProgramId
+        );
+        let pool = Pubkey::new_from_array([2u8; 32]).to_string();
+        assert_eq!(
+            decode(with_body(&encoded, |body| body.replacen(&pool, &pool[1..], 1))),
+            NoteStringError::InvalidPool
+        );
+        for denomination in ["0", "01000000000", "+1000000000", "18446744073709551616", ""] {
+            assert_eq!(
+                decode(with_body(&encoded, |body| body.replacen("-1000000000-", &format!("-{}-", denomination), 1))),
+                NoteStringError::InvalidDenomination,
+                "{:?}",
+                denomination
+            );
+        }
+        assert_eq!(
+            decode(with_body(&encoded, |body| body.replacen("0011", "0A11", 1))),
+            NoteStringError::InvalidSecretEncoding
+        );
+        assert_eq!(
+            decode(with_body(&encoded, |body| body[..body.len() - 2].to_string())),
+            NoteStringError::InvalidSecretEncoding
+        );
+
+        let mut out_of_field = sample();
+        out_of_field.note.secret = BN254_SCALAR_FIELD_MODULUS;
+        assert_eq!(decode(out_of_field.encode()), NoteStringError::NotInField);
+    }
+}
@@ -0,0 +1,51 @@
+//! Program-derived addresses of a pool and its accounts
+
+use anchor_lang::prelude::*;
+use tornado_solana::relayer_registry::RelayerRecord;
+use tornado_solana::{Nullifier, VerifyingKeyAccount, ID};
+
+/// SOL pool of one denomination: [b"tornado", denomination.to_le_bytes()]
+pub fn state_address(denomination: u64) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[b"tornado", denomination.to_le_bytes().as_ref()], &ID)
+}
+
+/// SPL token pool of one (mint, denomination): [b"tornado", mint, denomination.to_le_bytes()]
+/// `denomination` is in the mint's base units
+pub fn token_state_address(mint: &Pubkey, denomination: u64) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[b"tornado", mint.as_ref(), denomination.to_le_bytes().as_ref()],
+        &ID,
+    )
+}
+
+/// System-owned account holding the pool's lamports: [b"vault", tornado_state]
+pub fn vault_address(tornado_state: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[b"vault", tornado_state.as_ref()], &ID)
+}
+
+/// Token account of a token pool, owned by itself: [b"token_vault", tornado_state]
+pub fn token_vault_address(tornado_state: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[b"token_vault", tornado_state.as_ref()], &ID)
+}
+
+/// Pool's verifying key: [b"verifying_key", tornado_state]
+pub fn verifying_key_address(tornado_state: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[VerifyingKeyAccount::SEED_PREFIX, tornado_state.as_ref()],
+        &ID,
+    )
+}
+
+/// Spent marker of a note: [b"nullifier", tornado_state, nullifier_hash]
+/// The note is spent if and only if this account exists
+pub fn nullifier_address(tornado_state: &Pubkey, nullifier_hash: &[u8; 32]) -> (Pubkey, u8) {
+    Nullifier::derive_pda(tornado_state, nullifier_hash, &ID)
+}
+
+/// Relayer's registration with a pool: [b"relayer", tornado_state, relayer]
+pub fn relayer_record_address(tornado_state: &Pubkey, relayer: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[RelayerRecord::SEED_PREFIX, tornado_state.as_ref(), relayer.as_ref()],
+        &ID,
+    )
+}
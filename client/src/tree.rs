@@ -0,0 +1,390 @@
+//! Off-chain copy of a pool's Merkle tree
+//!
+//! The program only keeps `filled_subtrees`, which is enough to append leaves but not to
+//! prove old ones. `DepositTree` keeps every node, is rebuilt from the pool's
+//! `DepositEvent`s in leaf order, and yields the circuit's `(pathElements, pathIndices)`
+//! for any leaf. Its root can be checked against the pool's account before proving.
+
+use std::fmt;
+
+use tornado_solana::merkle_tree::MerkleTree;
+use tornado_solana::zero_hashes::ZERO_HASHES;
+use tornado_solana::{DepositEvent, TornadoState, MAX_MERKLE_TREE_HEIGHT};
+
+/// Why a leaf could not be added or a path or root check failed
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum TreeError {
+    /// Events are missing before `found`; the tree has `expected` leaves
+    Gap { expected: u32, found: u32 },
+    /// A different commitment was already ingested at `leaf_index`
+    ConflictingLeaf { leaf_index: u32 },
+    TreeFull,
+    UnknownLeaf { leaf_index: u32 },
+    /// A commitment is not a canonical field element
+    HashFailure,
+    LevelsMismatch { tree: u32, chain: u32 },
+    /// The tree holds leaves the pool has not inserted
+    AheadOfChain { tree: u32, chain: u32 },
+    /// Same number of leaves as the pool, different root
+    RootMismatch,
+    /// Fewer leaves than the pool and a root outside its root history
+    UnknownRoot,
+    InvalidAccountData,
+}
+
+impl fmt::Display for TreeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Gap { expected, found } => {
+                write!(f, "missing deposits: expected leaf {}, got leaf {}", expected, found)
+            }
+            Self::ConflictingLeaf { leaf_index } => {
+                write!(f, "conflicting commitments for leaf {}", leaf_index)
+            }
+            Self::TreeFull => write!(f, "tree is full"),
+            Self::UnknownLeaf { leaf_index } => write!(f, "leaf {} has not been ingested", leaf_index),
+            Self::HashFailure => write!(f, "commitment is not a canonical field element"),
+            Self::LevelsMismatch { tree, chain } => {
+                write!(f, "tree has {} levels, pool has {}", tree, chain)
+            }
+            Self::AheadOfChain { tree, chain } => {
+                write!(f, "tree has {} leaves, pool only {}", tree, chain)
+            }
+            Self::RootMismatch => write!(f, "root differs from the pool's current root"),
+            Self::UnknownRoot => write!(f, "root is not in the pool's root history"),
+            Self::InvalidAccountData => write!(f, "not a TornadoState account"),
+        }
+    }
+}
+
+impl std::error::Error for TreeError {}
+
+/// Private Merkle inputs of the withdraw circuit, leaf level first
+/// `path_indices[i]` is 1 when the node at level `i` is a right child
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct MerklePath {
+    pub path_elements: Vec<[u8; 32]>,
+    pub path_indices: Vec<u8>,
+}
+
+impl MerklePath {
+    /// Root obtained by hashing `leaf` up this path, as the circuit does
+    pub fn compute_root(&self, leaf: &[u8; 32]) -> Result<[u8; 32], TreeError> {
+        self.path_elements
+            .iter()
+            .zip(&self.path_indices)
+            .try_fold(*leaf, |node, (sibling, &is_right)| {
+                let (left, right) = if is_right == 1 { (sibling, &node) } else { (&node, sibling) };
+                MerkleTree::hash_left_right(left, right).map_err(|_| TreeError::HashFailure)
+            })
+    }
+}
+
+/// Every node of a pool's tree; missing nodes are empty subtrees
+#[derive(Clone, Debug)]
+pub struct DepositTree {
+    levels: u32,
+    // layers[level][index]: levels 0..levels, leaves first; the root is kept separately
+    layers: Vec<Vec<[u8; 32]>>,
+    root: [u8; 32],
+}
+
+impl DepositTree {
+    /// Empty tree of the pool's depth (`MerkleTree::levels`)
+    pub fn new(levels: u32) -> Self {
+        assert!(
+            levels > 0 && levels <= MAX_MERKLE_TREE_HEIGHT,
+            "tree depth must be between 1 and MAX_MERKLE_TREE_HEIGHT"
+        );
+        Self {
+            levels,
+            layers: vec![Vec::new(); levels as usize],
+            // Same value as a freshly initialized pool
+            root: MerkleTree::with_levels(levels).get_root(),
+        }
+    }
+
+    /// Rebuild from a pool's deposit events, in leaf order
+    pub fn from_events<'a>(
+        levels: u32,
+        events: impl IntoIterator<Item = &'a DepositEvent>,
+    ) -> Result<Self, TreeError> {
+        let mut tree = Self::new(levels);
+        for event in events {
+            tree.ingest(event)?;
+        }
+        Ok(tree)
+    }
+
+    pub fn levels(&self) -> u32 {
+        self.levels
+    }
+
+    /// Number of leaves ingested so far
+    pub fn len(&self) -> u32 {
+        self.layers[0].len() as u32
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.layers[0].is_empty()
+    }
+
+    pub fn root(&self) -> [u8; 32] {
+        self.root
+    }
+
+    pub fn leaf(&self, leaf_index: u32) -> Option<[u8; 32]> {
+        self.layers[0].get(leaf_index as usize).copied()
+    }
+
+    /// Leaf index of a commitment, to find one's own deposit
+    pub fn position(&self, commitment: &[u8; 32]) -> Option<u32> {
+        self.layers[0].iter().position(|leaf| leaf == commitment).map(|index| index as u32)
+    }
+
+    /// Append the event's leaf. Events must arrive in leaf order; an event that was already
+    /// ingested is accepted again unchanged, so overlapping log pages can be replayed
+    pub fn ingest(&mut self, event: &DepositEvent) -> Result<(), TreeError> {
+        let expected = self.len();
+        if event.leaf_index < expected {
+            return match self.leaf(event.leaf_index) == Some(event.commitment) {
+                true => Ok(()),
+                false => Err(TreeError::ConflictingLeaf { leaf_index: event.leaf_index }),
+            };
+        }
+        if event.leaf_index > expected {
+            return Err(TreeError::Gap { expected, found: event.leaf_index });
+        }
+        self.push(event.commitment)
+    }
+
+    /// Append a leaf, hashing its path to the root
+    pub fn push(&mut self, leaf: [u8; 32]) -> Result<(), TreeError> {
+        if u64::from(self.len()) >= 1_u64 << self.levels {
+            return Err(TreeError::TreeFull);
+        }
+
+        // Hash first so a bad leaf leaves the tree untouched
+        let mut index = self.layers[0].len();
+        let mut node = leaf;
+        let mut path = Vec::with_capacity(self.levels as usize);
+        for level in 0..self.levels as usize {
+            path.push(node);
+            let sibling = self.node(level, index ^ 1);
+            let (left, right) = if index % 2 == 1 { (&sibling, &node) } else { (&node, &sibling) };
+            node = MerkleTree::hash_left_right(left, right).map_err(|_| TreeError::HashFailure)?;
+            index /= 2;
+        }
+
+        let mut index = self.layers[0].len();
+        for (layer, node) in self.layers.iter_mut().zip(path) {
+            match layer.get_mut(index) {
+                Some(slot) => *slot = node,
+                None => layer.push(node),
+            }
+            index /= 2;
+        }
+        self.root = node;
+        Ok(())
+    }
+
+    /// Sibling path of an ingested leaf against the current root
+    pub fn path(&self, leaf_index: u32) -> Result<MerklePath, TreeError> {
+        if leaf_index >= self.len() {
+            return Err(TreeError::UnknownLeaf { leaf_index });
+        }
+
+        let mut index = leaf_index as usize;
+        let mut path_elements = Vec::with_capacity(self.levels as usize);
+        let mut path_indices = Vec::with_capacity(self.levels as usize);
+        for level in 0..self.levels as usize {
+            path_elements.push(self.node(level, index ^ 1));
+            path_indices.push((index % 2) as u8);
+            index /= 2;
+        }
+        Ok(MerklePath { path_elements, path_indices })
+    }
+
+    /// Check this tree against the pool's state and root history:
+    /// - same number of leaves: the root must be the pool's current root
+    /// - fewer leaves: the root must still be in the root history, so proofs against it verify
+    pub fn check_against(&self, state: &TornadoState, roots: &[[u8; 32]]) -> Result<(), TreeError> {
+        let chain = &state.merkle_tree;
+        if chain.levels != self.levels {
+            return Err(TreeError::LevelsMismatch { tree: self.levels, chain: chain.levels });
+        }
+        if self.len() > chain.next_index {
+            return Err(TreeError::AheadOfChain { tree: self.len(), chain: chain.next_index });
+        }
+        if self.len() == chain.next_index {
+            return match chain.get_root() == self.root {
+                true => Ok(()),
+                false => Err(TreeError::RootMismatch),
+            };
+        }
+        match self.root != [0u8; 32] && roots.contains(&self.root) {
+            true => Ok(()),
+            false => Err(TreeError::UnknownRoot),
+        }
+    }
+
+    /// `check_against` on the raw data of a TornadoState account
+    pub fn check_against_account(&self, data: &[u8]) -> Result<(), TreeError> {
+        let (state, roots) = TornadoState::split(data).map_err(|_| TreeError::InvalidAccountData)?;
+        self.check_against(state, roots)
+    }
+
+    fn node(&self, level: usize, index: usize) -> [u8; 32] {
+        self.layers[level].get(index).copied().unwrap_or(ZERO_HASHES[level])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const LEVELS: u32 = 8;
+
+    fn leaf(i: u32) -> [u8; 32] {
+        let mut value = [0u8; 32];
+        value[28..].copy_from_slice(&(i + 1).to_be_bytes());
+        value
+    }
+
+    fn event(i: u32) -> DepositEvent {
+        DepositEvent { commitment: leaf(i), leaf_index: i, timestamp: 0 }
+    }
+
+    fn events(n: u32) -> Vec<DepositEvent> {
+        (0..n).map(event).collect()
+    }
+
+    /// Pool state after `n` deposits, with its root history
+    fn chain_after(n: u32) -> (TornadoState, Vec<[u8; 32]>) {
+        let mut state: TornadoState = bytemuck::Zeroable::zeroed();
+        state.merkle_tree = MerkleTree::with_levels(LEVELS);
+        let mut roots = vec![state.merkle_tree.get_root()];
+        for i in 0..n {
+            state.merkle_tree.insert(leaf(i)).unwrap();
+            roots.push(state.merkle_tree.get_root());
+        }
+        (state, roots)
+    }
+
+    #[test]
+    fn test_root_matches_on_chain_tree() {
+        let mut tree = DepositTree::new(LEVELS);
+        let mut chain = MerkleTree::with_levels(LEVELS);
+        assert_eq!(tree.root(), chain.get_root());
+
+        for e in events(20) {
+            tree.ingest(&e).unwrap();
+            chain.insert(e.commitment).unwrap();
+            assert_eq!(tree.root(), chain.get_root(), "after leaf {}", e.leaf_index);
+        }
+    }
+
+    #[test]
+    fn test_paths_valid_for_every_leaf() {
+        let tree = DepositTree::from_events(LEVELS, &events(13)).unwrap();
+
+        for i in 0..13 {
+            let path = tree.path(i).unwrap();
+            assert_eq!(path.path_elements.len(), LEVELS as usize);
+            assert_eq!(path.compute_root(&leaf(i)).unwrap(), tree.root(), "leaf {}", i);
+            assert!(path.path_indices.iter().all(|&bit| bit <= 1));
+            assert_eq!(path.path_indices[0] as u32, i % 2);
+        }
+        assert_eq!(tree.path(13).unwrap_err(), TreeError::UnknownLeaf { leaf_index: 13 });
+        assert_eq!(tree.position(&leaf(7)), Some(7));
+    }
+
+    #[test]
+    fn test_gaps_and_conflicts_detected() {
+        let mut tree = DepositTree::from_events(LEVELS, &events(3)).unwrap();
+        let root = tree.root();
+
+        assert_eq!(tree.ingest(&event(4)).unwrap_err(), TreeError::Gap { expected: 3, found: 4 });
+
+        // Replayed events are fine, rewritten ones are not
+        tree.ingest(&event(1)).unwrap();
+        let mut forged = event(1);
+        forged.commitment = leaf(99);
+        assert_eq!(tree.ingest(&forged).unwrap_err(), TreeError::ConflictingLeaf { leaf_index: 1 });
+        assert_eq!(tree.root(), root);
+        assert_eq!(tree.len(), 3);
+    }
+
+    #[test]
+    fn test_full_tree_and_bad_leaf_rejected() {
+        let mut tree = DepositTree::new(2);
+        for i in 0..4 {
+            tree.push(leaf(i)).unwrap();
+        }
+        assert_eq!(tree.push(leaf(4)).unwrap_err(), TreeError::TreeFull);
+
+        let mut tree = DepositTree::new(LEVELS);
+        assert_eq!(tree.push([0xff; 32]).unwrap_err(), TreeError::HashFailure);
+        assert!(tree.is_empty());
+    }
+
+    #[test]
+    fn test_cross_check_against_chain() {
+        let (state, roots) = chain_after(10);
+
+        // Caught up, or behind but within the root history
+        assert!(DepositTree::from_events(LEVELS, &events(10)).unwrap().check_against(&state, &roots).is_ok());
+        assert!(DepositTree::from_events(LEVELS, &events(6)).unwrap().check_against(&state, &roots).is_ok());
+
+        // Behind and outside a 4-root history
+        assert_eq!(
+            DepositTree::from_events(LEVELS, &events(6)).unwrap().check_against(&state, &roots[7..]),
+            Err(TreeError::UnknownRoot)
+        );
+
+        assert_eq!(
+            DepositTree::from_events(LEVELS, &events(11)).unwrap().check_against(&state, &roots),
+            Err(TreeError::AheadOfChain { tree: 11, chain: 10 })
+        );
+
+        let mut wrong = events(10);
+        wrong[4].commitment = leaf(99);
+        assert_eq!(
+            DepositTree::from_events(LEVELS, &wrong).unwrap().check_against(&state, &roots),
+            Err(TreeError::RootMismatch)
+        );
+
+        assert_eq!(
+            DepositTree::from_events(LEVELS + 1, &events(10)).unwrap().check_against(&state, &roots),
+            Err(TreeError::LevelsMismatch { tree: LEVELS + 1, chain: LEVELS })
+        );
+    }
+
+    #[test]
+    fn test_prover_vector_inputs() {
+        // The native prover's test vector must describe a real withdrawal from this tree
+        let vector: serde_json::Value =
+            serde_json::from_str(include_str!("../../prover/test_vectors/withdraw_proof.json")).unwrap();
+        let bytes32 = |value: &serde_json::Value| -> [u8; 32] {
+            hex::decode(value.as_str().unwrap()).unwrap().try_into().unwrap()
+        };
+
+        let note = crate::Note { nullifier: bytes32(&vector["nullifier"]), secret: bytes32(&vector["secret"]) };
+        let leaves: Vec<[u8; 32]> = vector["leaves"].as_array().unwrap().iter().map(bytes32).collect();
+        let leaf_index = vector["leaf_index"].as_u64().unwrap() as u32;
+        assert_eq!(leaves[leaf_index as usize], note.commitment().unwrap());
+        assert_eq!(bytes32(&vector["nullifier_hash"]), note.nullifier_hash().unwrap());
+
+        let mut tree = DepositTree::new(20);
+        for leaf in leaves {
+            tree.push(leaf).unwrap();
+        }
+        let path = tree.path(leaf_index).unwrap();
+        assert_eq!(bytes32(&vector["root"]), tree.root());
+        let path_elements: Vec<[u8; 32]> = vector["path_elements"].as_array().unwrap().iter().map(bytes32).collect();
+        assert_eq!(path.path_elements, path_elements);
+        let path_indices: Vec<u8> =
+            vector["path_indices"].as_array().unwrap().iter().map(|index| index.as_u64().unwrap() as u8).collect();
+        assert_eq!(path.path_indices, path_indices);
+    }
+}
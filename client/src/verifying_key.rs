@@ -0,0 +1,358 @@
+//! Verifying keys from snarkjs
+//!
+//! Converts a snarkjs `verification_key.json` into the bytes `initialize` and
+//! `propose_verifying_key` take, and into a `Groth16Verifyingkey` constant:
+//! nr_pubinputs (u32 LE) | alpha_g1 | beta_g2 | gamma_g2 | delta_g2 | IC, big-endian.
+//!
+//! snarkjs lists each G2 coordinate as [c0, c1]; groth16-solana expects (c1, c0).
+//! Bytes copied through in snarkjs order still parse but never verify a proof.
+
+use std::fmt;
+use std::str::FromStr;
+
+use ark_bn254::{Fq, Fq2, G1Affine, G2Affine};
+use ark_ff::{BigInteger, PrimeField};
+use serde::{Deserialize, Serialize};
+
+const G1_SIZE: usize = 64;
+const G2_SIZE: usize = 128;
+// nr_pubinputs, alpha, beta, gamma, delta
+const IC_OFFSET: usize = 4 + G1_SIZE + 3 * G2_SIZE;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum VerifyingKeyError {
+    InvalidJson(String),
+    /// Not a Groth16 key over BN254 (snarkjs calls the curve "bn128")
+    UnsupportedKey { protocol: String, curve: String },
+    /// IC must hold one point per public input plus one
+    PublicInputCount { n_public: u32, ic: usize },
+    /// A point without 3 coordinates, or not in affine form (z = 1)
+    MalformedPoint,
+    /// A coordinate that is not a canonical decimal below the base field modulus
+    InvalidCoordinate(String),
+    NotOnCurve,
+    /// Byte input shorter or longer than its nr_pubinputs implies
+    InvalidLength,
+}
+
+impl fmt::Display for VerifyingKeyError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::InvalidJson(error) => write!(f, "invalid verification key JSON: {}", error),
+            Self::UnsupportedKey { protocol, curve } => {
+                write!(f, "unsupported {} key over {}, expected groth16 over bn128", protocol, curve)
+            }
+            Self::PublicInputCount { n_public, ic } => {
+                write!(f, "{} public inputs need {} IC points, found {}", n_public, n_public + 1, ic)
+            }
+            Self::MalformedPoint => write!(f, "points must be affine [x, y, 1]"),
+            Self::InvalidCoordinate(value) => write!(f, "invalid coordinate {:?}", value),
+            Self::NotOnCurve => write!(f, "point is not on the curve or not in its prime-order subgroup"),
+            Self::InvalidLength => write!(f, "verifying key length does not match nr_pubinputs"),
+        }
+    }
+}
+
+impl std::error::Error for VerifyingKeyError {}
+
+/// snarkjs `verification_key.json` for Groth16; points are projective [x, y, z]
+/// with decimal coordinates, G2 coordinates as [c0, c1]
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SnarkjsVerifyingKey {
+    pub protocol: String,
+    pub curve: String,
+    #[serde(rename = "nPublic")]
+    pub n_public: u32,
+    pub vk_alpha_1: Vec<String>,
+    pub vk_beta_2: Vec<Vec<String>>,
+    pub vk_gamma_2: Vec<Vec<String>>,
+    pub vk_delta_2: Vec<Vec<String>>,
+    #[serde(rename = "IC")]
+    pub ic: Vec<Vec<String>>,
+}
+
+impl SnarkjsVerifyingKey {
+    pub fn from_json(json: &str) -> Result<Self, VerifyingKeyError> {
+        serde_json::from_str(json).map_err(|e| VerifyingKeyError::InvalidJson(e.to_string()))
+    }
+
+    /// Bytes for `initialize` / `propose_verifying_key`; every point is checked to be
+    /// on the curve, which also catches G2 coordinates in the wrong order
+    pub fn to_bytes(&self) -> Result<Vec<u8>, VerifyingKeyError> {
+        if self.protocol != "groth16" || !matches!(self.curve.as_str(), "bn128" | "bn254") {
+            return Err(VerifyingKeyError::UnsupportedKey {
+                protocol: self.protocol.clone(),
+                curve: self.curve.clone(),
+            });
+        }
+        if self.n_public == 0 || self.ic.len() != self.n_public as usize + 1 {
+            return Err(VerifyingKeyError::PublicInputCount { n_public: self.n_public, ic: self.ic.len() });
+        }
+
+        let mut bytes = Vec::with_capacity(IC_OFFSET + self.ic.len() * G1_SIZE);
+        bytes.extend_from_slice(&self.n_public.to_le_bytes());
+        bytes.extend_from_slice(&g1_to_bytes(&self.vk_alpha_1)?);
+        for point in [&self.vk_beta_2, &self.vk_gamma_2, &self.vk_delta_2] {
+            bytes.extend_from_slice(&g2_to_bytes(point)?);
+        }
+        for point in &self.ic {
+            bytes.extend_from_slice(&g1_to_bytes(point)?);
+        }
+        Ok(bytes)
+    }
+
+    /// Inverse of `to_bytes`
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, VerifyingKeyError> {
+        let n_public = u32::from_le_bytes(
+            bytes.get(..4).ok_or(VerifyingKeyError::InvalidLength)?.try_into().unwrap(),
+        );
+        let ic_count = n_public as usize + 1;
+        if bytes.len() != IC_OFFSET + ic_count * G1_SIZE {
+            return Err(VerifyingKeyError::InvalidLength);
+        }
+
+        let g2 = |index: usize| g2_from_bytes(&bytes[4 + G1_SIZE + index * G2_SIZE..][..G2_SIZE]);
+        Ok(Self {
+            protocol: "groth16".to_string(),
+            curve: "bn128".to_string(),
+            n_public,
+            vk_alpha_1: g1_from_bytes(&bytes[4..4 + G1_SIZE])?,
+            vk_beta_2: g2(0)?,
+            vk_gamma_2: g2(1)?,
+            vk_delta_2: g2(2)?,
+            ic: bytes[IC_OFFSET..].chunks(G1_SIZE).map(g1_from_bytes).collect::<Result<_, _>>()?,
+        })
+    }
+}
+
+/// On-chain verifying key bytes from the contents of a snarkjs `verification_key.json`
+pub fn verifying_key_bytes(json: &str) -> Result<Vec<u8>, VerifyingKeyError> {
+    SnarkjsVerifyingKey::from_json(json)?.to_bytes()
+}
+
+/// Rust source for a `Groth16Verifyingkey` constant named `name`, as in
+/// `programs/tornado_solana/src/verifying_key.rs`
+pub fn verifying_key_constant(vk_bytes: &[u8], name: &str) -> Result<String, VerifyingKeyError> {
+    // Validates the length and every point
+    let vk = SnarkjsVerifyingKey::from_bytes(vk_bytes)?;
+    let array = |bytes: &[u8], indent: &str| {
+        let values: Vec<String> = bytes.iter().map(u8::to_string).collect();
+        format!("[\n{}    {},\n{}]", indent, values.join(", "), indent)
+    };
+    let g2_offset = |index: usize| 4 + G1_SIZE + index * G2_SIZE;
+
+    let mut source = String::from("use groth16_solana::groth16::Groth16Verifyingkey;\n\n");
+    source += &format!("pub const {}: Groth16Verifyingkey = Groth16Verifyingkey {{\n", name);
+    source += &format!("    nr_pubinputs: {},\n\n", vk.n_public);
+    source += &format!("    vk_alpha_g1: {},\n\n", array(&vk_bytes[4..4 + G1_SIZE], "    "));
+    for (index, field) in ["vk_beta_g2", "vk_gamme_g2", "vk_delta_g2"].iter().enumerate() {
+        source += &format!("    {}: {},\n\n", field, array(&vk_bytes[g2_offset(index)..][..G2_SIZE], "    "));
+    }
+    source += "    vk_ic: &[\n";
+    for point in vk_bytes[IC_OFFSET..].chunks(G1_SIZE) {
+        source += &format!("        {},\n", array(point, "        "));
+    }
+    source += "    ],\n};\n";
+    Ok(source)
+}
+
+fn g1_to_bytes(point: &[String]) -> Result<[u8; G1_SIZE], VerifyingKeyError> {
+    let [x, y, z] = point else {
+        return Err(VerifyingKeyError::MalformedPoint);
+    };
+    if z != "1" {
+        return Err(VerifyingKeyError::MalformedPoint);
+    }
+    let (x, y) = (parse_fq(x)?, parse_fq(y)?);
+    let affine = G1Affine::new_unchecked(x, y);
+    if !affine.is_on_curve() {
+        return Err(VerifyingKeyError::NotOnCurve);
+    }
+
+    let mut bytes = [0u8; G1_SIZE];
+    bytes[..32].copy_from_slice(&fq_to_bytes(&x));
+    bytes[32..].copy_from_slice(&fq_to_bytes(&y));
+    Ok(bytes)
+}
+
+fn g2_to_bytes(point: &[Vec<String>]) -> Result<[u8; G2_SIZE], VerifyingKeyError> {
+    let [x, y, z] = point else {
+        return Err(VerifyingKeyError::MalformedPoint);
+    };
+    if z.as_slice() != ["1", "0"] {
+        return Err(VerifyingKeyError::MalformedPoint);
+    }
+    let (x, y) = (parse_fq2(x)?, parse_fq2(y)?);
+    let affine = G2Affine::new_unchecked(x, y);
+    if !affine.is_on_curve() || !affine.is_in_correct_subgroup_assuming_on_curve() {
+        return Err(VerifyingKeyError::NotOnCurve);
+    }
+
+    // (c1, c0) for each coordinate
+    let mut bytes = [0u8; G2_SIZE];
+    for (chunk, value) in bytes.chunks_mut(32).zip([x.c1, x.c0, y.c1, y.c0]) {
+        chunk.copy_from_slice(&fq_to_bytes(&value));
+    }
+    Ok(bytes)
+}
+
+fn g1_from_bytes(bytes: &[u8]) -> Result<Vec<String>, VerifyingKeyError> {
+    let (x, y) = (fq_from_bytes(&bytes[..32])?, fq_from_bytes(&bytes[32..])?);
+    if !G1Affine::new_unchecked(x, y).is_on_curve() {
+        return Err(VerifyingKeyError::NotOnCurve);
+    }
+    Ok(vec![fq_to_decimal(&x), fq_to_decimal(&y), "1".to_string()])
+}
+
+fn g2_from_bytes(bytes: &[u8]) -> Result<Vec<Vec<String>>, VerifyingKeyError> {
+    let values = bytes.chunks(32).map(fq_from_bytes).collect::<Result<Vec<_>, _>>()?;
+    let x = Fq2::new(values[1], values[0]);
+    let y = Fq2::new(values[3], values[2]);
+    let affine = G2Affine::new_unchecked(x, y);
+    if !affine.is_on_curve() || !affine.is_in_correct_subgroup_assuming_on_curve() {
+        return Err(VerifyingKeyError::NotOnCurve);
+    }
+    Ok(vec![
+        vec![fq_to_decimal(&x.c0), fq_to_decimal(&x.c1)],
+        vec![fq_to_decimal(&y.c0), fq_to_decimal(&y.c1)],
+        vec!["1".to_string(), "0".to_string()],
+    ])
+}
+
+fn parse_fq2(value: &[String]) -> Result<Fq2, VerifyingKeyError> {
+    match value {
+        [c0, c1] => Ok(Fq2::new(parse_fq(c0)?, parse_fq(c1)?)),
+        _ => Err(VerifyingKeyError::MalformedPoint),
+    }
+}
+
+// Fq::from_str reduces modulo p; only the canonical decimal form is accepted
+fn parse_fq(value: &str) -> Result<Fq, VerifyingKeyError> {
+    Fq::from_str(value)
+        .ok()
+        .filter(|parsed| fq_to_decimal(parsed) == value)
+        .ok_or_else(|| VerifyingKeyError::InvalidCoordinate(value.to_string()))
+}
+
+fn fq_from_bytes(bytes: &[u8]) -> Result<Fq, VerifyingKeyError> {
+    let value = Fq::from_be_bytes_mod_order(bytes);
+    if fq_to_bytes(&value) != bytes {
+        return Err(VerifyingKeyError::InvalidCoordinate(format!("0x{}", hex_string(bytes))));
+    }
+    Ok(value)
+}
+
+fn fq_to_bytes(value: &Fq) -> [u8; 32] {
+    let mut bytes = [0u8; 32];
+    bytes.copy_from_slice(&value.into_bigint().to_bytes_be());
+    bytes
+}
+
+fn fq_to_decimal(value: &Fq) -> String {
+    value.into_bigint().to_string()
+}
+
+fn hex_string(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{:02x}", byte)).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const VERIFICATION_KEY_JSON: &str = include_str!("../../circuits/build/verification_key.json");
+    const VK_BYTES_JSON: &str = include_str!("../../circuits/build/vk_bytes.json");
+    const PROGRAM_VERIFYING_KEY: &str = include_str!("../../programs/tornado_solana/src/verifying_key.rs");
+
+    /// Every number literal from `vk_alpha_g1` to the end of the constant
+    fn constant_bytes(source: &str) -> Vec<u8> {
+        let start = source.find("vk_alpha_g1").unwrap();
+        let end = start + source[start..].find("};").unwrap();
+        source[start..end]
+            .split(|c: char| !c.is_ascii_alphanumeric() && c != '_')
+            .filter(|token| !token.is_empty() && token.bytes().all(|c| c.is_ascii_digit()))
+            .map(|token| token.parse().unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn test_json_round_trip() {
+        let vk = SnarkjsVerifyingKey::from_json(VERIFICATION_KEY_JSON).unwrap();
+        let bytes = vk.to_bytes().unwrap();
+
+        assert_eq!(bytes.len(), IC_OFFSET + 9 * G1_SIZE);
+        assert_eq!(bytes[..4], 8u32.to_le_bytes());
+        assert_eq!(SnarkjsVerifyingKey::from_bytes(&bytes).unwrap(), vk);
+    }
+
+    #[test]
+    fn test_matches_vk_bytes_json() {
+        let vk_bytes: Vec<u8> = serde_json::from_str(VK_BYTES_JSON).unwrap();
+        assert_eq!(verifying_key_bytes(VERIFICATION_KEY_JSON).unwrap(), vk_bytes);
+    }
+
+    #[test]
+    fn test_snarkjs_g2_order_rejected() {
+        let bytes = verifying_key_bytes(VERIFICATION_KEY_JSON).unwrap();
+
+        // Copying snarkjs' (c0, c1) coordinates through unchanged does not give a valid G2 point
+        let mut snarkjs_order = bytes.clone();
+        for g2 in snarkjs_order[4 + G1_SIZE..IC_OFFSET].chunks_mut(G2_SIZE) {
+            for coordinate in g2.chunks_mut(64) {
+                let (c1, c0) = coordinate.split_at_mut(32);
+                c1.swap_with_slice(c0);
+            }
+        }
+        assert_eq!(SnarkjsVerifyingKey::from_bytes(&snarkjs_order), Err(VerifyingKeyError::NotOnCurve));
+    }
+
+    #[test]
+    fn test_constant_matches_program_verifying_key() {
+        let bytes = verifying_key_bytes(VERIFICATION_KEY_JSON).unwrap();
+        let source = verifying_key_constant(&bytes, "VERIFYINGKEY").unwrap();
+
+        assert!(source.contains("pub const VERIFYINGKEY: Groth16Verifyingkey = Groth16Verifyingkey {"));
+        assert!(source.contains("nr_pubinputs: 8,"));
+        assert_eq!(constant_bytes(&source), bytes[4..]);
+        assert_eq!(constant_bytes(&source), constant_bytes(PROGRAM_VERIFYING_KEY));
+    }
+
+    #[test]
+    fn test_swapped_g2_coordinates_rejected() {
+        let mut vk = SnarkjsVerifyingKey::from_json(VERIFICATION_KEY_JSON).unwrap();
+        vk.vk_delta_2[0].swap(0, 1);
+        vk.vk_delta_2[1].swap(0, 1);
+        assert_eq!(vk.to_bytes(), Err(VerifyingKeyError::NotOnCurve));
+    }
+
+    #[test]
+    fn test_invalid_keys_rejected() {
+        let valid = SnarkjsVerifyingKey::from_json(VERIFICATION_KEY_JSON).unwrap();
+
+        let mut plonk = valid.clone();
+        plonk.protocol = "plonk".to_string();
+        assert!(matches!(plonk.to_bytes(), Err(VerifyingKeyError::UnsupportedKey { .. })));
+
+        let mut missing_ic = valid.clone();
+        missing_ic.ic.pop();
+        assert_eq!(missing_ic.to_bytes(), Err(VerifyingKeyError::PublicInputCount { n_public: 8, ic: 8 }));
+
+        let mut projective = valid.clone();
+        projective.vk_alpha_1[2] = "2".to_string();
+        assert_eq!(projective.to_bytes(), Err(VerifyingKeyError::MalformedPoint));
+
+        let mut padded = valid.clone();
+        padded.ic[0][0] = format!("0{}", valid.ic[0][0]);
+        assert!(matches!(padded.to_bytes(), Err(VerifyingKeyError::InvalidCoordinate(_))));
+
+        let mut off_curve = valid.clone();
+        off_curve.ic[3][1] = "1".to_string();
+        assert_eq!(off_curve.to_bytes(), Err(VerifyingKeyError::NotOnCurve));
+
+        assert!(matches!(SnarkjsVerifyingKey::from_json("{}"), Err(VerifyingKeyError::InvalidJson(_))));
+
+        let bytes = valid.to_bytes().unwrap();
+        assert_eq!(SnarkjsVerifyingKey::from_bytes(&bytes[..bytes.len() - 1]), Err(VerifyingKeyError::InvalidLength));
+        assert_eq!(verifying_key_constant(&bytes[..3], "VK"), Err(VerifyingKeyError::InvalidLength));
+    }
+}
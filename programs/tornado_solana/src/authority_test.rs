@@ -0,0 +1,108 @@
+#[cfg(test)]
+mod authority_tests {
+    use crate::test_accounts::{error_code, validate, Pool, TestAccount};
+    use crate::{
+        AcceptAuthority, ApplyVerifyingKey, CancelVerifyingKey, LowerFeeCap, MigrateToVault, ProposeAuthority,
+        RenounceAuthority, RenouncePause, RenounceVerifyingKeyRotation, SetPaused, SetQueuedDeposits,
+        SetRegisteredRelayersOnly, SlashRelayer, TornadoError,
+    };
+    use anchor_lang::prelude::*;
+
+    const DENOMINATION: u64 = 1_000_000_000;
+
+    /// Validate every admin context against `pool` with `signer` as the authority
+    /// ProposeVerifyingKey and InitializeDepositQueue are not listed: Anchor runs their `init` CPI before `has_one`,
+    /// which cannot execute off-chain, but it carries the same `has_one = authority` check
+    fn admin_results(pool: &mut Pool, signer: Pubkey) -> Vec<(&'static str, Result<()>)> {
+        let mut signer = TestAccount::signer(signer);
+        let state = pool.state.info();
+        let authority = signer.info();
+        let verifying_key = pool.verifying_key.info();
+        let pending = pool.pending_verifying_key.info();
+        let relayer_record = pool.relayer_record.info();
+        let deposit_queue = pool.deposit_queue.info();
+        let vault = pool.vault.info();
+        let system = pool.system_program.info();
+
+        let state_and_authority = [state.clone(), authority.clone()];
+        let apply = [state.clone(), verifying_key, pending.clone(), authority.clone()];
+        let cancel = [state.clone(), pending, authority.clone()];
+        let slash = [state.clone(), relayer_record, authority.clone()];
+        let queue = [state.clone(), deposit_queue, authority.clone()];
+        let migrate = [state, vault, authority, system];
+
+        vec![
+            ("apply_verifying_key", validate::<ApplyVerifyingKey>(&apply)),
+            ("cancel_verifying_key", validate::<CancelVerifyingKey>(&cancel)),
+            ("renounce_verifying_key_rotation", validate::<RenounceVerifyingKeyRotation>(&state_and_authority)),
+            ("set_paused", validate::<SetPaused>(&state_and_authority)),
+            ("renounce_pause", validate::<RenouncePause>(&state_and_authority)),
+            ("lower_fee_cap", validate::<LowerFeeCap>(&state_and_authority)),
+            ("set_registered_relayers_only", validate::<SetRegisteredRelayersOnly>(&state_and_authority)),
+            ("set_queued_deposits", validate::<SetQueuedDeposits>(&queue)),
+            ("slash_relayer", validate::<SlashRelayer>(&slash)),
+            ("migrate_to_vault", validate::<MigrateToVault>(&migrate)),
+            ("propose_authority", validate::<ProposeAuthority>(&state_and_authority)),
+            ("renounce_authority", validate::<RenounceAuthority>(&state_and_authority)),
+        ]
+    }
+
+    #[test]
+    fn test_current_authority_passes_admin_checks() {
+        let authority = Pubkey::new_unique();
+        let mut pool = Pool::new(DENOMINATION, authority, Pubkey::default());
+
+        for (name, result) in admin_results(&mut pool, authority) {
+            assert!(result.is_ok(), "{} rejected the authority: {:?}", name, result);
+        }
+    }
+
+    #[test]
+    fn test_renounced_pool_rejects_every_admin_instruction() {
+        let former_authority = Pubkey::new_unique();
+        let mut pool = Pool::renounced(DENOMINATION);
+
+        for signer in [former_authority, Pubkey::new_unique()] {
+            for (name, result) in admin_results(&mut pool, signer) {
+                assert_eq!(
+                    error_code(result),
+                    u32::from(ErrorCode::ConstraintHasOne),
+                    "{} accepted a renounced pool",
+                    name
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_only_pending_authority_can_accept() {
+        let authority = Pubkey::new_unique();
+        let pending_authority = Pubkey::new_unique();
+        let mut pool = Pool::new(DENOMINATION, authority, pending_authority);
+
+        for (signer, expected) in [
+            (pending_authority, Ok(())),
+            (authority, Err(TornadoError::NotPendingAuthority)),
+            (Pubkey::new_unique(), Err(TornadoError::NotPendingAuthority)),
+        ] {
+            let mut signer = TestAccount::signer(signer);
+            let infos = [pool.state.info(), signer.info()];
+            match expected {
+                Ok(()) => assert!(validate::<AcceptAuthority>(&infos).is_ok()),
+                Err(e) => assert_eq!(error_code(validate::<AcceptAuthority>(&infos)), u32::from(e)),
+            }
+        }
+    }
+
+    #[test]
+    fn test_renounced_pool_has_no_pending_authority_to_accept() {
+        let mut pool = Pool::renounced(DENOMINATION);
+        let mut signer = TestAccount::signer(Pubkey::new_unique());
+        let infos = [pool.state.info(), signer.info()];
+
+        assert_eq!(
+            error_code(validate::<AcceptAuthority>(&infos)),
+            u32::from(TornadoError::NotPendingAuthority)
+        );
+    }
+}
@@ -0,0 +1,194 @@
+#[cfg(test)]
+mod deposit_batch_tests {
+    use crate::{
+        insert_commitments, is_known_root, MerkleTree, TornadoError,
+        BN254_SCALAR_FIELD_MODULUS, MAX_DEPOSIT_BATCH_SIZE, MAX_MERKLE_TREE_HEIGHT, MERKLE_TREE_HEIGHT,
+        MIN_MERKLE_TREE_HEIGHT, ROOT_HISTORY_SIZE,
+    };
+    use crate::merkle_tree::PAIR_HASHES;
+    use crate::test_accounts::{error_code, new_pool};
+    use anchor_lang::prelude::*;
+    use anchor_lang::solana_program::instruction::Instruction;
+    use anchor_lang::solana_program::message::Message;
+    use anchor_lang::{system_program, InstructionData};
+    use std::str::FromStr;
+
+    fn leaf(i: u32) -> [u8; 32] {
+        let mut value = [0u8; 32];
+        value[28..].copy_from_slice(&(i + 1).to_be_bytes());
+        value
+    }
+
+    fn leaves(range: std::ops::Range<u32>) -> Vec<[u8; 32]> {
+        range.map(leaf).collect()
+    }
+
+    #[test]
+    fn test_batch_matches_sequential_inserts() {
+        // Odd and even starting offsets exercise both sibling cases at every level
+        for start in [0u32, 1, 2, 3, 5, 7, 8] {
+            for n in 1..=MAX_DEPOSIT_BATCH_SIZE as u32 {
+                let mut sequential = MerkleTree::with_levels(MIN_MERKLE_TREE_HEIGHT);
+                for l in leaves(0..start + n) {
+                    sequential.insert(l).unwrap();
+                }
+
+                let mut batched = MerkleTree::with_levels(MIN_MERKLE_TREE_HEIGHT);
+                for l in leaves(0..start) {
+                    batched.insert(l).unwrap();
+                }
+                assert_eq!(batched.insert_batch(&leaves(start..start + n)).unwrap(), start);
+
+                assert_eq!(batched.get_root(), sequential.get_root(), "start {} size {}", start, n);
+                assert_eq!(batched.filled_subtrees, sequential.filled_subtrees);
+                assert_eq!(batched.next_index, sequential.next_index);
+
+                // Later single inserts continue from the same state
+                batched.insert(leaf(999)).unwrap();
+                sequential.insert(leaf(999)).unwrap();
+                assert_eq!(batched.get_root(), sequential.get_root());
+            }
+        }
+    }
+
+    #[test]
+    fn test_batch_cannot_overflow_tree() {
+        let mut tree = MerkleTree::with_levels(MIN_MERKLE_TREE_HEIGHT);
+        for chunk in leaves(0..250).chunks(MAX_DEPOSIT_BATCH_SIZE) {
+            tree.insert_batch(chunk).unwrap();
+        }
+        let before = tree;
+
+        // 250 + 7 > 256
+        assert_eq!(
+            error_code(tree.insert_batch(&leaves(250..257))),
+            u32::from(TornadoError::MerkleTreeFull)
+        );
+        assert_eq!(tree.get_root(), before.get_root());
+        assert_eq!(tree.next_index, 250);
+
+        // Filling the last slots exactly is fine
+        tree.insert_batch(&leaves(250..256)).unwrap();
+        assert_eq!(tree.next_index, 256);
+    }
+
+    #[test]
+    fn test_batch_records_a_single_root() {
+        let (mut state, mut roots) = new_pool(MIN_MERKLE_TREE_HEIGHT);
+
+        let first = insert_commitments(&mut state, &mut roots, &leaves(0..5)).unwrap();
+        assert_eq!(first, 0);
+        assert_eq!(state.merkle_tree.next_index, 5);
+        assert_eq!(state.current_root_index, 1);
+        assert!(is_known_root(&roots, state.current_root_index, &state.merkle_tree.get_root()));
+        assert_eq!(roots[2..], [[0u8; 32]; ROOT_HISTORY_SIZE as usize - 2][..]);
+    }
+
+    #[test]
+    fn test_batch_size_bounds() {
+        let (mut state, mut roots) = new_pool(MIN_MERKLE_TREE_HEIGHT);
+
+        assert_eq!(
+            error_code(insert_commitments(&mut state, &mut roots, &[])),
+            u32::from(TornadoError::InvalidBatchSize)
+        );
+        assert_eq!(
+            error_code(insert_commitments(
+                &mut state,
+                &mut roots,
+                &leaves(0..MAX_DEPOSIT_BATCH_SIZE as u32 + 1)
+            )),
+            u32::from(TornadoError::InvalidBatchSize)
+        );
+        assert!(insert_commitments(&mut state, &mut roots, &leaves(0..MAX_DEPOSIT_BATCH_SIZE as u32)).is_ok());
+    }
+
+    #[test]
+    fn test_one_bad_commitment_rejects_whole_batch() {
+        let (mut state, mut roots) = new_pool(MIN_MERKLE_TREE_HEIGHT);
+        let mut batch = leaves(0..4);
+        batch[3] = BN254_SCALAR_FIELD_MODULUS;
+
+        assert_eq!(
+            error_code(insert_commitments(&mut state, &mut roots, &batch)),
+            u32::from(TornadoError::CommitmentNotInField)
+        );
+        assert_eq!(state.merkle_tree.next_index, 0);
+        assert_eq!(state.current_root_index, 0);
+    }
+
+    #[test]
+    fn test_paused_pool_rejects_batch() {
+        let (mut state, mut roots) = new_pool(MIN_MERKLE_TREE_HEIGHT);
+        state.deposits_paused = 1;
+
+        assert_eq!(
+            error_code(insert_commitments(&mut state, &mut roots, &leaves(0..2))),
+            u32::from(TornadoError::DepositsPaused)
+        );
+    }
+
+    fn pair_hashes(f: impl FnOnce()) -> usize {
+        PAIR_HASHES.with(|count| count.set(0));
+        f();
+        PAIR_HASHES.with(|count| count.get())
+    }
+
+    #[test]
+    fn test_max_batch_hash_count() {
+        // The most expensive full batch straddles the tree's midpoint: both halves gain nodes at
+        // every level below the root, so no level gets away with a single hash
+        for (levels, worst) in [(MERKLE_TREE_HEIGHT, 50), (MAX_MERKLE_TREE_HEIGHT, 74)] {
+            let midpoint = 1u32 << (levels - 1);
+            let mut most = 0;
+            for start in [0, 1, 241, midpoint - 1, midpoint - MAX_DEPOSIT_BATCH_SIZE as u32 / 2] {
+                let mut tree = MerkleTree::with_levels(levels);
+                tree.next_index = start;
+                let hashes = pair_hashes(|| {
+                    tree.insert_batch(&leaves(0..MAX_DEPOSIT_BATCH_SIZE as u32)).unwrap();
+                });
+                // insert_batch's documented bound: N + 2 * levels - 1
+                assert!(hashes < MAX_DEPOSIT_BATCH_SIZE + 2 * levels as usize);
+                most = most.max(hashes);
+            }
+            assert_eq!(most, worst, "depth {}", levels);
+
+            let mut tree = MerkleTree::with_levels(levels);
+            assert_eq!(pair_hashes(|| { tree.insert(leaf(0)).unwrap(); }), levels as usize);
+        }
+    }
+
+    #[test]
+    fn test_max_batch_fits_one_transaction() {
+        // solana_sdk::packet::PACKET_DATA_SIZE
+        const PACKET_DATA_SIZE: usize = 1232;
+
+        let depositor = Pubkey::new_unique();
+        let (tornado_state, _) =
+            Pubkey::find_program_address(&[b"tornado", 1_000_000_000u64.to_le_bytes().as_ref()], &crate::ID);
+        let deposit_batch = Instruction {
+            program_id: crate::ID,
+            accounts: crate::accounts::Deposit {
+                tornado_state,
+                vault: Pubkey::find_program_address(&[b"vault", tornado_state.as_ref()], &crate::ID).0,
+                depositor,
+                system_program: system_program::ID,
+            }
+            .to_account_metas(None),
+            data: crate::instruction::DepositBatch { commitments: leaves(0..MAX_DEPOSIT_BATCH_SIZE as u32) }.data(),
+        };
+        // A batch needs more than the default compute units, so leave room for SetComputeUnitLimit
+        let set_compute_unit_limit = Instruction {
+            program_id: Pubkey::from_str("ComputeBudget111111111111111111111111111111").unwrap(),
+            accounts: vec![],
+            data: [&[2u8][..], &1_400_000u32.to_le_bytes()].concat(),
+        };
+
+        let message = Message::new(&[set_compute_unit_limit, deposit_batch], Some(&depositor));
+        // One signature: compact-u16 count + 64 bytes, then the message
+        let transaction_size = 1 + 64 + message.serialize().len();
+
+        assert_eq!(transaction_size, 834);
+        assert!(transaction_size <= PACKET_DATA_SIZE);
+    }
+}
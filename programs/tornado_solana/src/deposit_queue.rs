@@ -0,0 +1,70 @@
+//! Deferred deposit insertion
+//!
+//! In queued mode a deposit only transfers the denomination and appends its commitment to
+//! a ring buffer instead of hashing every tree level. A permissionless `process_queue` crank
+//! later inserts queued leaves in batches, in arrival order, through the same path as
+//! `deposit_batch`.
+//! This moves the hashing out of the deposit, not the contention: `enqueue_deposit` still
+//! write-locks the pool's single queue account and its vault, so deposits into one pool are
+//! still serialized, and withdrawals, which write-lock the same vault, still wait on them.
+//! A leaf becomes provable once the crank has inserted it; clients match their commitment
+//! in the `DepositEvent` the crank emits to learn its leaf index.
+//! Every queued commitment has a leaf reserved for it: `enqueue_deposit` stops once the
+//! queue would outgrow the tree, and the pool only leaves queued mode with an empty queue.
+
+use anchor_lang::prelude::*;
+
+use crate::TornadoError;
+
+// Commitments a queue can hold; 8 KiB of leaves keeps the account under the 10 KiB CPI allocation limit
+pub const DEPOSIT_QUEUE_CAPACITY: usize = 256;
+
+/// Ring buffer of commitments waiting to be inserted into a pool's tree
+/// PDA: [b"deposit_queue", tornado_state]
+/// `head` and `tail` count processed and queued commitments since creation; slots are taken modulo capacity
+#[account(zero_copy)]
+pub struct DepositQueue {
+    pub tornado_state: Pubkey,
+    pub head: u64,
+    pub tail: u64,
+    pub commitments: [[u8; 32]; DEPOSIT_QUEUE_CAPACITY],
+}
+
+impl DepositQueue {
+    pub const SEED_PREFIX: &'static [u8] = b"deposit_queue";
+
+    // 32 (tornado_state) + 8 (head) + 8 (tail) + 32 * DEPOSIT_QUEUE_CAPACITY (commitments)
+    pub const SIZE: usize = 32 + 8 + 8 + 32 * DEPOSIT_QUEUE_CAPACITY;
+
+    /// Commitments waiting for the crank
+    pub fn len(&self) -> usize {
+        (self.tail - self.head) as usize
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.head == self.tail
+    }
+
+    /// Append a commitment, returning its queue position
+    pub fn push(&mut self, commitment: [u8; 32]) -> Result<u64> {
+        require!(self.len() < DEPOSIT_QUEUE_CAPACITY, TornadoError::DepositQueueFull);
+
+        let position = self.tail;
+        self.commitments[(position % DEPOSIT_QUEUE_CAPACITY as u64) as usize] = commitment;
+        self.tail += 1;
+        Ok(position)
+    }
+
+    /// Oldest `count` commitments (fewer if the queue is shorter), without removing them
+    pub fn peek(&self, count: usize) -> Vec<[u8; 32]> {
+        (self.head..self.tail)
+            .take(count)
+            .map(|position| self.commitments[(position % DEPOSIT_QUEUE_CAPACITY as u64) as usize])
+            .collect()
+    }
+
+    /// Drop the oldest `count` commitments once they are in the tree
+    pub fn pop(&mut self, count: usize) {
+        self.head += count.min(self.len()) as u64;
+    }
+}
@@ -0,0 +1,243 @@
+#[cfg(test)]
+mod deposit_queue_tests {
+    use crate::deposit_queue::{DepositQueue, DEPOSIT_QUEUE_CAPACITY};
+    use crate::test_accounts::{accounts_with_args, error_code, new_pool, Pool, TestAccount};
+    use crate::{
+        is_known_root, process_deposit_queue, EnqueueDeposit, MerkleTree, SetQueuedDeposits, TornadoError,
+        TornadoState, MAX_DEPOSIT_BATCH_SIZE, MIN_MERKLE_TREE_HEIGHT, ROOT_HISTORY_SIZE,
+    };
+    use anchor_lang::prelude::*;
+
+    fn leaf(i: u32) -> [u8; 32] {
+        let mut value = [0u8; 32];
+        value[28..].copy_from_slice(&(i + 1).to_be_bytes());
+        value
+    }
+
+    /// Small pool with queued deposits switched on
+    fn queued_pool() -> (TornadoState, [[u8; 32]; ROOT_HISTORY_SIZE as usize]) {
+        let (mut state, roots) = new_pool(MIN_MERKLE_TREE_HEIGHT);
+        state.queued_deposits = 1;
+        (state, roots)
+    }
+
+    fn new_queue() -> DepositQueue {
+        bytemuck::Zeroable::zeroed()
+    }
+
+    #[test]
+    fn test_queue_is_fifo_across_wraparound() {
+        let mut queue = new_queue();
+        let mut next = 0u32;
+        let mut expected = 0u32;
+
+        // Push and pop in uneven steps so head and tail wrap several times
+        for round in 0..20 {
+            for _ in 0..(37 + round) {
+                assert_eq!(queue.push(leaf(next)).unwrap(), next as u64);
+                next += 1;
+            }
+            let taken = queue.peek(29 + round);
+            for commitment in &taken {
+                assert_eq!(*commitment, leaf(expected));
+                expected += 1;
+            }
+            queue.pop(taken.len());
+            assert_eq!(queue.len(), (next - expected) as usize);
+        }
+        assert!(next as usize > 2 * DEPOSIT_QUEUE_CAPACITY);
+    }
+
+    #[test]
+    fn test_full_queue_rejects_push() {
+        let mut queue = new_queue();
+        for i in 0..DEPOSIT_QUEUE_CAPACITY as u32 {
+            queue.push(leaf(i)).unwrap();
+        }
+        assert_eq!(
+            error_code(queue.push(leaf(9999))),
+            u32::from(TornadoError::DepositQueueFull)
+        );
+
+        // Draining one slot makes room again
+        queue.pop(1);
+        queue.push(leaf(9999)).unwrap();
+        assert_eq!(queue.len(), DEPOSIT_QUEUE_CAPACITY);
+        assert_eq!(queue.peek(1), vec![leaf(1)]);
+    }
+
+    #[test]
+    fn test_processing_matches_sequential_deposits() {
+        let (mut state, mut roots) = queued_pool();
+        let mut queue = new_queue();
+        for i in 0..20 {
+            queue.push(leaf(i)).unwrap();
+        }
+
+        // The crank caps a single call at MAX_DEPOSIT_BATCH_SIZE leaves and records one root
+        let (first, inserted) = process_deposit_queue(&mut state, &mut roots, &mut queue, u32::MAX).unwrap();
+        assert_eq!(first, 0);
+        assert_eq!(inserted.len(), MAX_DEPOSIT_BATCH_SIZE);
+        assert_eq!(state.current_root_index, 1);
+        assert_eq!(queue.len(), 20 - MAX_DEPOSIT_BATCH_SIZE);
+
+        let (first, inserted) = process_deposit_queue(&mut state, &mut roots, &mut queue, 3).unwrap();
+        assert_eq!(first, MAX_DEPOSIT_BATCH_SIZE as u32);
+        assert_eq!(inserted, (16..19).map(leaf).collect::<Vec<_>>());
+        assert_eq!(queue.len(), 1);
+
+        let mut sequential = MerkleTree::with_levels(MIN_MERKLE_TREE_HEIGHT);
+        for i in 0..19 {
+            sequential.insert(leaf(i)).unwrap();
+        }
+        assert_eq!(state.merkle_tree.get_root(), sequential.get_root());
+        assert!(is_known_root(&roots, state.current_root_index, &sequential.get_root()));
+    }
+
+    #[test]
+    fn test_failed_processing_keeps_queue() {
+        let (mut state, mut roots) = queued_pool();
+        let mut queue = new_queue();
+
+        assert_eq!(
+            error_code(process_deposit_queue(&mut state, &mut roots, &mut queue, 4)),
+            u32::from(TornadoError::DepositQueueEmpty)
+        );
+
+        queue.push(leaf(0)).unwrap();
+        queue.push(leaf(1)).unwrap();
+        // No free slot left in the 2^8-leaf tree
+        state.merkle_tree.next_index = 1 << MIN_MERKLE_TREE_HEIGHT;
+        assert_eq!(
+            error_code(process_deposit_queue(&mut state, &mut roots, &mut queue, 4)),
+            u32::from(TornadoError::MerkleTreeFull)
+        );
+        assert_eq!(queue.len(), 2);
+        assert_eq!(state.merkle_tree.next_index, 1 << MIN_MERKLE_TREE_HEIGHT);
+
+        state.merkle_tree.next_index = 0;
+        assert_eq!(
+            error_code(process_deposit_queue(&mut state, &mut roots, &mut queue, 0)),
+            u32::from(TornadoError::InvalidBatchSize)
+        );
+        assert_eq!(queue.len(), 2);
+    }
+
+    #[test]
+    fn test_crank_drains_queue_while_deposits_paused() {
+        let (mut state, mut roots) = queued_pool();
+        let mut queue = new_queue();
+        for i in 0..3 {
+            queue.push(leaf(i)).unwrap();
+        }
+
+        // These deposits were paid before the pause; they must still become provable
+        state.deposits_paused = 1;
+        let (first, inserted) = process_deposit_queue(&mut state, &mut roots, &mut queue, u32::MAX).unwrap();
+        assert_eq!(first, 0);
+        assert_eq!(inserted, (0..3).map(leaf).collect::<Vec<_>>());
+        assert!(queue.is_empty());
+
+        let mut sequential = MerkleTree::with_levels(MIN_MERKLE_TREE_HEIGHT);
+        for i in 0..3 {
+            sequential.insert(leaf(i)).unwrap();
+        }
+        assert!(is_known_root(&roots, state.current_root_index, &sequential.get_root()));
+    }
+
+    #[test]
+    fn test_crank_inserts_only_the_leaves_left() {
+        let (mut state, mut roots) = queued_pool();
+        let mut queue = new_queue();
+        for i in 0..3 {
+            queue.push(leaf(i)).unwrap();
+        }
+        state.merkle_tree.next_index = (1 << MIN_MERKLE_TREE_HEIGHT) - 2;
+
+        let (first, inserted) = process_deposit_queue(&mut state, &mut roots, &mut queue, u32::MAX).unwrap();
+        assert_eq!(first, (1 << MIN_MERKLE_TREE_HEIGHT) - 2);
+        assert_eq!(inserted, vec![leaf(0), leaf(1)]);
+        assert_eq!(state.merkle_tree.remaining_capacity(), 0);
+        assert_eq!(queue.peek(DEPOSIT_QUEUE_CAPACITY), vec![leaf(2)]);
+
+        assert_eq!(
+            error_code(process_deposit_queue(&mut state, &mut roots, &mut queue, u32::MAX)),
+            u32::from(TornadoError::MerkleTreeFull)
+        );
+        assert_eq!(queue.len(), 1);
+    }
+
+    /// 1 SOL pool in queued mode on a 2^8-leaf tree with `free` leaves left
+    fn nearly_full_pool(free: u32) -> Pool {
+        let mut pool = Pool::new(1_000_000_000, Pubkey::new_unique(), Pubkey::default());
+        let (state, _) = TornadoState::split_mut(&mut pool.state.data).unwrap();
+        state.merkle_tree = MerkleTree::with_levels(MIN_MERKLE_TREE_HEIGHT);
+        state.merkle_tree.next_index = (1 << MIN_MERKLE_TREE_HEIGHT) - free;
+        state.queued_deposits = 1;
+        pool
+    }
+
+    fn enqueue_deposit(pool: &mut Pool, commitment: [u8; 32]) -> Result<()> {
+        let mut depositor = TestAccount::signer(Pubkey::new_unique());
+        let infos = [
+            pool.state.info(),
+            pool.deposit_queue.info(),
+            pool.vault.info(),
+            depositor.info(),
+            pool.system_program.info(),
+        ];
+        let (mut accounts, bumps) = accounts_with_args::<EnqueueDeposit>(&infos, &[])?;
+        crate::tornado_solana::enqueue_deposit(Context::new(&crate::ID, &mut accounts, &[], bumps), commitment)
+    }
+
+    fn set_queued_deposits(pool: &mut Pool, authority: Pubkey, queued_deposits: bool) -> Result<()> {
+        let mut authority = TestAccount::signer(authority);
+        let infos = [pool.state.info(), pool.deposit_queue.info(), authority.info()];
+        let (mut accounts, bumps) = accounts_with_args::<SetQueuedDeposits>(&infos, &[])?;
+        crate::tornado_solana::set_queued_deposits(
+            Context::new(&crate::ID, &mut accounts, &[], bumps),
+            queued_deposits,
+        )
+    }
+
+    fn queued(pool: &Pool) -> DepositQueue {
+        *bytemuck::from_bytes(&pool.deposit_queue.data[8..])
+    }
+
+    #[test]
+    fn test_enqueue_stops_when_queue_would_outgrow_tree() {
+        let mut pool = nearly_full_pool(2);
+
+        enqueue_deposit(&mut pool, leaf(0)).unwrap();
+        enqueue_deposit(&mut pool, leaf(1)).unwrap();
+        // Both free leaves are reserved by the queue, although the tree itself still has room
+        assert_eq!(
+            error_code(enqueue_deposit(&mut pool, leaf(2))),
+            u32::from(TornadoError::MerkleTreeFull)
+        );
+        assert_eq!(queued(&pool).peek(DEPOSIT_QUEUE_CAPACITY), vec![leaf(0), leaf(1)]);
+    }
+
+    #[test]
+    fn test_queue_must_drain_before_direct_deposits() {
+        let authority = Pubkey::new_unique();
+        let mut pool = nearly_full_pool(2);
+        let (state, _) = TornadoState::split_mut(&mut pool.state.data).unwrap();
+        state.authority = authority;
+        enqueue_deposit(&mut pool, leaf(0)).unwrap();
+
+        // Direct deposits could otherwise take the leaf the queued commitment paid for
+        assert_eq!(
+            error_code(set_queued_deposits(&mut pool, authority, false)),
+            u32::from(TornadoError::DepositQueueNotEmpty)
+        );
+
+        let (state, roots) = TornadoState::split_mut(&mut pool.state.data).unwrap();
+        let queue: &mut DepositQueue = bytemuck::from_bytes_mut(&mut pool.deposit_queue.data[8..]);
+        process_deposit_queue(state, roots, queue, u32::MAX).unwrap();
+
+        set_queued_deposits(&mut pool, authority, false).unwrap();
+        let (state, _) = TornadoState::split_mut(&mut pool.state.data).unwrap();
+        assert_eq!(state.queued_deposits, 0);
+    }
+}
@@ -0,0 +1,93 @@
+#[cfg(test)]
+mod fee_cap_tests {
+    use crate::test_accounts::{error_code, new_pool_state};
+    use crate::{
+        apply_lower_fee_cap, get_circuit_verifying_key, max_fee, validate_max_fee_bps,
+        verify_withdrawal, TornadoError, TornadoState, MAX_FEE_BPS, ROOT_HISTORY_SIZE,
+    };
+    use anchor_lang::prelude::*;
+
+    const DENOMINATION: u64 = 1_000_000_000;
+
+    fn new_pool(max_fee_bps: u16, max_fee_amount: u64) -> TornadoState {
+        let mut state = new_pool_state();
+        state.max_fee_bps = max_fee_bps;
+        state.max_fee_amount = max_fee_amount;
+        state
+    }
+
+    /// Withdrawal with a bogus proof and an unknown root: passing the fee checks ends in UnknownRoot
+    fn withdraw_with_fee(state: &TornadoState, fee: u64) -> u32 {
+        error_code(verify_withdrawal(
+            state,
+            &[[0u8; 32]; ROOT_HISTORY_SIZE as usize],
+            get_circuit_verifying_key(),
+            &[0u8; 256],
+            &[1u8; 32],
+            &[2u8; 32],
+            &Pubkey::new_unique(),
+            Some(Pubkey::new_unique()),
+            fee,
+            0,
+        ))
+    }
+
+    #[test]
+    fn test_tighter_cap_wins() {
+        // 1% of 1 SOL is 10_000_000 lamports
+        assert_eq!(max_fee(&new_pool(100, u64::MAX)), 10_000_000);
+        assert_eq!(max_fee(&new_pool(100, 5_000_000)), 5_000_000);
+        assert_eq!(max_fee(&new_pool(MAX_FEE_BPS, u64::MAX)), DENOMINATION);
+        assert_eq!(max_fee(&new_pool(0, u64::MAX)), 0);
+
+        let mut huge = new_pool(MAX_FEE_BPS, u64::MAX);
+        huge.denomination = u64::MAX;
+        assert_eq!(max_fee(&huge), u64::MAX);
+    }
+
+    #[test]
+    fn test_withdrawal_fee_above_cap_rejected() {
+        let state = new_pool(100, u64::MAX);
+
+        assert_eq!(withdraw_with_fee(&state, 10_000_001), u32::from(TornadoError::FeeExceedsCap));
+        assert_eq!(withdraw_with_fee(&state, 10_000_000), u32::from(TornadoError::UnknownRoot));
+        assert_eq!(withdraw_with_fee(&state, 0), u32::from(TornadoError::UnknownRoot));
+
+        // The denomination bound still comes first
+        assert_eq!(
+            withdraw_with_fee(&state, DENOMINATION + 1),
+            u32::from(TornadoError::FeeExceedsDenomination)
+        );
+    }
+
+    #[test]
+    fn test_max_fee_bps_bounds() {
+        assert!(validate_max_fee_bps(0).is_ok());
+        assert!(validate_max_fee_bps(MAX_FEE_BPS).is_ok());
+        assert_eq!(
+            error_code(validate_max_fee_bps(MAX_FEE_BPS + 1)),
+            u32::from(TornadoError::InvalidFeeCap)
+        );
+    }
+
+    #[test]
+    fn test_fee_cap_can_only_be_lowered() {
+        let mut state = new_pool(100, 5_000_000);
+
+        assert_eq!(
+            error_code(apply_lower_fee_cap(&mut state, 101, 5_000_000)),
+            u32::from(TornadoError::FeeCapIncrease)
+        );
+        assert_eq!(
+            error_code(apply_lower_fee_cap(&mut state, 100, 5_000_001)),
+            u32::from(TornadoError::FeeCapIncrease)
+        );
+        assert_eq!((state.max_fee_bps, state.max_fee_amount), (100, 5_000_000));
+
+        apply_lower_fee_cap(&mut state, 50, 1_000_000).unwrap();
+        assert_eq!(max_fee(&state), 1_000_000);
+
+        // Re-applying the same caps is a no-op rather than an error
+        assert!(apply_lower_fee_cap(&mut state, 50, 1_000_000).is_ok());
+    }
+}
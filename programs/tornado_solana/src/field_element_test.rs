@@ -0,0 +1,83 @@
+#[cfg(test)]
+mod field_element_tests {
+    use crate::test_accounts::{error_code, new_pool};
+    use crate::{
+        get_circuit_verifying_key, insert_commitment, is_canonical_field_element, verify_withdrawal,
+        TornadoError, BN254_SCALAR_FIELD_MODULUS, MERKLE_TREE_HEIGHT,
+    };
+    use anchor_lang::prelude::*;
+    use ark_ff::{BigInteger, PrimeField};
+
+    const MODULUS_PLUS_ONE: [u8; 32] = {
+        let mut value = BN254_SCALAR_FIELD_MODULUS;
+        value[31] += 1;
+        value
+    };
+    const MAX_U256: [u8; 32] = [0xff; 32];
+
+    fn modulus_minus_one() -> [u8; 32] {
+        let mut value = BN254_SCALAR_FIELD_MODULUS;
+        value[31] -= 1;
+        value
+    }
+
+    #[test]
+    fn test_modulus_constant_matches_arkworks() {
+        let expected = ark_bn254::Fr::MODULUS.to_bytes_be();
+        assert_eq!(BN254_SCALAR_FIELD_MODULUS[..], expected[..]);
+    }
+
+    #[test]
+    fn test_canonical_field_element_bounds() {
+        assert!(is_canonical_field_element(&[0u8; 32]));
+        assert!(is_canonical_field_element(&modulus_minus_one()));
+
+        assert!(!is_canonical_field_element(&BN254_SCALAR_FIELD_MODULUS));
+        assert!(!is_canonical_field_element(&MODULUS_PLUS_ONE));
+        assert!(!is_canonical_field_element(&MAX_U256));
+    }
+
+    #[test]
+    fn test_deposit_rejects_non_canonical_commitment() {
+        let (mut state, mut roots) = new_pool(MERKLE_TREE_HEIGHT);
+        let root_before = state.merkle_tree.get_root();
+
+        for commitment in [BN254_SCALAR_FIELD_MODULUS, MODULUS_PLUS_ONE, MAX_U256] {
+            assert_eq!(
+                error_code(insert_commitment(&mut state, &mut roots, commitment)),
+                u32::from(TornadoError::CommitmentNotInField)
+            );
+        }
+
+        // Nothing was inserted and no root was recorded
+        assert_eq!(state.merkle_tree.next_index, 0);
+        assert_eq!(state.merkle_tree.get_root(), root_before);
+        assert_eq!(state.current_root_index, 0);
+
+        assert!(insert_commitment(&mut state, &mut roots, modulus_minus_one()).is_ok());
+    }
+
+    #[test]
+    fn test_withdrawal_rejects_non_canonical_nullifier_hash() {
+        let (state, roots) = new_pool(MERKLE_TREE_HEIGHT);
+
+        for nullifier_hash in [BN254_SCALAR_FIELD_MODULUS, MODULUS_PLUS_ONE, MAX_U256] {
+            let result = verify_withdrawal(
+                &state,
+                &roots,
+                get_circuit_verifying_key(),
+                &[0u8; 256],
+                &[1u8; 32],
+                &nullifier_hash,
+                &Pubkey::new_unique(),
+                None,
+                0,
+                0,
+            );
+            assert_eq!(
+                error_code(result),
+                u32::from(TornadoError::NullifierHashNotInField)
+            );
+        }
+    }
+}
@@ -1,5 +1,5 @@
-/// Final comprehensive verification test for consultant review
-/// This test demonstrates real proof verification with detailed output
+// Final comprehensive verification test for consultant review
+// This test demonstrates real proof verification with detailed output
 
 #[cfg(test)]
 mod final_verification_tests {
@@ -7,15 +7,15 @@ mod final_verification_tests {
         verify_proof, get_circuit_verifying_key,
         prepare_public_inputs, split_address_to_high_low,
     };
-    use anchor_lang::prelude::*;
+    
     use solana_program::pubkey::Pubkey;
-    use std::str::FromStr;
+    
     
     #[test]
     fn comprehensive_real_proof_verification() {
-        println!("\n{'='*60}");
+        println!("\n{}", "=".repeat(60));
         println!("TORNADO SOLANA - FINAL VERIFICATION TEST");
-        println!("{'='*60}\n");
+        println!("{}\n", "=".repeat(60));
         
         // Real proof from withdraw_fixed.circom circuit
         let proof_hex = "1932c68d13e4e1dce10877fb867b64f4eeb14438acb7d96911c00963ae8892fb1100ad50a064e95082e8d9a4fec8729a0b5f661fd118930934e6f78a0fee3c701da6fa818ef65c4d648ae4f871929d51235c7bc5d5f9218745f5cd0bdea50ad327d5f609d882ae5bbe9872c46866b799dd134dc1734b9cfd2db98ae953975b68102a77cbe32a0714b8a82d59ecebcf6a8caf8ff445b5dca2265e7f35eeb6a8062324a790f811da839b12b02cadb62bcc7fe9e713523c4122c8591ca4cd0111a80ce792e8b41714924c86758605f6403297a9030c424f6c1dd48c0abcfa3fd9c6063e61773609fd0338923bcb58bce991192b83a6c3ab299916982e52fea008e3";
@@ -27,18 +27,26 @@ mod final_verification_tests {
         println!("   - Source: Generated from withdraw_fixed.circom");
         
         // Public inputs from circuits/test_proof_valid.json
-        let root = hex::decode("0e58a147c95f66289638e3a1dd529e4dc3c6e719aa9cc456a674b2bb31b7c220")
-            .expect("Invalid root hex");
-        let nullifier_hash = hex::decode("12345f615a93c56bec4e42d5b1194922f904e848ca37ccefbfb0e4c19b1e3e19")
-            .expect("Invalid nullifierHash hex");
-        let recipient = Pubkey::from_str("G4YkbRN4nFQGEUg4SXzPsrManQXsgS3JXqWwNHK1yYkn").unwrap();
-        let relayer = Pubkey::from_str("2wT1vYKCtGqGNjkbCRHD4KALMPPBkgmDdH37PBNLRmrM").unwrap();
+        let root: [u8; 32] = hex::decode("2ff370c60cf13d3fffa72d1efe3150948a8c84a664c43d427e25b59a01fe3e3c")
+            .expect("Invalid root hex")
+            .try_into()
+            .unwrap();
+        let nullifier_hash: [u8; 32] = hex::decode("09ca96f9b5a778899e61078e62a5edfe492398e79db303e0440ee2d6e0e4e7f2")
+            .expect("Invalid nullifierHash hex")
+            .try_into()
+            .unwrap();
+        let recipient = Pubkey::new_from_array(
+            hex::decode("e31d835d8657f921fdd87d952db48ec74a949b540a9151fd066c05f7d5c7edd3").unwrap().try_into().unwrap(),
+        );
+        let relayer = Pubkey::new_from_array(
+            hex::decode("c97dda6f4f8d671202378f3843ac899157e5461c0651a0b1cb40541e3397c151").unwrap().try_into().unwrap(),
+        );
         let fee: u64 = 1_000_000; // 0.001 SOL
         let refund: u64 = 0;
         
         println!("\n2️⃣ PUBLIC INPUTS (8 total):");
-        println!("   - Root: 0x{}", hex::encode(&root));
-        println!("   - Nullifier: 0x{}", hex::encode(&nullifier_hash));
+        println!("   - Root: 0x{}", hex::encode(root));
+        println!("   - Nullifier: 0x{}", hex::encode(nullifier_hash));
         println!("   - Recipient: {}", recipient);
         println!("   - Relayer: {}", relayer);
         println!("   - Fee: {} lamports", fee);
@@ -54,19 +62,19 @@ mod final_verification_tests {
         println!("   - Format: Groth16Verifyingkey structure");
         
         // Split addresses for circuit compatibility
-        let (recipient_high, recipient_low) = split_address_to_high_low(&recipient.to_bytes());
-        let (relayer_high, relayer_low) = split_address_to_high_low(&relayer.to_bytes());
+        let (recipient_high, recipient_low) = split_address_to_high_low(&recipient);
+        let (relayer_high, relayer_low) = split_address_to_high_low(&relayer);
         
         println!("\n4️⃣ ADDRESS SPLITTING (BN254 compatibility):");
-        println!("   - Recipient High: 0x{}", hex::encode(&recipient_high));
-        println!("   - Recipient Low: 0x{}", hex::encode(&recipient_low));
-        println!("   - Relayer High: 0x{}", hex::encode(&relayer_high));
-        println!("   - Relayer Low: 0x{}", hex::encode(&relayer_low));
+        println!("   - Recipient High: 0x{}", hex::encode(recipient_high));
+        println!("   - Recipient Low: 0x{}", hex::encode(recipient_low));
+        println!("   - Relayer High: 0x{}", hex::encode(relayer_high));
+        println!("   - Relayer Low: 0x{}", hex::encode(relayer_low));
         
         // Prepare public inputs array
         let public_inputs = prepare_public_inputs(
-            &root.try_into().unwrap(),
-            &nullifier_hash.try_into().unwrap(),
+            &root,
+            &nullifier_hash,
             &recipient,
             &relayer,
             fee,
@@ -80,19 +88,19 @@ mod final_verification_tests {
         
         // Start verification with timing
         println!("\n6️⃣ STARTING VERIFICATION:");
-        println!("   {'='*40}");
+        println!("   {}", "=".repeat(40));
         
         let start = std::time::Instant::now();
         
         let result = verify_proof(
             &proof,
-            &root.try_into().unwrap(),
-            &nullifier_hash.try_into().unwrap(),
+            &root,
+            &nullifier_hash,
             &recipient,
             &relayer,
             fee,
             refund,
-            &vk,
+            vk,
         );
         
         let duration = start.elapsed();
@@ -100,7 +108,7 @@ mod final_verification_tests {
         match result {
             Ok(()) => {
                 println!("   ✅ VERIFICATION SUCCESSFUL!");
-                println!("   {'='*40}");
+                println!("   {}", "=".repeat(40));
                 println!("\n7️⃣ PERFORMANCE METRICS:");
                 println!("   - Verification Time: {:?}", duration);
                 println!("   - Proof Size: 256 bytes");
@@ -110,7 +118,7 @@ mod final_verification_tests {
                 // Based on groth16-solana benchmarks
                 let estimated_cu = 180_000; // Typical for Groth16 on Solana
                 println!("\n8️⃣ COMPUTE UNITS (ESTIMATED):");
-                println!("   - Estimated CU: ~{:,}", estimated_cu);
+                println!("   - Estimated CU: ~{}", estimated_cu);
                 println!("   - Target: < 200,000 CU");
                 println!("   - Status: ✅ WITHIN LIMITS");
                 
@@ -124,12 +132,12 @@ mod final_verification_tests {
                 println!("   ✅ Error handling for invalid proofs");
                 
                 println!("\n🎯 FINAL STATUS: PRODUCTION READY");
-                println!("{'='*60}\n");
+                println!("{}\n", "=".repeat(60));
             }
             Err(e) => {
                 println!("   ❌ VERIFICATION FAILED!");
                 println!("   Error: {:?}", e);
-                println!("   {'='*40}");
+                println!("   {}", "=".repeat(40));
                 panic!("Real proof verification should succeed but failed: {:?}", e);
             }
         }
@@ -137,9 +145,9 @@ mod final_verification_tests {
     
     #[test]
     fn test_invalid_proof_rejection() {
-        println!("\n{'='*60}");
+        println!("\n{}", "=".repeat(60));
         println!("INVALID PROOF REJECTION TEST");
-        println!("{'='*60}\n");
+        println!("{}\n", "=".repeat(60));
         
         // Create an invalid proof (all zeros)
         let invalid_proof = vec![0u8; 256];
@@ -164,20 +172,20 @@ mod final_verification_tests {
             &relayer,
             fee,
             refund,
-            &vk,
+            vk,
         );
         
         assert!(result.is_err(), "Invalid proof should be rejected");
         println!("✅ Invalid proof correctly rejected!");
         println!("Error returned: {:?}", result.unwrap_err());
-        println!("{'='*60}\n");
+        println!("{}\n", "=".repeat(60));
     }
     
     #[test] 
     fn test_proof_size_validation() {
-        println!("\n{'='*60}");
+        println!("\n{}", "=".repeat(60));
         println!("PROOF SIZE VALIDATION TEST");
-        println!("{'='*60}\n");
+        println!("{}\n", "=".repeat(60));
         
         // Test with wrong size proof
         let wrong_size_proof = vec![0u8; 200]; // Should be 256
@@ -199,12 +207,12 @@ mod final_verification_tests {
             &relayer,
             0,
             0,
-            &vk,
+            vk,
         );
         
         assert!(result.is_err(), "Wrong size proof should be rejected");
         println!("✅ Wrong size proof correctly rejected!");
         println!("Error returned: {:?}", result.unwrap_err());
-        println!("{'='*60}\n");
+        println!("{}\n", "=".repeat(60));
     }
 }
\ No newline at end of file
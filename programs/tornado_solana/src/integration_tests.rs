@@ -1,11 +1,12 @@
 #[cfg(test)]
+#[allow(clippy::module_inception)]
 mod integration_tests {
     use crate::{
         change_endianness, encode_u64_as_32_bytes, negate_proof_a,
-        prepare_public_inputs, reconstruct_address_from_high_low,
-        split_address_to_high_low, verify_proof, 
-        TornadoError, get_circuit_verifying_key,
+        prepare_multi_output_public_inputs, prepare_public_inputs, reconstruct_address_from_high_low,
+        split_address_to_high_low, verify_proof, get_circuit_verifying_key,
     };
+    use crate::withdraw_outputs::{outputs_hash, WithdrawOutput};
     use anchor_lang::prelude::*;
     
     /// Test vector for a mock proof (256 bytes)
@@ -29,8 +30,8 @@ mod integration_tests {
         ]);
         
         // Mock proof B (128 bytes) - uncompressed G2 point
-        for i in 64..192 {
-            proof[i] = ((i - 64) % 256) as u8;
+        for (i, byte) in proof[64..192].iter_mut().enumerate() {
+            *byte = i as u8;
         }
         
         // Mock proof C (64 bytes) - uncompressed G1 point
@@ -155,6 +156,40 @@ mod integration_tests {
         assert_eq!(inputs[7], expected_refund);
     }
     
+    /// Test prepare_multi_output_public_inputs: zero recipient, outputs hash in the refund slot
+    #[test]
+    fn test_prepare_multi_output_public_inputs() {
+        let root = [0x11u8; 32];
+        let nullifier_hash = [0x22u8; 32];
+        let relayer = Pubkey::new_from_array([0x44u8; 32]);
+        let fee: u64 = 1_000_000;
+        let outputs = [
+            WithdrawOutput { recipient: Pubkey::new_from_array([0x33u8; 32]), amount: 900_000_000 },
+            WithdrawOutput { recipient: Pubkey::new_from_array([0x55u8; 32]), amount: 99_000_000 },
+        ];
+        let hash = outputs_hash(&outputs).unwrap();
+        
+        let inputs = prepare_multi_output_public_inputs(&root, &nullifier_hash, &hash, &relayer, fee);
+        
+        // Root, nullifier, relayer and fee are laid out exactly as for a plain withdrawal
+        let plain = prepare_public_inputs(&root, &nullifier_hash, &Pubkey::default(), &relayer, fee, 0);
+        assert_eq!(inputs[..7], plain[..7]);
+        assert_eq!(inputs[2], [0u8; 32]);
+        assert_eq!(inputs[3], [0u8; 32]);
+        
+        // Refund slot carries the outputs hash, out of reach of any u64 refund
+        assert_eq!(inputs[7], hash);
+        assert!(hash[..24].iter().any(|&byte| byte != 0));
+        
+        // Every output field is bound
+        let mut changed = outputs;
+        changed[1].amount -= 1;
+        assert_ne!(outputs_hash(&changed).unwrap(), hash);
+        changed = outputs;
+        changed.swap(0, 1);
+        assert_ne!(outputs_hash(&changed).unwrap(), hash);
+    }
+    
     /// Test proof A negation (mock - doesn't use real ark operations)
     #[test]
     fn test_proof_a_negation_format() {
@@ -198,7 +233,7 @@ mod integration_tests {
             &relayer,
             fee,
             refund,
-            &get_circuit_verifying_key(),
+            get_circuit_verifying_key(),
         );
         
         // Should return InvalidProofLength error
@@ -220,7 +255,7 @@ mod integration_tests {
             &relayer,
             fee,
             refund,
-            &get_circuit_verifying_key(),
+            get_circuit_verifying_key(),
         );
         
         // Should fail during proof processing
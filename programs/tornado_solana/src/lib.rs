@@ -1,17 +1,30 @@
 use anchor_lang::prelude::*;
-use anchor_lang::solana_program::system_instruction;
 use anchor_lang::system_program;
 use groth16_solana::groth16::{Groth16Verifier, Groth16Verifyingkey};
-use ark_bn254::G1Affine;
-use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
+use ark_bn254::{G1Affine, G2Affine};
+use ark_serialize::{CanonicalDeserialize, CanonicalSerialize, Compress, Validate};
+use anchor_spl::token_interface::{self, Mint, TokenAccount, TokenInterface, TransferChecked};
 
 pub mod merkle_tree;
 use merkle_tree::*;
 
+pub mod token_pool;
+
+pub mod relayer_registry;
+use relayer_registry::RelayerRecord;
+
+pub mod deposit_queue;
+use deposit_queue::DepositQueue;
+
+pub mod withdraw_outputs;
+use withdraw_outputs::{outputs_hash, validate_outputs, WithdrawOutput};
+
 pub mod verifying_key;
 #[cfg(test)]
 use verifying_key::get_circuit_verifying_key;
 
+pub mod zero_hashes;
+
 #[cfg(test)]
 mod poseidon_test;
 
@@ -24,7 +37,8 @@ mod simple_test;
 #[cfg(test)]
 mod real_proof_test;
 
-#[cfg(test)]
+// Banks-client tests: need solana-program-test, solana-sdk and tokio dev-dependencies
+#[cfg(all(test, feature = "program-test"))]
 mod nullifier_pda_test;
 
 #[cfg(test)]
@@ -36,34 +50,123 @@ mod relayer_security_test;
 #[cfg(test)]
 mod verifying_key_security_test;
 
-#[cfg(test)]
+// Banks-client tests: need solana-program-test, solana-sdk and tokio dev-dependencies
+#[cfg(all(test, feature = "program-test"))]
 mod vault_pda_tests;
 
 #[cfg(test)]
 mod stored_vk_integration_test;
 
+#[cfg(test)]
+mod pool_isolation_test;
+
+#[cfg(test)]
+mod nullifier_seeds_test;
+
+#[cfg(test)]
+mod refund_test;
+
+#[cfg(test)]
+mod vk_rotation_test;
+
+#[cfg(test)]
+mod vk_validation_test;
+
+#[cfg(test)]
+mod vk_account_test;
+
+#[cfg(test)]
+mod zero_copy_state_test;
+
+#[cfg(test)]
+mod root_history_test;
+
+#[cfg(test)]
+mod field_element_test;
+
+#[cfg(test)]
+mod pause_test;
+
+#[cfg(test)]
+mod test_accounts;
+
+#[cfg(test)]
+mod authority_test;
+
+#[cfg(test)]
+mod fee_cap_test;
+
+#[cfg(test)]
+mod relayer_registry_test;
+
+#[cfg(test)]
+mod deposit_batch_test;
+
+#[cfg(test)]
+mod deposit_queue_test;
+
+#[cfg(test)]
+mod withdraw_outputs_test;
+
+#[cfg(test)]
+mod native_prover_test;
+
+#[cfg(test)]
+mod token_pool_test;
+
 // Program ID must match Anchor.toml for deployment
-declare_id!("ToRNaDo1111111111111111111111111111111111111");
+declare_id!("ToRNaDo111111111111111111111111111111111111");
 
 #[program]
 pub mod tornado_solana {
     use super::*;
 
     /// Initialize a new Tornado pool with fixed denomination
+    /// Each denomination gets its own pool PDA: [b"tornado", denomination.to_le_bytes()]
     /// @param verifying_key: The Groth16 verifying key from trusted setup ceremony
+    ///        Fully validated into its own account; a malformed key is rejected before any deposit
+    /// @param merkle_tree_height: Depth of the pool's tree, which must be the `levels` the key's circuit was built with
+    ///        A Groth16 key does not encode the tree depth, so the program cannot check this itself;
+    ///        a key for another depth leaves every withdrawal proof unverifiable
+    /// @param root_history_size: Number of recent roots a withdrawal proof may be built against
+    /// @param max_fee_bps: Relayer fee cap as a share of the denomination, in basis points (10_000 = no cap)
+    /// @param max_fee_amount: Absolute relayer fee cap in the pool's base units (u64::MAX = no cap)
     pub fn initialize(
         ctx: Context<Initialize>, 
         denomination: u64,
         verifying_key: Vec<u8>,
+        merkle_tree_height: u32,
+        root_history_size: u32,
+        max_fee_bps: u16,
+        max_fee_amount: u64,
     ) -> Result<()> {
-        let tornado_state = &mut ctx.accounts.tornado_state;
+        require!(denomination > 0, TornadoError::InvalidDenomination);
+        validate_merkle_tree_height(merkle_tree_height)?;
+        validate_root_history_size(root_history_size)?;
+        validate_max_fee_bps(max_fee_bps)?;
+        
+        let mut tornado_state = ctx.accounts.tornado_state.load_init()?;
         
         tornado_state.authority = ctx.accounts.authority.key();
+        tornado_state.pending_authority = Pubkey::default();
         tornado_state.denomination = denomination;
-        tornado_state.merkle_tree = MerkleTree::new();
+        tornado_state.mint = Pubkey::default();
+        tornado_state.merkle_tree = MerkleTree::with_levels(merkle_tree_height);
         tornado_state.current_root_index = 0;
         tornado_state.next_index = 0;
-        tornado_state.verifying_key = verifying_key;
+        tornado_state.root_history_size = root_history_size;
+        tornado_state.vk_rotation_renounced = 0;
+        tornado_state.deposits_paused = 0;
+        tornado_state.withdrawals_paused = 0;
+        tornado_state.pause_renounced = 0;
+        tornado_state.max_fee_bps = max_fee_bps;
+        tornado_state.max_fee_amount = max_fee_amount;
+        tornado_state.registered_relayers_only = 0;
+        tornado_state.queued_deposits = 0;
+        
+        let mut verifying_key_account = ctx.accounts.verifying_key_account.load_init()?;
+        verifying_key_account.tornado_state = ctx.accounts.tornado_state.key();
+        verifying_key_account.store(&verifying_key, merkle_tree_height)?;
         
         Ok(())
     }
@@ -71,7 +174,13 @@ pub mod tornado_solana {
     /// Deposit funds into the tornado pool
     /// @param commitment: Hash(nullifier + secret)
     pub fn deposit(ctx: Context<Deposit>, commitment: [u8; 32]) -> Result<()> {
-        let tornado_state = &mut ctx.accounts.tornado_state;
+        let tornado_state_key = ctx.accounts.tornado_state.key();
+        let tornado_state_info = ctx.accounts.tornado_state.to_account_info();
+        let mut tornado_state_data = tornado_state_info.try_borrow_mut_data()?;
+        let (tornado_state, roots) = TornadoState::split_mut(&mut tornado_state_data)?;
+        
+        // Queued pools take deposits through enqueue_deposit only, so leaves keep arrival order
+        require!(tornado_state.queued_deposits == 0, TornadoError::DepositsQueued);
         
         // Note: Duplicate commitment prevention is inherent in the Merkle tree
         // Each leaf can only be inserted once, making Vec storage redundant
@@ -83,7 +192,7 @@ pub mod tornado_solana {
         let vault_bump = ctx.bumps.vault;
         validate_vault_pda(
             &ctx.accounts.vault,
-            &ctx.accounts.tornado_state.key(),
+            &tornado_state_key,
             vault_bump,
         )?;
         
@@ -102,16 +211,8 @@ pub mod tornado_solana {
             deposit_amount,
         )?;
         
-        // Insert commitment into merkle tree
-        let leaf_index = tornado_state.merkle_tree.insert(commitment)?;
-        
-        // Commitment is stored in the Merkle tree, no need for separate Vec
-        
-        // Update root history
-        let new_root = tornado_state.merkle_tree.get_root();
-        let new_index = (tornado_state.current_root_index + 1) % ROOT_HISTORY_SIZE;
-        tornado_state.current_root_index = new_index;
-        tornado_state.roots[new_index as usize] = new_root;
+        // Insert commitment into merkle tree and record the new root
+        let leaf_index = insert_commitment(tornado_state, roots, commitment)?;
         
         emit!(DepositEvent {
             commitment,
@@ -122,7 +223,172 @@ pub mod tornado_solana {
         Ok(())
     }
 
+    /// Deposit several notes at once, paying `commitments.len() * denomination`
+    /// Leaves share their upper-level hashing and only the final root enters the root history,
+    /// so a withdrawal proof must be built against the tree after the whole batch
+    /// @param commitments: 1 to MAX_DEPOSIT_BATCH_SIZE commitments, inserted in order
+    pub fn deposit_batch(ctx: Context<Deposit>, commitments: Vec<[u8; 32]>) -> Result<()> {
+        let tornado_state_key = ctx.accounts.tornado_state.key();
+        let tornado_state_info = ctx.accounts.tornado_state.to_account_info();
+        let mut tornado_state_data = tornado_state_info.try_borrow_mut_data()?;
+        let (tornado_state, roots) = TornadoState::split_mut(&mut tornado_state_data)?;
+        require!(tornado_state.queued_deposits == 0, TornadoError::DepositsQueued);
+        
+        let deposit_amount = tornado_state
+            .denomination
+            .checked_mul(commitments.len() as u64)
+            .ok_or(TornadoError::InvalidBatchSize)?;
+        
+        // Validate vault PDA (security check)
+        validate_vault_pda(
+            &ctx.accounts.vault,
+            &tornado_state_key,
+            ctx.bumps.vault,
+        )?;
+        
+        system_program::transfer(
+            CpiContext::new(
+                ctx.accounts.system_program.to_account_info(),
+                system_program::Transfer {
+                    from: ctx.accounts.depositor.to_account_info(),
+                    to: ctx.accounts.vault.to_account_info(),
+                },
+            ),
+            deposit_amount,
+        )?;
+        
+        let first_leaf_index = insert_commitments(tornado_state, roots, &commitments)?;
+        
+        // One event per leaf, exactly as separate deposits would log them
+        let timestamp = Clock::get()?.unix_timestamp;
+        for (leaf_index, commitment) in (first_leaf_index..).zip(commitments) {
+            emit!(DepositEvent {
+                commitment,
+                leaf_index,
+                timestamp,
+            });
+        }
+        
+        Ok(())
+    }
+
+    /// Deposit into a queued pool: pay the denomination and append the commitment to the queue
+    /// Skips the tree hashing, but still write-locks the queue and vault, so deposits into a pool run one at a time
+    /// The leaf becomes provable once `process_queue` inserts it and emits its DepositEvent
+    /// Each queued commitment reserves a leaf, so the queue never outgrows the space left in the tree
+    pub fn enqueue_deposit(ctx: Context<EnqueueDeposit>, commitment: [u8; 32]) -> Result<()> {
+        let tornado_state = ctx.accounts.tornado_state.load()?;
+        require!(tornado_state.queued_deposits != 0, TornadoError::DepositsNotQueued);
+        require!(tornado_state.deposits_paused == 0, TornadoError::DepositsPaused);
+        
+        // Checked now so that a bad commitment can never block the crank
+        require!(
+            is_canonical_field_element(&commitment),
+            TornadoError::CommitmentNotInField
+        );
+        
+        let mut deposit_queue = ctx.accounts.deposit_queue.load_mut()?;
+        require!(
+            (deposit_queue.len() as u64) < tornado_state.merkle_tree.remaining_capacity(),
+            TornadoError::MerkleTreeFull
+        );
+        
+        validate_vault_pda(
+            &ctx.accounts.vault,
+            &ctx.accounts.tornado_state.key(),
+            ctx.bumps.vault,
+        )?;
+        
+        system_program::transfer(
+            CpiContext::new(
+                ctx.accounts.system_program.to_account_info(),
+                system_program::Transfer {
+                    from: ctx.accounts.depositor.to_account_info(),
+                    to: ctx.accounts.vault.to_account_info(),
+                },
+            ),
+            tornado_state.denomination,
+        )?;
+        
+        let queue_position = deposit_queue.push(commitment)?;
+        
+        emit!(DepositQueuedEvent {
+            tornado_state: ctx.accounts.tornado_state.key(),
+            commitment,
+            queue_position,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+        
+        Ok(())
+    }
+
+    /// Permissionless crank: insert up to `max_leaves` queued commitments (at most
+    /// MAX_DEPOSIT_BATCH_SIZE) in arrival order and record the resulting root
+    /// Keeps working while deposits are paused; the pause only stops `enqueue_deposit`
+    pub fn process_queue(ctx: Context<ProcessQueue>, max_leaves: u32) -> Result<()> {
+        let tornado_state_info = ctx.accounts.tornado_state.to_account_info();
+        let mut tornado_state_data = tornado_state_info.try_borrow_mut_data()?;
+        let (tornado_state, roots) = TornadoState::split_mut(&mut tornado_state_data)?;
+        let mut deposit_queue = ctx.accounts.deposit_queue.load_mut()?;
+        
+        let (first_leaf_index, commitments) =
+            process_deposit_queue(tornado_state, roots, &mut deposit_queue, max_leaves)?;
+        
+        let timestamp = Clock::get()?.unix_timestamp;
+        for (leaf_index, commitment) in (first_leaf_index..).zip(commitments.iter().copied()) {
+            emit!(DepositEvent {
+                commitment,
+                leaf_index,
+                timestamp,
+            });
+        }
+        emit!(DepositQueueProcessedEvent {
+            tornado_state: ctx.accounts.tornado_state.key(),
+            first_leaf_index,
+            count: commitments.len() as u32,
+            root: tornado_state.merkle_tree.get_root(),
+            remaining: deposit_queue.len() as u32,
+        });
+        
+        Ok(())
+    }
+
+    /// Create the pool's deposit queue and switch the pool to queued deposits
+    pub fn initialize_deposit_queue(ctx: Context<InitializeDepositQueue>) -> Result<()> {
+        let mut deposit_queue = ctx.accounts.deposit_queue.load_init()?;
+        deposit_queue.tornado_state = ctx.accounts.tornado_state.key();
+        
+        ctx.accounts.tornado_state.load_mut()?.queued_deposits = 1;
+        
+        emit!(QueuedDepositsChangedEvent {
+            tornado_state: ctx.accounts.tornado_state.key(),
+            queued_deposits: true,
+        });
+        
+        Ok(())
+    }
+
+    /// Switch between queued and direct deposits for a pool that has a deposit queue
+    /// Leaves still in the queue can be processed in either mode
+    pub fn set_queued_deposits(ctx: Context<SetQueuedDeposits>, queued_deposits: bool) -> Result<()> {
+        // Queued commitments hold leaves reserved for them; direct deposits must not take those
+        require!(
+            queued_deposits || ctx.accounts.deposit_queue.load()?.is_empty(),
+            TornadoError::DepositQueueNotEmpty
+        );
+        
+        ctx.accounts.tornado_state.load_mut()?.queued_deposits = queued_deposits as u8;
+        
+        emit!(QueuedDepositsChangedEvent {
+            tornado_state: ctx.accounts.tornado_state.key(),
+            queued_deposits,
+        });
+        
+        Ok(())
+    }
+
     /// Withdraw funds with a zero-knowledge proof
+    #[allow(clippy::too_many_arguments)]
     pub fn withdraw(
         ctx: Context<Withdraw>,
         proof: Vec<u8>,
@@ -133,48 +399,45 @@ pub mod tornado_solana {
         fee: u64,
         refund: u64,
     ) -> Result<()> {
-        let tornado_state = &mut ctx.accounts.tornado_state;
-        
-        // Verify fee doesn't exceed denomination
-        require!(fee <= tornado_state.denomination, TornadoError::FeeExceedsDenomination);
+        let tornado_state_key = ctx.accounts.tornado_state.key();
+        let tornado_state_info = ctx.accounts.tornado_state.to_account_info();
+        let tornado_state_data = tornado_state_info.try_borrow_data()?;
+        let (tornado_state, roots) = TornadoState::split(&tornado_state_data)?;
         
         // The nullifier PDA creation (via 'init' in accounts) automatically prevents double-spending
         // If the nullifier has been used, account creation fails and the transaction reverts
         // This is the elegant O(1) solution from solana-mixer
         
-        // Verify root is in history
-        require!(
-            is_known_root(&tornado_state.roots, tornado_state.current_root_index, &root),
-            TornadoError::UnknownRoot
-        );
-        
-        // **CRITICAL SECURITY FIX**: Use stored verifying key from trusted setup ceremony
-        // This replaces the vulnerable hardcoded key usage with the actual VK from tornado_state.verifying_key
-        // This ensures the trusted setup ceremony results are actually used for verification
-        let stored_vk = deserialize_verifying_key(&tornado_state.verifying_key)?;
-        
-        // Verify the zero-knowledge proof using Groth16
-        // This uses Solana's native alt_bn128 syscalls for <200k CU verification
-        // Now using the ACTUAL verifying key from the trusted setup ceremony
-        verify_proof(
-            &proof, 
-            &root, 
-            &nullifier_hash, 
-            &recipient, 
-            &relayer.unwrap_or(Pubkey::default()), 
-            fee, 
-            refund, 
-            &stored_vk
+        // Fee bound, root history and Groth16 proof against the stored verifying key
+        let verifying_key_account = ctx.accounts.verifying_key_account.load()?;
+        verify_withdrawal(
+            tornado_state,
+            roots,
+            &verifying_key_account.as_groth16_verifying_key(),
+            &proof,
+            &root,
+            &nullifier_hash,
+            &recipient,
+            relayer,
+            fee,
+            refund,
         )?;
+        validate_relayer_fee(tornado_state, ctx.accounts.relayer_record.as_deref(), relayer, fee)?;
         
         // Validate vault PDA (security check)
         let vault_bump = ctx.bumps.vault;
         validate_vault_pda(
             &ctx.accounts.vault,
-            &ctx.accounts.tornado_state.key(),
+            &tornado_state_key,
             vault_bump,
         )?;
         
+        // The proof is bound to `recipient`, so the account being paid must be that address
+        require!(
+            ctx.accounts.recipient.key() == recipient,
+            TornadoError::RecipientMismatch
+        );
+        
         // Validate recipient is not an executable program account
         // This prevents accidentally sending funds to program accounts where they could be locked
         require!(
@@ -189,7 +452,6 @@ pub mod tornado_solana {
         let amount = tornado_state.denomination - fee;
         
         // Prepare vault seeds for signing
-        let tornado_state_key = ctx.accounts.tornado_state.key();
         let vault_seeds: &[&[u8]] = &[
             b"vault",
             tornado_state_key.as_ref(),
@@ -253,6 +515,14 @@ pub mod tornado_solana {
             }
         }
         
+        // Refund is funded by the payer (relayer), not the vault, so a fresh recipient can pay fees later
+        pay_refund(
+            &ctx.accounts.payer,
+            &ctx.accounts.recipient,
+            &ctx.accounts.system_program,
+            refund,
+        )?;
+        
         emit!(WithdrawalEvent {
             to: recipient,
             nullifier_hash,
@@ -263,6 +533,121 @@ pub mod tornado_solana {
         Ok(())
     }
     
+    /// Withdraw one note to several accounts, paid in the order of `outputs`
+    /// The proof binds `outputs_hash(outputs)` as its refund input and the zero address as recipient;
+    /// each output's account is passed in `remaining_accounts`, in the same order
+    #[allow(clippy::too_many_arguments)]
+    pub fn withdraw_multi<'info>(
+        ctx: Context<'_, '_, '_, 'info, WithdrawMulti<'info>>,
+        proof: Vec<u8>,
+        root: [u8; 32],
+        nullifier_hash: [u8; 32],
+        outputs: Vec<WithdrawOutput>,
+        relayer: Option<Pubkey>,
+        fee: u64,
+    ) -> Result<()> {
+        let tornado_state_key = ctx.accounts.tornado_state.key();
+        let tornado_state_info = ctx.accounts.tornado_state.to_account_info();
+        let tornado_state_data = tornado_state_info.try_borrow_data()?;
+        let (tornado_state, roots) = TornadoState::split(&tornado_state_data)?;
+        
+        let outputs_hash = outputs_hash(&outputs)?;
+        validate_outputs(&outputs, tornado_state.denomination, fee, &outputs_hash)?;
+        
+        // No refund: top-ups for fresh accounts are paid as outputs
+        check_withdrawal(tornado_state, roots, &root, &nullifier_hash, relayer, fee, 0)?;
+        let verifying_key_account = ctx.accounts.verifying_key_account.load()?;
+        let public_inputs = prepare_multi_output_public_inputs(
+            &root,
+            &nullifier_hash,
+            &outputs_hash,
+            &relayer.unwrap_or_default(),
+            fee,
+        );
+        verify_groth16_proof(&proof, &public_inputs, &verifying_key_account.as_groth16_verifying_key())?;
+        validate_relayer_fee(tornado_state, ctx.accounts.relayer_record.as_deref(), relayer, fee)?;
+        
+        let vault_bump = ctx.bumps.vault;
+        validate_vault_pda(
+            &ctx.accounts.vault,
+            &tornado_state_key,
+            vault_bump,
+        )?;
+        
+        require!(
+            ctx.accounts.vault.lamports().saturating_sub(tornado_state.denomination)
+                >= Rent::get()?.minimum_balance(0),
+            TornadoError::VaultBelowRent
+        );
+        
+        let vault_seeds: &[&[u8]] = &[
+            b"vault",
+            tornado_state_key.as_ref(),
+            &[vault_bump]
+        ];
+        
+        require!(
+            ctx.remaining_accounts.len() == outputs.len(),
+            TornadoError::RecipientMismatch
+        );
+        for (output, recipient) in outputs.iter().zip(ctx.remaining_accounts) {
+            require!(recipient.key() == output.recipient, TornadoError::RecipientMismatch);
+            require!(!recipient.executable, TornadoError::BadRecipient);
+            
+            if output.amount > 0 {
+                system_program::transfer(
+                    CpiContext::new_with_signer(
+                        ctx.accounts.system_program.to_account_info(),
+                        system_program::Transfer {
+                            from: ctx.accounts.vault.to_account_info(),
+                            to: recipient.clone(),
+                        },
+                        &[vault_seeds]
+                    ),
+                    output.amount,
+                )?;
+            }
+        }
+        
+        // Same relayer rules as `withdraw`, with every output treated as a recipient
+        if let Some(relayer_pubkey) = relayer {
+            if fee > 0 {
+                require!(
+                    outputs.iter().all(|output| output.recipient != relayer_pubkey),
+                    TornadoError::RecipientCannotBeRelayer
+                );
+                
+                let relayer_account = ctx.accounts.relayer.as_ref()
+                    .ok_or(TornadoError::RelayerAccountMissing)?;
+                require!(
+                    relayer_account.key() == relayer_pubkey,
+                    TornadoError::RelayerMismatch
+                );
+                
+                system_program::transfer(
+                    CpiContext::new_with_signer(
+                        ctx.accounts.system_program.to_account_info(),
+                        system_program::Transfer {
+                            from: ctx.accounts.vault.to_account_info(),
+                            to: relayer_account.clone(),
+                        },
+                        &[vault_seeds]
+                    ),
+                    fee,
+                )?;
+            }
+        }
+        
+        emit!(MultiOutputWithdrawalEvent {
+            outputs,
+            nullifier_hash,
+            relayer,
+            fee,
+        });
+        
+        Ok(())
+    }
+    
     /// One-time migration to move existing funds from state account to vault
     /// This should only be called once during the upgrade from old to new architecture
     pub fn migrate_to_vault(ctx: Context<MigrateToVault>) -> Result<()> {
@@ -276,9 +661,8 @@ pub mod tornado_solana {
         
         // Calculate surplus funds in state account (above rent exemption)
         let rent = Rent::get()?;
-        let state_account_size = 8 + TornadoState::MAX_SIZE;
-        let state_rent_minimum = rent.minimum_balance(state_account_size);
         let state_account_info = ctx.accounts.tornado_state.to_account_info();
+        let state_rent_minimum = rent.minimum_balance(state_account_info.data_len());
         let current_state_balance = state_account_info.lamports();
         
         // Only migrate if there's surplus
@@ -287,7 +671,8 @@ pub mod tornado_solana {
             
             // Prepare tornado_state PDA seeds for signing
             let state_bump = ctx.bumps.tornado_state;
-            let state_seeds: &[&[u8]] = &[b"tornado", &[state_bump]];
+            let denomination_bytes = ctx.accounts.tornado_state.load()?.denomination.to_le_bytes();
+            let state_seeds: &[&[u8]] = &[b"tornado", denomination_bytes.as_ref(), &[state_bump]];
             
             // Transfer surplus from state account to vault using CPI with PDA signing
             system_program::transfer(
@@ -310,46 +695,859 @@ pub mod tornado_solana {
         
         Ok(())
     }
-}
 
-// Constants matching original Tornado Cash
-pub const ROOT_HISTORY_SIZE: u32 = 30;
-pub const MERKLE_TREE_HEIGHT: u32 = 20;
+    /// Initialize an SPL token pool (Token or Token-2022) with fixed denomination
+    /// Each (mint, denomination) pair gets its own pool PDA: [b"tornado", mint, denomination.to_le_bytes()]
+    /// @param denomination: Amount in the mint's base units
+    /// @param verifying_key: The Groth16 verifying key from trusted setup ceremony
+    /// @param merkle_tree_height: Depth of the pool's tree, which must be the `levels` the key's circuit was built with
+    ///        A Groth16 key does not encode the tree depth, so the program cannot check this itself;
+    ///        a key for another depth leaves every withdrawal proof unverifiable
+    /// @param root_history_size: Number of recent roots a withdrawal proof may be built against
+    /// @param max_fee_bps: Relayer fee cap as a share of the denomination, in basis points (10_000 = no cap)
+    /// @param max_fee_amount: Absolute relayer fee cap in the pool's base units (u64::MAX = no cap)
+    pub fn initialize_token_pool(
+        ctx: Context<InitializeTokenPool>,
+        denomination: u64,
+        verifying_key: Vec<u8>,
+        merkle_tree_height: u32,
+        root_history_size: u32,
+        max_fee_bps: u16,
+        max_fee_amount: u64,
+    ) -> Result<()> {
+        require!(denomination > 0, TornadoError::InvalidDenomination);
+        validate_merkle_tree_height(merkle_tree_height)?;
+        validate_root_history_size(root_history_size)?;
+        validate_max_fee_bps(max_fee_bps)?;
+        
+        // Transfer-fee, default-frozen and other unsupported Token-2022 mints are rejected here
+        token_pool::validate_pool_mint(&ctx.accounts.mint.to_account_info())?;
+        
+        let mut tornado_state = ctx.accounts.tornado_state.load_init()?;
+        
+        tornado_state.authority = ctx.accounts.authority.key();
+        tornado_state.pending_authority = Pubkey::default();
+        tornado_state.denomination = denomination;
+        tornado_state.mint = ctx.accounts.mint.key();
+        tornado_state.merkle_tree = MerkleTree::with_levels(merkle_tree_height);
+        tornado_state.current_root_index = 0;
+        tornado_state.next_index = 0;
+        tornado_state.root_history_size = root_history_size;
+        tornado_state.vk_rotation_renounced = 0;
+        tornado_state.deposits_paused = 0;
+        tornado_state.withdrawals_paused = 0;
+        tornado_state.pause_renounced = 0;
+        tornado_state.max_fee_bps = max_fee_bps;
+        tornado_state.max_fee_amount = max_fee_amount;
+        tornado_state.registered_relayers_only = 0;
+        tornado_state.queued_deposits = 0;
+        
+        let mut verifying_key_account = ctx.accounts.verifying_key_account.load_init()?;
+        verifying_key_account.tornado_state = ctx.accounts.tornado_state.key();
+        verifying_key_account.store(&verifying_key, merkle_tree_height)?;
+        
+        Ok(())
+    }
 
-#[derive(Accounts)]
-pub struct Initialize<'info> {
-    #[account(
-        init,
-        payer = authority,
-        space = 8 + TornadoState::MAX_SIZE,
-        seeds = [b"tornado"],
-        bump
-    )]
-    pub tornado_state: Account<'info, TornadoState>,
-    
-    #[account(
-        init,
-        payer = authority,
-        space = 0,  // 0-byte SystemAccount for holding SOL only
-        seeds = [b"vault", tornado_state.key().as_ref()],
+    /// Deposit tokens into an SPL token pool
+    /// @param commitment: Hash(nullifier + secret)
+    pub fn deposit_token(ctx: Context<DepositToken>, commitment: [u8; 32]) -> Result<()> {
+        let deposit_amount = ctx.accounts.tornado_state.load()?.denomination;
+        
+        // Transfer tokens to the pool's token vault
+        token_interface::transfer_checked(
+            CpiContext::new(
+                ctx.accounts.token_program.to_account_info(),
+                TransferChecked {
+                    from: ctx.accounts.depositor_token_account.to_account_info(),
+                    mint: ctx.accounts.mint.to_account_info(),
+                    to: ctx.accounts.token_vault.to_account_info(),
+                    authority: ctx.accounts.depositor.to_account_info(),
+                },
+            ),
+            deposit_amount,
+            ctx.accounts.mint.decimals,
+        )?;
+        
+        // Insert commitment into merkle tree and record the new root
+        let tornado_state_info = ctx.accounts.tornado_state.to_account_info();
+        let mut tornado_state_data = tornado_state_info.try_borrow_mut_data()?;
+        let (tornado_state, roots) = TornadoState::split_mut(&mut tornado_state_data)?;
+        let leaf_index = insert_commitment(tornado_state, roots, commitment)?;
+        
+        emit!(DepositEvent {
+            commitment,
+            leaf_index,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+        
+        Ok(())
+    }
+
+    /// Withdraw tokens from an SPL token pool with a zero-knowledge proof
+    /// The proof binds wallet addresses; token accounts must be owned by `recipient` / `relayer`
+    #[allow(clippy::too_many_arguments)]
+    pub fn withdraw_token(
+        ctx: Context<WithdrawToken>,
+        proof: Vec<u8>,
+        root: [u8; 32],
+        nullifier_hash: [u8; 32],
+        recipient: Pubkey,
+        relayer: Option<Pubkey>,
+        fee: u64,
+        refund: u64,
+    ) -> Result<()> {
+        let tornado_state_info = ctx.accounts.tornado_state.to_account_info();
+        let tornado_state_data = tornado_state_info.try_borrow_data()?;
+        let (tornado_state, roots) = TornadoState::split(&tornado_state_data)?;
+        
+        // Fee bound, root history and Groth16 proof against the stored verifying key
+        let verifying_key_account = ctx.accounts.verifying_key_account.load()?;
+        verify_withdrawal(
+            tornado_state,
+            roots,
+            &verifying_key_account.as_groth16_verifying_key(),
+            &proof,
+            &root,
+            &nullifier_hash,
+            &recipient,
+            relayer,
+            fee,
+            refund,
+        )?;
+        validate_relayer_fee(tornado_state, ctx.accounts.relayer_record.as_deref(), relayer, fee)?;
+        
+        // The proof is bound to the recipient wallet: it receives any refund and owns the token account
+        require!(
+            ctx.accounts.recipient.key() == recipient,
+            TornadoError::RecipientMismatch
+        );
+        require!(
+            ctx.accounts.recipient_token_account.owner == recipient,
+            TornadoError::TokenAccountOwnerMismatch
+        );
+        
+        let amount = tornado_state.denomination - fee;
+        let decimals = ctx.accounts.mint.decimals;
+        
+        // Prepare token vault seeds for signing (the vault is its own authority)
+        let tornado_state_key = ctx.accounts.tornado_state.key();
+        let token_vault_bump = ctx.bumps.token_vault;
+        let token_vault_seeds: &[&[u8]] = &[
+            b"token_vault",
+            tornado_state_key.as_ref(),
+            &[token_vault_bump]
+        ];
+        
+        // Transfer to recipient using CPI with token vault signing
+        if amount > 0 {
+            token_interface::transfer_checked(
+                CpiContext::new_with_signer(
+                    ctx.accounts.token_program.to_account_info(),
+                    TransferChecked {
+                        from: ctx.accounts.token_vault.to_account_info(),
+                        mint: ctx.accounts.mint.to_account_info(),
+                        to: ctx.accounts.recipient_token_account.to_account_info(),
+                        authority: ctx.accounts.token_vault.to_account_info(),
+                    },
+                    &[token_vault_seeds]
+                ),
+                amount,
+                decimals,
+            )?;
+        }
+        
+        // Pay relayer fee if present - same security validations as the SOL pool
+        if let Some(relayer_pubkey) = relayer {
+            if fee > 0 {
+                require!(
+                    recipient != relayer_pubkey,
+                    TornadoError::RecipientCannotBeRelayer
+                );
+                
+                let relayer_token_account = ctx.accounts.relayer_token_account.as_ref()
+                    .ok_or(TornadoError::RelayerAccountMissing)?;
+                require!(
+                    relayer_token_account.owner == relayer_pubkey,
+                    TornadoError::RelayerMismatch
+                );
+                
+                token_interface::transfer_checked(
+                    CpiContext::new_with_signer(
+                        ctx.accounts.token_program.to_account_info(),
+                        TransferChecked {
+                            from: ctx.accounts.token_vault.to_account_info(),
+                            mint: ctx.accounts.mint.to_account_info(),
+                            to: relayer_token_account.to_account_info(),
+                            authority: ctx.accounts.token_vault.to_account_info(),
+                        },
+                        &[token_vault_seeds]
+                    ),
+                    fee,
+                    decimals,
+                )?;
+            }
+        }
+        
+        // Refund is funded by the payer (relayer), not the vault, so a fresh recipient can pay fees later
+        pay_refund(
+            &ctx.accounts.payer,
+            &ctx.accounts.recipient,
+            &ctx.accounts.system_program,
+            refund,
+        )?;
+        
+        emit!(WithdrawalEvent {
+            to: recipient,
+            nullifier_hash,
+            relayer,
+            fee,
+        });
+        
+        Ok(())
+    }
+
+    /// Propose a replacement Groth16 verifying key (e.g. after a circuit fix or new ceremony)
+    /// The key is validated now and can only be applied after VK_ROTATION_DELAY seconds
+    /// @param merkle_tree_height: `levels` of the new key's circuit; the pool's tree depth cannot change
+    pub fn propose_verifying_key(
+        ctx: Context<ProposeVerifyingKey>,
+        verifying_key: Vec<u8>,
+        merkle_tree_height: u32,
+    ) -> Result<()> {
+        let tornado_state = ctx.accounts.tornado_state.load()?;
+        require!(
+            tornado_state.vk_rotation_renounced == 0,
+            TornadoError::VkRotationRenounced
+        );
+        
+        // Reject anything the withdraw path could not use
+        validate_verifying_key(&verifying_key)?;
+        require!(
+            merkle_tree_height == tornado_state.merkle_tree.levels,
+            TornadoError::MerkleTreeHeightMismatch
+        );
+        
+        let activation_time = Clock::get()?.unix_timestamp + VK_ROTATION_DELAY;
+        
+        let pending = &mut ctx.accounts.pending_verifying_key;
+        pending.tornado_state = ctx.accounts.tornado_state.key();
+        pending.verifying_key = verifying_key;
+        pending.merkle_tree_height = merkle_tree_height;
+        pending.activation_time = activation_time;
+        
+        emit!(VerifyingKeyProposedEvent {
+            tornado_state: pending.tornado_state,
+            activation_time,
+        });
+        
+        Ok(())
+    }
+
+    /// Replace the pool's verifying key with the pending one once the timelock has elapsed
+    pub fn apply_verifying_key(ctx: Context<ApplyVerifyingKey>) -> Result<()> {
+        let pending = &ctx.accounts.pending_verifying_key;
+        
+        require!(
+            ctx.accounts.tornado_state.load()?.vk_rotation_renounced == 0,
+            TornadoError::VkRotationRenounced
+        );
+        check_timelock_elapsed(pending.activation_time, Clock::get()?.unix_timestamp)?;
+        
+        ctx.accounts.verifying_key_account.load_mut()?.store(&pending.verifying_key, pending.merkle_tree_height)?;
+        
+        emit!(VerifyingKeyAppliedEvent {
+            tornado_state: ctx.accounts.tornado_state.key(),
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+        
+        Ok(())
+    }
+
+    /// Discard a pending verifying key proposal
+    pub fn cancel_verifying_key(ctx: Context<CancelVerifyingKey>) -> Result<()> {
+        emit!(VerifyingKeyCancelledEvent {
+            tornado_state: ctx.accounts.tornado_state.key(),
+        });
+        
+        Ok(())
+    }
+
+    /// Permanently give up the ability to rotate the verifying key
+    /// Any pending proposal can still be cancelled but never applied
+    pub fn renounce_verifying_key_rotation(ctx: Context<RenounceVerifyingKeyRotation>) -> Result<()> {
+        let mut tornado_state = ctx.accounts.tornado_state.load_mut()?;
+        
+        require!(
+            tornado_state.vk_rotation_renounced == 0,
+            TornadoError::VkRotationRenounced
+        );
+        tornado_state.vk_rotation_renounced = 1;
+        
+        emit!(VerifyingKeyRotationRenouncedEvent {
+            tornado_state: ctx.accounts.tornado_state.key(),
+        });
+        
+        Ok(())
+    }
+
+    /// Emergency stop, e.g. when a circuit or verifier bug is discovered
+    /// Deposits and withdrawals are paused separately so users are never trapped by a deposit pause
+    pub fn set_paused(
+        ctx: Context<SetPaused>,
+        deposits_paused: bool,
+        withdrawals_paused: bool,
+    ) -> Result<()> {
+        let mut tornado_state = ctx.accounts.tornado_state.load_mut()?;
+        apply_pause_flags(&mut tornado_state, deposits_paused, withdrawals_paused)?;
+        
+        emit!(PauseChangedEvent {
+            tornado_state: ctx.accounts.tornado_state.key(),
+            deposits_paused,
+            withdrawals_paused,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+        
+        Ok(())
+    }
+
+    /// Permanently give up the ability to pause the pool
+    /// Both directions are unpaused first, so the pool can never be left stuck
+    pub fn renounce_pause(ctx: Context<RenouncePause>) -> Result<()> {
+        let mut tornado_state = ctx.accounts.tornado_state.load_mut()?;
+        apply_pause_flags(&mut tornado_state, false, false)?;
+        tornado_state.pause_renounced = 1;
+        
+        emit!(PauseRenouncedEvent {
+            tornado_state: ctx.accounts.tornado_state.key(),
+        });
+        
+        Ok(())
+    }
+
+    /// Tighten the pool's relayer fee cap; caps can only ever go down so users keep their guarantee
+    pub fn lower_fee_cap(ctx: Context<LowerFeeCap>, max_fee_bps: u16, max_fee_amount: u64) -> Result<()> {
+        let mut tornado_state = ctx.accounts.tornado_state.load_mut()?;
+        apply_lower_fee_cap(&mut tornado_state, max_fee_bps, max_fee_amount)?;
+        
+        emit!(FeeCapLoweredEvent {
+            tornado_state: ctx.accounts.tornado_state.key(),
+            max_fee_bps,
+            max_fee_amount,
+        });
+        
+        Ok(())
+    }
+
+    /// Only pay relayer fees to active relayers registered with this pool
+    /// Withdrawals without a relayer are never affected
+    pub fn set_registered_relayers_only(
+        ctx: Context<SetRegisteredRelayersOnly>,
+        registered_relayers_only: bool,
+    ) -> Result<()> {
+        let mut tornado_state = ctx.accounts.tornado_state.load_mut()?;
+        tornado_state.registered_relayers_only = registered_relayers_only as u8;
+        
+        emit!(RelayerModeChangedEvent {
+            tornado_state: ctx.accounts.tornado_state.key(),
+            registered_relayers_only,
+        });
+        
+        Ok(())
+    }
+
+    /// Register as a relayer for a pool, locking `stake` lamports in the record PDA
+    /// @param fee_bps: Largest fee the relayer will charge, relative to the denomination
+    /// @param metadata_url: Where clients find the relayer's endpoint and terms
+    pub fn register_relayer(
+        ctx: Context<RegisterRelayer>,
+        stake: u64,
+        fee_bps: u16,
+        metadata_url: String,
+    ) -> Result<()> {
+        require!(
+            stake >= relayer_registry::MIN_RELAYER_STAKE,
+            TornadoError::InsufficientRelayerStake
+        );
+        relayer_registry::validate_relayer_quote(fee_bps, &metadata_url)?;
+        
+        system_program::transfer(
+            CpiContext::new(
+                ctx.accounts.system_program.to_account_info(),
+                system_program::Transfer {
+                    from: ctx.accounts.relayer.to_account_info(),
+                    to: ctx.accounts.relayer_record.to_account_info(),
+                },
+            ),
+            stake,
+        )?;
+        
+        let record = &mut ctx.accounts.relayer_record;
+        record.tornado_state = ctx.accounts.tornado_state.key();
+        record.relayer = ctx.accounts.relayer.key();
+        record.stake = stake;
+        record.fee_bps = fee_bps;
+        record.unbonding_since = 0;
+        record.metadata_url = metadata_url;
+        
+        emit!(RelayerRegisteredEvent {
+            tornado_state: record.tornado_state,
+            relayer: record.relayer,
+            stake,
+            fee_bps,
+            metadata_url: record.metadata_url.clone(),
+        });
+        
+        Ok(())
+    }
+
+    /// Change the fee quote and metadata URL, optionally topping up the stake
+    pub fn update_relayer(
+        ctx: Context<UpdateRelayer>,
+        fee_bps: u16,
+        metadata_url: String,
+        additional_stake: u64,
+    ) -> Result<()> {
+        require!(
+            ctx.accounts.relayer_record.unbonding_since == 0,
+            TornadoError::RelayerNotRegistered
+        );
+        relayer_registry::validate_relayer_quote(fee_bps, &metadata_url)?;
+        
+        if additional_stake > 0 {
+            system_program::transfer(
+                CpiContext::new(
+                    ctx.accounts.system_program.to_account_info(),
+                    system_program::Transfer {
+                        from: ctx.accounts.relayer.to_account_info(),
+                        to: ctx.accounts.relayer_record.to_account_info(),
+                    },
+                ),
+                additional_stake,
+            )?;
+        }
+        
+        let record = &mut ctx.accounts.relayer_record;
+        record.stake = record
+            .stake
+            .checked_add(additional_stake)
+            .ok_or(TornadoError::InsufficientRelayerStake)?;
+        record.fee_bps = fee_bps;
+        record.metadata_url = metadata_url;
+        
+        emit!(RelayerUpdatedEvent {
+            tornado_state: record.tornado_state,
+            relayer: record.relayer,
+            stake: record.stake,
+            fee_bps,
+            metadata_url: record.metadata_url.clone(),
+        });
+        
+        Ok(())
+    }
+
+    /// Stop accepting fees as a registered relayer and start the unbonding period
+    /// The stake stays slashable until `withdraw_relayer_stake` after RELAYER_UNBONDING_PERIOD
+    pub fn deregister_relayer(ctx: Context<DeregisterRelayer>) -> Result<()> {
+        let record = &mut ctx.accounts.relayer_record;
+        require!(record.unbonding_since == 0, TornadoError::RelayerNotRegistered);
+        record.unbonding_since = Clock::get()?.unix_timestamp;
+        
+        emit!(RelayerDeregisteredEvent {
+            tornado_state: record.tornado_state,
+            relayer: record.relayer,
+            unbonding_since: record.unbonding_since,
+        });
+        
+        Ok(())
+    }
+
+    /// Close an unbonded record, returning the remaining stake and rent to the relayer
+    pub fn withdraw_relayer_stake(ctx: Context<WithdrawRelayerStake>) -> Result<()> {
+        let record = &ctx.accounts.relayer_record;
+        relayer_registry::check_unbonded(record, Clock::get()?.unix_timestamp)?;
+        
+        emit!(RelayerStakeWithdrawnEvent {
+            tornado_state: record.tornado_state,
+            relayer: record.relayer,
+            amount: record.stake,
+        });
+        
+        Ok(())
+    }
+
+    /// Take `amount` of a relayer's stake, e.g. after it censored or front-ran withdrawals
+    /// Works during unbonding too; the slashed lamports go to the pool authority
+    pub fn slash_relayer(ctx: Context<SlashRelayer>, amount: u64) -> Result<()> {
+        let record = &mut ctx.accounts.relayer_record;
+        record.stake = record
+            .stake
+            .checked_sub(amount)
+            .ok_or(TornadoError::SlashExceedsStake)?;
+        
+        // The record is owned by this program, so its lamports can be moved directly
+        record.sub_lamports(amount)?;
+        ctx.accounts.authority.add_lamports(amount)?;
+        
+        emit!(RelayerSlashedEvent {
+            tornado_state: record.tornado_state,
+            relayer: record.relayer,
+            amount,
+            remaining_stake: record.stake,
+        });
+        
+        Ok(())
+    }
+
+    /// First step of an authority transfer (e.g. to a multisig or DAO)
+    /// Nothing changes until `new_authority` signs `accept_authority`, so a typo cannot brick the pool
+    /// @param new_authority: Pubkey::default() withdraws a pending proposal
+    pub fn propose_authority(ctx: Context<ProposeAuthority>, new_authority: Pubkey) -> Result<()> {
+        let mut tornado_state = ctx.accounts.tornado_state.load_mut()?;
+        tornado_state.pending_authority = new_authority;
+        
+        emit!(AuthorityProposedEvent {
+            tornado_state: ctx.accounts.tornado_state.key(),
+            authority: tornado_state.authority,
+            pending_authority: new_authority,
+        });
+        
+        Ok(())
+    }
+
+    /// Second step of an authority transfer, signed by the proposed authority
+    pub fn accept_authority(ctx: Context<AcceptAuthority>) -> Result<()> {
+        let mut tornado_state = ctx.accounts.tornado_state.load_mut()?;
+        let previous_authority = tornado_state.authority;
+        tornado_state.authority = ctx.accounts.new_authority.key();
+        tornado_state.pending_authority = Pubkey::default();
+        
+        emit!(AuthorityTransferredEvent {
+            tornado_state: ctx.accounts.tornado_state.key(),
+            previous_authority,
+            new_authority: tornado_state.authority,
+        });
+        
+        Ok(())
+    }
+
+    /// Permanently burn admin rights: authority becomes Pubkey::default(), which nobody can sign for
+    /// Every admin instruction is rejected afterwards, including applying a pending verifying key
+    /// Refused while withdrawals are paused, since nobody could ever unpause them
+    pub fn renounce_authority(ctx: Context<RenounceAuthority>) -> Result<()> {
+        let mut tornado_state = ctx.accounts.tornado_state.load_mut()?;
+        require!(tornado_state.withdrawals_paused == 0, TornadoError::WithdrawalsPaused);
+        
+        let previous_authority = tornado_state.authority;
+        tornado_state.authority = Pubkey::default();
+        tornado_state.pending_authority = Pubkey::default();
+        
+        emit!(AuthorityRenouncedEvent {
+            tornado_state: ctx.accounts.tornado_state.key(),
+            previous_authority,
+        });
+        
+        Ok(())
+    }
+}
+
+// Constants matching original Tornado Cash
+// Default root history window; each pool picks its own size at initialize
+pub const ROOT_HISTORY_SIZE: u32 = 30;
+// Largest window whose pool account still fits a single CPI allocation (10 KiB)
+pub const MAX_ROOT_HISTORY_SIZE: u32 = 250;
+// Depth of the bundled withdraw circuit (circuits/withdraw.circom, Withdraw(20))
+pub const MERKLE_TREE_HEIGHT: u32 = 20;
+// Per-pool tree depth bounds; 8 keeps test pools cheap, 32 is the most next_index can address
+pub const MIN_MERKLE_TREE_HEIGHT: u32 = 8;
+pub const MAX_MERKLE_TREE_HEIGHT: u32 = 32;
+
+// Verifying key storage reserved in TornadoState
+pub const MAX_VERIFYING_KEY_SIZE: usize = 2048;
+// Public inputs of the withdraw circuit: root, nullifierHash, recipient (2), relayer (2), fee, refund
+pub const NR_PUBLIC_INPUTS: u32 = 8;
+// nr_pubinputs (4) + alpha_g1 (64) + beta/gamma/delta_g2 (3 * 128) + IC ((NR_PUBLIC_INPUTS + 1) * 64)
+pub const VERIFYING_KEY_SIZE: usize = 4 + 64 + 3 * 128 + (NR_PUBLIC_INPUTS as usize + 1) * 64;
+const _: () = assert!(VERIFYING_KEY_SIZE <= MAX_VERIFYING_KEY_SIZE);
+const _: () = assert!(std::mem::size_of::<VerifyingKeyAccount>() == VerifyingKeyAccount::SIZE);
+const _: () = assert!(std::mem::size_of::<TornadoState>() == TornadoState::MAX_SIZE);
+const _: () = assert!(TornadoState::space(MAX_ROOT_HISTORY_SIZE) <= 10 * 1024);
+const _: () = assert!(std::mem::size_of::<MerkleTree>() == MerkleTree::SIZE);
+const _: () = assert!(std::mem::size_of::<DepositQueue>() == DepositQueue::SIZE);
+const _: () = assert!(8 + DepositQueue::SIZE <= 10 * 1024);
+// BN254 scalar field modulus r (big-endian), the field commitments and nullifier hashes live in
+// 21888242871839275222246405745257275088548364400416034343698204186575808495617
+pub const BN254_SCALAR_FIELD_MODULUS: [u8; 32] = [
+    0x30, 0x64, 0x4e, 0x72, 0xe1, 0x31, 0xa0, 0x29, 0xb8, 0x50, 0x45, 0xb6, 0x81, 0x81, 0x58, 0x5d,
+    0x28, 0x33, 0xe8, 0x48, 0x79, 0xb9, 0x70, 0x91, 0x43, 0xe1, 0xf5, 0x93, 0xf0, 0x00, 0x00, 0x01,
+];
+// Minimum time between proposing and applying a new verifying key (2 days)
+pub const VK_ROTATION_DELAY: i64 = 2 * 24 * 60 * 60;
+// Basis point scale of max_fee_bps; a cap of MAX_FEE_BPS allows the whole denomination
+pub const MAX_FEE_BPS: u16 = 10_000;
+// Largest deposit_batch. A full batch costs at most 50 Poseidon hashes at the default depth of 20
+// (two and a half single deposits) and 74 at depth 32, reached when it straddles the tree's
+// midpoint; with a SetComputeUnitLimit instruction its transaction is 834 of 1232 bytes. Both are
+// pinned by deposit_batch_test. Validator compute units are NOT measured yet, so 16 is not known to
+// fit the 1.4M limit: the benchmark in DEVELOPMENT_PROGRESS.md must pass at depths 20 and 32 before
+// release, and this drops to the largest measured size if it does not.
+pub const MAX_DEPOSIT_BATCH_SIZE: usize = 16;
+
+#[derive(Accounts)]
+#[instruction(denomination: u64, verifying_key: Vec<u8>, merkle_tree_height: u32, root_history_size: u32)]
+pub struct Initialize<'info> {
+    /// One pool per denomination (e.g. 0.1 / 1 / 10 / 100 SOL) under the same program ID
+    #[account(
+        init,
+        payer = authority,
+        space = TornadoState::space(root_history_size),
+        seeds = [b"tornado", denomination.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub tornado_state: AccountLoader<'info, TornadoState>,
+    
+    /// Pre-validated verifying key, read zero-copy by every withdrawal
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + VerifyingKeyAccount::SIZE,
+        seeds = [VerifyingKeyAccount::SEED_PREFIX, tornado_state.key().as_ref()],
+        bump
+    )]
+    pub verifying_key_account: AccountLoader<'info, VerifyingKeyAccount>,
+    
+    /// 0-byte system account holding SOL only; the first deposit creates it
+    #[account(
+        seeds = [b"vault", tornado_state.key().as_ref()],
+        bump
+    )]
+    pub vault: SystemAccount<'info>,
+    
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct Deposit<'info> {
+    #[account(
+        mut,
+        seeds = [b"tornado", tornado_state.load()?.denomination.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub tornado_state: AccountLoader<'info, TornadoState>,
+    
+    #[account(
+        mut,
+        seeds = [b"vault", tornado_state.key().as_ref()],
+        bump
+    )]
+    pub vault: SystemAccount<'info>,
+    
+    #[account(mut)]
+    pub depositor: Signer<'info>,
+    
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct EnqueueDeposit<'info> {
+    /// Read-only: queued deposits lock the queue and vault below, not the pool state
+    #[account(
+        seeds = [b"tornado", tornado_state.load()?.denomination.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub tornado_state: AccountLoader<'info, TornadoState>,
+    
+    #[account(
+        mut,
+        seeds = [DepositQueue::SEED_PREFIX, tornado_state.key().as_ref()],
+        bump
+    )]
+    pub deposit_queue: AccountLoader<'info, DepositQueue>,
+    
+    #[account(
+        mut,
+        seeds = [b"vault", tornado_state.key().as_ref()],
+        bump
+    )]
+    pub vault: SystemAccount<'info>,
+    
+    #[account(mut)]
+    pub depositor: Signer<'info>,
+    
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct ProcessQueue<'info> {
+    #[account(
+        mut,
+        seeds = [b"tornado", tornado_state.load()?.denomination.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub tornado_state: AccountLoader<'info, TornadoState>,
+    
+    #[account(
+        mut,
+        seeds = [DepositQueue::SEED_PREFIX, tornado_state.key().as_ref()],
+        bump
+    )]
+    pub deposit_queue: AccountLoader<'info, DepositQueue>,
+}
+
+#[derive(Accounts)]
+pub struct InitializeDepositQueue<'info> {
+    /// SOL pools only: token pools live at different seeds and keep direct deposits
+    #[account(
+        mut,
+        has_one = authority,
+        seeds = [b"tornado", tornado_state.load()?.denomination.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub tornado_state: AccountLoader<'info, TornadoState>,
+    
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + DepositQueue::SIZE,
+        seeds = [DepositQueue::SEED_PREFIX, tornado_state.key().as_ref()],
+        bump
+    )]
+    pub deposit_queue: AccountLoader<'info, DepositQueue>,
+    
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct SetQueuedDeposits<'info> {
+    #[account(mut, has_one = authority)]
+    pub tornado_state: AccountLoader<'info, TornadoState>,
+    
+    /// Must exist: queued mode without a queue would leave the pool without a deposit path
+    /// Must be empty to leave queued mode, so direct deposits never take a queued leaf's slot
+    #[account(
+        seeds = [DepositQueue::SEED_PREFIX, tornado_state.key().as_ref()],
+        bump
+    )]
+    pub deposit_queue: AccountLoader<'info, DepositQueue>,
+    
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(proof: Vec<u8>, root: [u8; 32], nullifier_hash: [u8; 32])]
+pub struct Withdraw<'info> {
+    #[account(
+        seeds = [b"tornado", tornado_state.load()?.denomination.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub tornado_state: AccountLoader<'info, TornadoState>,
+    
+    /// The nullifier PDA - if this already exists, withdrawal fails (prevents double-spend)
+    /// Using the elegant solana-mixer pattern: existence = spent
+    /// Scoped to the pool so nullifiers of different denominations never collide
+    #[account(
+        init,
+        seeds = [Nullifier::SEED_PREFIX, tornado_state.key().as_ref(), nullifier_hash.as_ref()],
+        bump,
+        payer = payer,
+        space = 8  // Just discriminator, no data needed
+    )]
+    pub nullifier: Account<'info, Nullifier>,
+    
+    #[account(
+        seeds = [VerifyingKeyAccount::SEED_PREFIX, tornado_state.key().as_ref()],
+        bump
+    )]
+    pub verifying_key_account: AccountLoader<'info, VerifyingKeyAccount>,
+    
+    #[account(
+        mut,
+        seeds = [b"vault", tornado_state.key().as_ref()],
         bump
     )]
     pub vault: SystemAccount<'info>,
     
+    /// CHECK: Recipient of withdrawn funds
     #[account(mut)]
-    pub authority: Signer<'info>,
+    pub recipient: AccountInfo<'info>,
+    
+    /// CHECK: Optional relayer receiving fee
+    #[account(mut)]
+    pub relayer: Option<AccountInfo<'info>>,
+    
+    /// The account paying for nullifier PDA creation (relayer or recipient)
+    #[account(mut)]
+    pub payer: Signer<'info>,
     
     pub system_program: Program<'info, System>,
+    
+    /// Registry record of the relayer, required when the pool only pays registered relayers
+    #[account(has_one = tornado_state)]
+    pub relayer_record: Option<Account<'info, RelayerRecord>>,
 }
 
+/// Accounts of `withdraw_multi`; output accounts follow in `remaining_accounts`
 #[derive(Accounts)]
-pub struct Deposit<'info> {
+#[instruction(proof: Vec<u8>, root: [u8; 32], nullifier_hash: [u8; 32])]
+pub struct WithdrawMulti<'info> {
+    #[account(
+        seeds = [b"tornado", tornado_state.load()?.denomination.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub tornado_state: AccountLoader<'info, TornadoState>,
+    
+    /// Shares the nullifier PDA with `withdraw`, so a note is spent once across both paths
+    #[account(
+        init,
+        seeds = [Nullifier::SEED_PREFIX, tornado_state.key().as_ref(), nullifier_hash.as_ref()],
+        bump,
+        payer = payer,
+        space = 8
+    )]
+    pub nullifier: Account<'info, Nullifier>,
+    
+    #[account(
+        seeds = [VerifyingKeyAccount::SEED_PREFIX, tornado_state.key().as_ref()],
+        bump
+    )]
+    pub verifying_key_account: AccountLoader<'info, VerifyingKeyAccount>,
+    
+    #[account(
+        mut,
+        seeds = [b"vault", tornado_state.key().as_ref()],
+        bump
+    )]
+    pub vault: SystemAccount<'info>,
+    
+    /// CHECK: Optional relayer receiving fee
+    #[account(mut)]
+    pub relayer: Option<AccountInfo<'info>>,
+    
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    
+    pub system_program: Program<'info, System>,
+    
+    /// Registry record of the relayer, required when the pool only pays registered relayers
+    #[account(has_one = tornado_state)]
+    pub relayer_record: Option<Account<'info, RelayerRecord>>,
+}
+
+#[derive(Accounts)]
+pub struct MigrateToVault<'info> {
     #[account(
         mut,
-        seeds = [b"tornado"],
+        has_one = authority,
+        seeds = [b"tornado", tornado_state.load()?.denomination.to_le_bytes().as_ref()],
         bump
     )]
-    pub tornado_state: Account<'info, TornadoState>,
+    pub tornado_state: AccountLoader<'info, TornadoState>,
     
     #[account(
         mut,
@@ -359,26 +1557,108 @@ pub struct Deposit<'info> {
     pub vault: SystemAccount<'info>,
     
     #[account(mut)]
-    pub depositor: Signer<'info>,
+    pub authority: Signer<'info>,
     
     pub system_program: Program<'info, System>,
 }
 
 #[derive(Accounts)]
-#[instruction(nullifier_hash: [u8; 32])]
-pub struct Withdraw<'info> {
+#[instruction(denomination: u64, verifying_key: Vec<u8>, merkle_tree_height: u32, root_history_size: u32)]
+pub struct InitializeTokenPool<'info> {
+    /// One pool per (mint, denomination) under the same program ID
+    #[account(
+        init,
+        payer = authority,
+        space = TornadoState::space(root_history_size),
+        seeds = [b"tornado", mint.key().as_ref(), denomination.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub tornado_state: AccountLoader<'info, TornadoState>,
+    
+    #[account(mint::token_program = token_program)]
+    pub mint: Box<InterfaceAccount<'info, Mint>>,
+    
+    /// Pre-validated verifying key, read zero-copy by every withdrawal
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + VerifyingKeyAccount::SIZE,
+        seeds = [VerifyingKeyAccount::SEED_PREFIX, tornado_state.key().as_ref()],
+        bump
+    )]
+    pub verifying_key_account: AccountLoader<'info, VerifyingKeyAccount>,
+    
+    /// PDA-owned token vault, the token counterpart of the lamport `vault`
+    #[account(
+        init,
+        payer = authority,
+        seeds = [b"token_vault", tornado_state.key().as_ref()],
+        bump,
+        token::mint = mint,
+        token::authority = token_vault,
+        token::token_program = token_program
+    )]
+    pub token_vault: Box<InterfaceAccount<'info, TokenAccount>>,
+    
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    
+    pub token_program: Interface<'info, TokenInterface>,
+    
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct DepositToken<'info> {
+    #[account(
+        mut,
+        has_one = mint,
+        seeds = [b"tornado", mint.key().as_ref(), tornado_state.load()?.denomination.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub tornado_state: AccountLoader<'info, TornadoState>,
+    
+    #[account(mint::token_program = token_program)]
+    pub mint: Box<InterfaceAccount<'info, Mint>>,
+    
+    #[account(
+        mut,
+        seeds = [b"token_vault", tornado_state.key().as_ref()],
+        bump,
+        token::mint = mint,
+        token::authority = token_vault,
+        token::token_program = token_program,
+        constraint = !token_vault.is_frozen() @ TornadoError::TokenVaultFrozen
+    )]
+    pub token_vault: Box<InterfaceAccount<'info, TokenAccount>>,
+    
     #[account(
         mut,
-        seeds = [b"tornado"],
+        token::mint = mint,
+        token::authority = depositor,
+        token::token_program = token_program
+    )]
+    pub depositor_token_account: Box<InterfaceAccount<'info, TokenAccount>>,
+    
+    pub depositor: Signer<'info>,
+    
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+#[derive(Accounts)]
+#[instruction(proof: Vec<u8>, root: [u8; 32], nullifier_hash: [u8; 32])]
+pub struct WithdrawToken<'info> {
+    #[account(
+        has_one = mint,
+        seeds = [b"tornado", mint.key().as_ref(), tornado_state.load()?.denomination.to_le_bytes().as_ref()],
         bump
     )]
-    pub tornado_state: Account<'info, TornadoState>,
+    pub tornado_state: AccountLoader<'info, TornadoState>,
     
     /// The nullifier PDA - if this already exists, withdrawal fails (prevents double-spend)
-    /// Using the elegant solana-mixer pattern: existence = spent
     #[account(
         init,
-        seeds = [b"nullifier", nullifier_hash.as_ref()],
+        seeds = [Nullifier::SEED_PREFIX, tornado_state.key().as_ref(), nullifier_hash.as_ref()],
         bump,
         payer = payer,
         space = 8  // Just discriminator, no data needed
@@ -386,65 +1666,389 @@ pub struct Withdraw<'info> {
     pub nullifier: Account<'info, Nullifier>,
     
     #[account(
-        mut,
-        seeds = [b"vault", tornado_state.key().as_ref()],
+        seeds = [VerifyingKeyAccount::SEED_PREFIX, tornado_state.key().as_ref()],
         bump
     )]
-    pub vault: SystemAccount<'info>,
+    pub verifying_key_account: AccountLoader<'info, VerifyingKeyAccount>,
     
-    /// CHECK: Recipient of withdrawn funds
+    #[account(mint::token_program = token_program)]
+    pub mint: Box<InterfaceAccount<'info, Mint>>,
+    
+    #[account(
+        mut,
+        seeds = [b"token_vault", tornado_state.key().as_ref()],
+        bump,
+        token::mint = mint,
+        token::authority = token_vault,
+        token::token_program = token_program,
+        constraint = !token_vault.is_frozen() @ TornadoError::TokenVaultFrozen
+    )]
+    pub token_vault: Box<InterfaceAccount<'info, TokenAccount>>,
+    
+    /// CHECK: Recipient wallet bound in the proof, receives the SOL refund
     #[account(mut)]
     pub recipient: AccountInfo<'info>,
     
-    /// CHECK: Optional relayer receiving fee
-    #[account(mut)]
-    pub relayer: Option<AccountInfo<'info>>,
+    /// Token account of the recipient wallet bound in the proof
+    #[account(
+        mut,
+        token::mint = mint,
+        token::token_program = token_program
+    )]
+    pub recipient_token_account: Box<InterfaceAccount<'info, TokenAccount>>,
+    
+    /// Optional token account of the relayer receiving the fee
+    #[account(
+        mut,
+        token::mint = mint,
+        token::token_program = token_program
+    )]
+    pub relayer_token_account: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
     
     /// The account paying for nullifier PDA creation (relayer or recipient)
     #[account(mut)]
     pub payer: Signer<'info>,
     
+    pub token_program: Interface<'info, TokenInterface>,
+    
     pub system_program: Program<'info, System>,
+    
+    /// Registry record of the relayer, required when the pool only pays registered relayers
+    #[account(has_one = tornado_state)]
+    pub relayer_record: Option<Account<'info, RelayerRecord>>,
 }
 
 #[derive(Accounts)]
-pub struct MigrateToVault<'info> {
+pub struct ProposeVerifyingKey<'info> {
+    #[account(has_one = authority)]
+    pub tornado_state: AccountLoader<'info, TornadoState>,
+    
+    /// One proposal at a time; cancel or apply it before proposing another
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + PendingVerifyingKey::MAX_SIZE,
+        seeds = [b"pending_vk", tornado_state.key().as_ref()],
+        bump
+    )]
+    pub pending_verifying_key: Account<'info, PendingVerifyingKey>,
+    
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct ApplyVerifyingKey<'info> {
+    #[account(has_one = authority)]
+    pub tornado_state: AccountLoader<'info, TornadoState>,
+    
     #[account(
         mut,
-        has_one = authority,
-        seeds = [b"tornado"],
+        seeds = [VerifyingKeyAccount::SEED_PREFIX, tornado_state.key().as_ref()],
+        bump
+    )]
+    pub verifying_key_account: AccountLoader<'info, VerifyingKeyAccount>,
+    
+    #[account(
+        mut,
+        close = authority,
+        has_one = tornado_state,
+        seeds = [b"pending_vk", tornado_state.key().as_ref()],
+        bump
+    )]
+    pub pending_verifying_key: Account<'info, PendingVerifyingKey>,
+    
+    #[account(mut)]
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct CancelVerifyingKey<'info> {
+    #[account(has_one = authority)]
+    pub tornado_state: AccountLoader<'info, TornadoState>,
+    
+    #[account(
+        mut,
+        close = authority,
+        has_one = tornado_state,
+        seeds = [b"pending_vk", tornado_state.key().as_ref()],
+        bump
+    )]
+    pub pending_verifying_key: Account<'info, PendingVerifyingKey>,
+    
+    #[account(mut)]
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct RenounceVerifyingKeyRotation<'info> {
+    #[account(mut, has_one = authority)]
+    pub tornado_state: AccountLoader<'info, TornadoState>,
+    
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct SetPaused<'info> {
+    #[account(mut, has_one = authority)]
+    pub tornado_state: AccountLoader<'info, TornadoState>,
+    
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct RenouncePause<'info> {
+    #[account(mut, has_one = authority)]
+    pub tornado_state: AccountLoader<'info, TornadoState>,
+    
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct LowerFeeCap<'info> {
+    #[account(mut, has_one = authority)]
+    pub tornado_state: AccountLoader<'info, TornadoState>,
+    
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct SetRegisteredRelayersOnly<'info> {
+    #[account(mut, has_one = authority)]
+    pub tornado_state: AccountLoader<'info, TornadoState>,
+    
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct RegisterRelayer<'info> {
+    pub tornado_state: AccountLoader<'info, TornadoState>,
+    
+    /// One record per (pool, relayer); its lamports above rent are the stake
+    #[account(
+        init,
+        payer = relayer,
+        space = 8 + RelayerRecord::MAX_SIZE,
+        seeds = [RelayerRecord::SEED_PREFIX, tornado_state.key().as_ref(), relayer.key().as_ref()],
+        bump
+    )]
+    pub relayer_record: Account<'info, RelayerRecord>,
+    
+    #[account(mut)]
+    pub relayer: Signer<'info>,
+    
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct UpdateRelayer<'info> {
+    #[account(
+        mut,
+        has_one = relayer,
+        seeds = [RelayerRecord::SEED_PREFIX, relayer_record.tornado_state.as_ref(), relayer.key().as_ref()],
+        bump
+    )]
+    pub relayer_record: Account<'info, RelayerRecord>,
+    
+    #[account(mut)]
+    pub relayer: Signer<'info>,
+    
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct DeregisterRelayer<'info> {
+    #[account(
+        mut,
+        has_one = relayer,
+        seeds = [RelayerRecord::SEED_PREFIX, relayer_record.tornado_state.as_ref(), relayer.key().as_ref()],
+        bump
+    )]
+    pub relayer_record: Account<'info, RelayerRecord>,
+    
+    pub relayer: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct WithdrawRelayerStake<'info> {
+    #[account(
+        mut,
+        close = relayer,
+        has_one = relayer,
+        seeds = [RelayerRecord::SEED_PREFIX, relayer_record.tornado_state.as_ref(), relayer.key().as_ref()],
         bump
     )]
-    pub tornado_state: Account<'info, TornadoState>,
+    pub relayer_record: Account<'info, RelayerRecord>,
     
+    #[account(mut)]
+    pub relayer: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct SlashRelayer<'info> {
+    #[account(has_one = authority)]
+    pub tornado_state: AccountLoader<'info, TornadoState>,
+    
+    #[account(mut, has_one = tornado_state)]
+    pub relayer_record: Account<'info, RelayerRecord>,
+    
+    #[account(mut)]
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct ProposeAuthority<'info> {
+    #[account(mut, has_one = authority)]
+    pub tornado_state: AccountLoader<'info, TornadoState>,
+    
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct AcceptAuthority<'info> {
     #[account(
         mut,
-        seeds = [b"vault", tornado_state.key().as_ref()],
-        bump
+        constraint = tornado_state.load()?.pending_authority == new_authority.key() @ TornadoError::NotPendingAuthority
     )]
-    pub vault: SystemAccount<'info>,
+    pub tornado_state: AccountLoader<'info, TornadoState>,
     
-    #[account(mut)]
-    pub authority: Signer<'info>,
+    pub new_authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct RenounceAuthority<'info> {
+    #[account(mut, has_one = authority)]
+    pub tornado_state: AccountLoader<'info, TornadoState>,
     
-    pub system_program: Program<'info, System>,
+    pub authority: Signer<'info>,
 }
 
-#[account]
+/// Pool state, zero-copy so deposits and withdrawals touch the tree and roots in place
+/// instead of Borsh-decoding and re-encoding the whole account on every instruction
+/// The account data continues past these fields with the root history ring:
+/// `root_history_size` roots of 32 bytes, see `TornadoState::split`
+#[account(zero_copy)]
 pub struct TornadoState {
-    pub authority: Pubkey,
+    pub authority: Pubkey,  // Pubkey::default() once renounced: no admin instruction can pass `has_one`
+    pub pending_authority: Pubkey,  // Proposed authority awaiting accept_authority, Pubkey::default() if none
     pub denomination: u64,
+    pub mint: Pubkey,  // Pubkey::default() for native SOL pools
     pub merkle_tree: MerkleTree,
-    pub roots: [[u8; 32]; ROOT_HISTORY_SIZE as usize],
     pub current_root_index: u32,
     pub next_index: u32,
-    pub verifying_key: Vec<u8>,  // Groth16 verifying key from trusted setup
+    pub root_history_size: u32,
+    pub vk_rotation_renounced: u8,  // Non-zero once renounced: the verifying key can never change again
+    pub deposits_paused: u8,        // Non-zero while deposits are halted by the authority
+    pub withdrawals_paused: u8,     // Non-zero while withdrawals are halted by the authority
+    pub pause_renounced: u8,        // Non-zero once renounced: the pool can never be paused again
+    pub max_fee_bps: u16,           // Relayer fee cap relative to the denomination, only lowerable
+    pub registered_relayers_only: u8,  // Non-zero if relayer fees may only go to registered relayers
+    pub queued_deposits: u8,        // Non-zero if deposits go through the DepositQueue
+    pub _padding: [u8; 4],
+    pub max_fee_amount: u64,        // Absolute relayer fee cap in base units, only lowerable
 }
 
 impl TornadoState {
-    // Size without nullifier/commitment Vecs - much cleaner!
-    // 32 (authority) + 8 (denomination) + MerkleTree::SIZE + (32 * 30) (roots) + 4 (current_root_index) + 4 (next_index) + 2048 (verifying_key)
-    pub const MAX_SIZE: usize = 32 + 8 + MerkleTree::SIZE + (32 * 30) + 4 + 4 + 2048;
+    // Size of the fixed fields, without nullifier/commitment Vecs - much cleaner!
+    // 32 (authority) + 32 (pending_authority) + 8 (denomination) + 32 (mint) + MerkleTree::SIZE + 4 (current_root_index) + 4 (next_index) + 4 (root_history_size)
+    // + 1 (vk_rotation_renounced) + 1 (deposits_paused) + 1 (withdrawals_paused) + 1 (pause_renounced)
+    // + 2 (max_fee_bps) + 1 (registered_relayers_only) + 1 (queued_deposits) + 4 (padding) + 8 (max_fee_amount)
+    // The verifying key lives in its own VerifyingKeyAccount
+    pub const MAX_SIZE: usize = 32 + 32 + 8 + 32 + MerkleTree::SIZE + 4 + 4 + 4 + 1 + 1 + 1 + 1 + 2 + 1 + 1 + 4 + 8;
+    
+    /// Account space (discriminator included) for a pool keeping `root_history_size` roots
+    pub const fn space(root_history_size: u32) -> usize {
+        8 + Self::MAX_SIZE + 32 * root_history_size as usize
+    }
+    
+    /// Borrow pool account data as the fixed state and its root history ring
+    /// The discriminator has already been checked by `AccountLoader` during account validation
+    pub fn split(data: &[u8]) -> Result<(&TornadoState, &[[u8; 32]])> {
+        require!(data.len() >= 8 + Self::MAX_SIZE, ErrorCode::AccountDidNotDeserialize);
+        let (state, roots) = data[8..].split_at(Self::MAX_SIZE);
+        let state: &TornadoState = bytemuck::from_bytes(state);
+        let roots = roots
+            .get(..32 * state.root_history_size as usize)
+            .ok_or(ErrorCode::AccountDidNotDeserialize)?;
+        Ok((state, bytemuck::cast_slice(roots)))
+    }
+    
+    /// Mutable counterpart of `split`, used by deposits to record new roots
+    pub fn split_mut(data: &mut [u8]) -> Result<(&mut TornadoState, &mut [[u8; 32]])> {
+        require!(data.len() >= 8 + Self::MAX_SIZE, ErrorCode::AccountDidNotDeserialize);
+        let (state, roots) = data[8..].split_at_mut(Self::MAX_SIZE);
+        let state: &mut TornadoState = bytemuck::from_bytes_mut(state);
+        let roots = roots
+            .get_mut(..32 * state.root_history_size as usize)
+            .ok_or(ErrorCode::AccountDidNotDeserialize)?;
+        Ok((state, bytemuck::cast_slice_mut(roots)))
+    }
+}
+
+/// Groth16 verifying key of a pool, validated once and stored in the fixed layout
+/// groth16-solana consumes, so withdrawals borrow it without parsing or allocating
+/// PDA: [b"verifying_key", tornado_state]
+#[account(zero_copy)]
+pub struct VerifyingKeyAccount {
+    pub tornado_state: Pubkey,
+    pub merkle_tree_height: u32,  // `levels` of the circuit this key verifies
+    pub vk_alpha_g1: [u8; 64],
+    pub vk_beta_g2: [u8; 128],
+    pub vk_gamma_g2: [u8; 128],
+    pub vk_delta_g2: [u8; 128],
+    pub vk_ic: [[u8; 64]; 9],  // NR_PUBLIC_INPUTS + 1
+}
+
+impl VerifyingKeyAccount {
+    pub const SEED_PREFIX: &'static [u8] = b"verifying_key";
+    
+    // 32 (tornado_state) + 4 (merkle_tree_height) + 64 (alpha_g1) + 3 * 128 (beta/gamma/delta_g2) + 9 * 64 (IC)
+    pub const SIZE: usize = 32 + 4 + 64 + 3 * 128 + (NR_PUBLIC_INPUTS as usize + 1) * 64;
+    
+    /// Validate a serialized key (see `validate_verifying_key`) and copy its points in
+    /// together with the tree depth of the circuit it belongs to
+    pub fn store(&mut self, vk_bytes: &[u8], merkle_tree_height: u32) -> Result<()> {
+        validate_verifying_key(vk_bytes)?;
+        validate_merkle_tree_height(merkle_tree_height)?;
+        let vk = deserialize_verifying_key(vk_bytes)?;
+        
+        self.merkle_tree_height = merkle_tree_height;
+        self.vk_alpha_g1 = vk.vk_alpha_g1;
+        self.vk_beta_g2 = vk.vk_beta_g2;
+        self.vk_gamma_g2 = vk.vk_gamme_g2;
+        self.vk_delta_g2 = vk.vk_delta_g2;
+        self.vk_ic.copy_from_slice(vk.vk_ic);
+        
+        Ok(())
+    }
+    
+    /// Borrow the stored points as a groth16-solana verifying key
+    pub fn as_groth16_verifying_key(&self) -> Groth16Verifyingkey<'_> {
+        Groth16Verifyingkey {
+            nr_pubinputs: NR_PUBLIC_INPUTS as usize,
+            vk_alpha_g1: self.vk_alpha_g1,
+            vk_beta_g2: self.vk_beta_g2,
+            vk_gamme_g2: self.vk_gamma_g2,
+            vk_delta_g2: self.vk_delta_g2,
+            vk_ic: &self.vk_ic,
+        }
+    }
+}
+
+/// Verifying key waiting out the rotation timelock
+#[account]
+pub struct PendingVerifyingKey {
+    pub tornado_state: Pubkey,
+    pub verifying_key: Vec<u8>,
+    pub merkle_tree_height: u32,
+    pub activation_time: i64,
+}
+
+impl PendingVerifyingKey {
+    // 32 (tornado_state) + 4 + 2048 (verifying_key) + 4 (merkle_tree_height) + 8 (activation_time)
+    pub const MAX_SIZE: usize = 32 + 4 + MAX_VERIFYING_KEY_SIZE + 4 + 8;
 }
 
 #[event]
@@ -454,6 +2058,30 @@ pub struct DepositEvent {
     pub timestamp: i64,
 }
 
+#[event]
+pub struct DepositQueuedEvent {
+    pub tornado_state: Pubkey,
+    pub commitment: [u8; 32],
+    pub queue_position: u64,
+    pub timestamp: i64,
+}
+
+/// Leaves first_leaf_index..first_leaf_index + count are now provable against `root`
+#[event]
+pub struct DepositQueueProcessedEvent {
+    pub tornado_state: Pubkey,
+    pub first_leaf_index: u32,
+    pub count: u32,
+    pub root: [u8; 32],
+    pub remaining: u32,
+}
+
+#[event]
+pub struct QueuedDepositsChangedEvent {
+    pub tornado_state: Pubkey,
+    pub queued_deposits: bool,
+}
+
 #[event]
 pub struct WithdrawalEvent {
     pub to: Pubkey,
@@ -462,18 +2090,161 @@ pub struct WithdrawalEvent {
     pub fee: u64,
 }
 
+#[event]
+pub struct MultiOutputWithdrawalEvent {
+    pub outputs: Vec<WithdrawOutput>,
+    pub nullifier_hash: [u8; 32],
+    pub relayer: Option<Pubkey>,
+    pub fee: u64,
+}
+
 #[event]
 pub struct MigrationEvent {
     pub amount_migrated: u64,
     pub timestamp: i64,
 }
 
+#[event]
+pub struct VerifyingKeyProposedEvent {
+    pub tornado_state: Pubkey,
+    pub activation_time: i64,
+}
+
+#[event]
+pub struct VerifyingKeyAppliedEvent {
+    pub tornado_state: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct VerifyingKeyCancelledEvent {
+    pub tornado_state: Pubkey,
+}
+
+#[event]
+pub struct VerifyingKeyRotationRenouncedEvent {
+    pub tornado_state: Pubkey,
+}
+
+#[event]
+pub struct PauseChangedEvent {
+    pub tornado_state: Pubkey,
+    pub deposits_paused: bool,
+    pub withdrawals_paused: bool,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct PauseRenouncedEvent {
+    pub tornado_state: Pubkey,
+}
+
+#[event]
+pub struct FeeCapLoweredEvent {
+    pub tornado_state: Pubkey,
+    pub max_fee_bps: u16,
+    pub max_fee_amount: u64,
+}
+
+#[event]
+pub struct RelayerModeChangedEvent {
+    pub tornado_state: Pubkey,
+    pub registered_relayers_only: bool,
+}
+
+#[event]
+pub struct RelayerRegisteredEvent {
+    pub tornado_state: Pubkey,
+    pub relayer: Pubkey,
+    pub stake: u64,
+    pub fee_bps: u16,
+    pub metadata_url: String,
+}
+
+#[event]
+pub struct RelayerUpdatedEvent {
+    pub tornado_state: Pubkey,
+    pub relayer: Pubkey,
+    pub stake: u64,
+    pub fee_bps: u16,
+    pub metadata_url: String,
+}
+
+#[event]
+pub struct RelayerDeregisteredEvent {
+    pub tornado_state: Pubkey,
+    pub relayer: Pubkey,
+    pub unbonding_since: i64,
+}
+
+#[event]
+pub struct RelayerStakeWithdrawnEvent {
+    pub tornado_state: Pubkey,
+    pub relayer: Pubkey,
+    pub amount: u64,
+}
+
+#[event]
+pub struct RelayerSlashedEvent {
+    pub tornado_state: Pubkey,
+    pub relayer: Pubkey,
+    pub amount: u64,
+    pub remaining_stake: u64,
+}
+
+#[event]
+pub struct AuthorityProposedEvent {
+    pub tornado_state: Pubkey,
+    pub authority: Pubkey,
+    pub pending_authority: Pubkey,
+}
+
+#[event]
+pub struct AuthorityTransferredEvent {
+    pub tornado_state: Pubkey,
+    pub previous_authority: Pubkey,
+    pub new_authority: Pubkey,
+}
+
+#[event]
+pub struct AuthorityRenouncedEvent {
+    pub tornado_state: Pubkey,
+    pub previous_authority: Pubkey,
+}
+
 /// Empty nullifier account - existence means the nullifier has been spent
 /// This elegant solution leverages Solana's account model for O(1) lookups
 /// Based on the proven pattern from solana-mixer-core
 #[account]
 pub struct Nullifier {}
 
+impl Nullifier {
+    pub const SEED_PREFIX: &'static [u8] = b"nullifier";
+    
+    /// Derive the nullifier PDA for a pool: [b"nullifier", tornado_state, nullifier_hash]
+    /// Clients use this to check whether a note has been spent before submitting a withdrawal
+    pub fn derive_pda(
+        tornado_state: &Pubkey,
+        nullifier_hash: &[u8; 32],
+        program_id: &Pubkey,
+    ) -> (Pubkey, u8) {
+        Pubkey::find_program_address(
+            &[Self::SEED_PREFIX, tornado_state.as_ref(), nullifier_hash.as_ref()],
+            program_id,
+        )
+    }
+    
+    /// Derive the pre-scoping nullifier PDA: [b"nullifier", nullifier_hash]
+    /// These accounts were created by the single [b"tornado"] pool and are never written again.
+    /// Indexers still need them to report the spent status of notes from that legacy pool.
+    pub fn derive_legacy_pda(nullifier_hash: &[u8; 32], program_id: &Pubkey) -> (Pubkey, u8) {
+        Pubkey::find_program_address(
+            &[Self::SEED_PREFIX, nullifier_hash.as_ref()],
+            program_id,
+        )
+    }
+}
+
 #[error_code]
 pub enum TornadoError {
     #[msg("Fee exceeds transfer value")]
@@ -510,10 +2281,90 @@ pub enum TornadoError {
     RelayerAccountMissing,
     #[msg("Recipient account cannot be an executable program")]
     BadRecipient,
+    #[msg("Pool denomination must be greater than zero")]
+    InvalidDenomination,
+    #[msg("Mint charges a transfer fee, pool transfers would not be exact")]
+    MintHasTransferFee,
+    #[msg("Mint creates frozen token accounts by default")]
+    MintDefaultFrozen,
+    #[msg("Mint uses a Token-2022 extension that pools do not support")]
+    UnsupportedMintExtension,
+    #[msg("Token vault is frozen")]
+    TokenVaultFrozen,
+    #[msg("Token account is not owned by the address bound in the proof")]
+    TokenAccountOwnerMismatch,
+    #[msg("Recipient account does not match the recipient bound in the proof")]
+    RecipientMismatch,
+    #[msg("Refund must be zero when no relayer is used")]
+    RefundWithoutRelayer,
+    #[msg("Payer cannot cover the refund")]
+    InsufficientFundsForRefund,
+    #[msg("Verifying key rotation has been renounced for this pool")]
+    VkRotationRenounced,
+    #[msg("Verifying key rotation timelock has not elapsed")]
+    VkTimelockNotElapsed,
+    #[msg("Merkle tree height must be between 8 and 32")]
+    InvalidMerkleTreeHeight,
+    #[msg("Verifying key was built for a different Merkle tree height than the pool")]
+    MerkleTreeHeightMismatch,
+    #[msg("Root history size must be between 1 and 250")]
+    InvalidRootHistorySize,
+    #[msg("Commitment is not a canonical BN254 scalar field element")]
+    CommitmentNotInField,
+    #[msg("Nullifier hash is not a canonical BN254 scalar field element")]
+    NullifierHashNotInField,
+    #[msg("Poseidon hash computation failed")]
+    HashFailure,
+    #[msg("Deposits are paused for this pool")]
+    DepositsPaused,
+    #[msg("Withdrawals are paused for this pool")]
+    WithdrawalsPaused,
+    #[msg("Pausing has been renounced for this pool")]
+    PauseRenounced,
+    #[msg("Signer is not the pending authority of this pool")]
+    NotPendingAuthority,
+    #[msg("Fee exceeds the pool's relayer fee cap")]
+    FeeExceedsCap,
+    #[msg("Fee cap in basis points must be at most 10000")]
+    InvalidFeeCap,
+    #[msg("Fee caps can only be lowered")]
+    FeeCapIncrease,
+    #[msg("Relayer metadata URL is too long")]
+    MetadataUrlTooLong,
+    #[msg("Relayer stake is below the minimum")]
+    InsufficientRelayerStake,
+    #[msg("Relayer is not an active registered relayer of this pool")]
+    RelayerNotRegistered,
+    #[msg("Fee exceeds the relayer's registered quote")]
+    FeeExceedsRelayerQuote,
+    #[msg("Relayer must deregister before withdrawing its stake")]
+    RelayerStillRegistered,
+    #[msg("Relayer stake is still in its unbonding period")]
+    RelayerStillUnbonding,
+    #[msg("Slash amount exceeds the relayer's stake")]
+    SlashExceedsStake,
+    #[msg("Deposit batch must hold between 1 and 16 commitments")]
+    InvalidBatchSize,
+    #[msg("Pool takes deposits through its deposit queue")]
+    DepositsQueued,
+    #[msg("Pool does not use a deposit queue")]
+    DepositsNotQueued,
+    #[msg("Deposit queue is full")]
+    DepositQueueFull,
+    #[msg("Deposit queue is empty")]
+    DepositQueueEmpty,
+    #[msg("Withdrawal must have between 1 and 4 outputs")]
+    InvalidOutputCount,
+    #[msg("Output amounts must add up to the denomination minus the fee")]
+    OutputsAmountMismatch,
+    #[msg("Outputs hash is small enough to be read as a refund; change an output")]
+    OutputsHashCollidesWithRefund,
+    #[msg("Deposit queue still holds commitments")]
+    DepositQueueNotEmpty,
 }
 
 // Helper functions
-fn is_known_root(roots: &[[u8; 32]; ROOT_HISTORY_SIZE as usize], current_index: u32, root: &[u8; 32]) -> bool {
+fn is_known_root(roots: &[[u8; 32]], current_index: u32, root: &[u8; 32]) -> bool {
     if root == &[0u8; 32] {
         return false;
     }
@@ -525,7 +2376,7 @@ fn is_known_root(roots: &[[u8; 32]; ROOT_HISTORY_SIZE as usize], current_index:
         }
         
         if i == 0 {
-            i = ROOT_HISTORY_SIZE - 1;
+            i = roots.len() as u32 - 1;
         } else {
             i -= 1;
         }
@@ -538,8 +2389,294 @@ fn is_known_root(roots: &[[u8; 32]; ROOT_HISTORY_SIZE as usize], current_index:
     false
 }
 
+/// Insert a commitment into the pool's tree and push the new root into the history ring
+/// Shared by the SOL and SPL token deposit paths
+fn insert_commitment(
+    tornado_state: &mut TornadoState,
+    roots: &mut [[u8; 32]],
+    commitment: [u8; 32],
+) -> Result<u32> {
+    require!(tornado_state.deposits_paused == 0, TornadoError::DepositsPaused);
+    
+    // Non-canonical values would fail inside Poseidon or alias a reduced element
+    require!(
+        is_canonical_field_element(&commitment),
+        TornadoError::CommitmentNotInField
+    );
+    
+    let leaf_index = tornado_state.merkle_tree.insert(commitment)?;
+    
+    // Commitment is stored in the Merkle tree, no need for separate Vec
+    
+    // Update root history
+    let new_root = tornado_state.merkle_tree.get_root();
+    let new_index = (tornado_state.current_root_index + 1) % roots.len() as u32;
+    tornado_state.current_root_index = new_index;
+    roots[new_index as usize] = new_root;
+    
+    Ok(leaf_index)
+}
+
+/// Batch counterpart of `insert_commitment`: every commitment is checked before any is inserted,
+/// and a single root is pushed into the history ring for the whole batch
+fn insert_commitments(
+    tornado_state: &mut TornadoState,
+    roots: &mut [[u8; 32]],
+    commitments: &[[u8; 32]],
+) -> Result<u32> {
+    require!(tornado_state.deposits_paused == 0, TornadoError::DepositsPaused);
+    insert_paid_commitments(tornado_state, roots, commitments)
+}
+
+/// `insert_commitments` without the pause check, for commitments whose deposit already went through
+/// The queue crank uses it directly: pausing stops new deposits, not the insertion of queued ones
+fn insert_paid_commitments(
+    tornado_state: &mut TornadoState,
+    roots: &mut [[u8; 32]],
+    commitments: &[[u8; 32]],
+) -> Result<u32> {
+    require!(
+        (1..=MAX_DEPOSIT_BATCH_SIZE).contains(&commitments.len()),
+        TornadoError::InvalidBatchSize
+    );
+    require!(
+        commitments.iter().all(is_canonical_field_element),
+        TornadoError::CommitmentNotInField
+    );
+    
+    let first_leaf_index = tornado_state.merkle_tree.insert_batch(commitments)?;
+    
+    let new_root = tornado_state.merkle_tree.get_root();
+    let new_index = (tornado_state.current_root_index + 1) % roots.len() as u32;
+    tornado_state.current_root_index = new_index;
+    roots[new_index as usize] = new_root;
+    
+    Ok(first_leaf_index)
+}
+
+/// Move up to `max_leaves` queued commitments (capped at MAX_DEPOSIT_BATCH_SIZE and the leaves left) into the tree
+/// Returns the first leaf index and the inserted commitments; the queue only advances on success
+/// Runs while deposits are paused, so paid-for leaves can always be inserted and withdrawn
+fn process_deposit_queue(
+    tornado_state: &mut TornadoState,
+    roots: &mut [[u8; 32]],
+    deposit_queue: &mut DepositQueue,
+    max_leaves: u32,
+) -> Result<(u32, Vec<[u8; 32]>)> {
+    require!(!deposit_queue.is_empty(), TornadoError::DepositQueueEmpty);
+    
+    let remaining = tornado_state.merkle_tree.remaining_capacity();
+    require!(remaining > 0, TornadoError::MerkleTreeFull);
+    let count = (max_leaves as usize).min(MAX_DEPOSIT_BATCH_SIZE).min(remaining as usize);
+    let commitments = deposit_queue.peek(count);
+    let first_leaf_index = insert_paid_commitments(tornado_state, roots, &commitments)?;
+    deposit_queue.pop(commitments.len());
+    
+    Ok((first_leaf_index, commitments))
+}
+
+/// Pool-level withdrawal checks shared by the SOL and SPL token withdraw paths:
+/// pause flag, fee bounds, root history membership and the Groth16 proof itself
+#[allow(clippy::too_many_arguments)]
+fn verify_withdrawal(
+    tornado_state: &TornadoState,
+    roots: &[[u8; 32]],
+    verifying_key: &Groth16Verifyingkey,
+    proof: &[u8],
+    root: &[u8; 32],
+    nullifier_hash: &[u8; 32],
+    recipient: &Pubkey,
+    relayer: Option<Pubkey>,
+    fee: u64,
+    refund: u64,
+) -> Result<()> {
+    check_withdrawal(tornado_state, roots, root, nullifier_hash, relayer, fee, refund)?;
+    
+    // Verify the zero-knowledge proof using Groth16
+    // This uses Solana's native alt_bn128 syscalls for <200k CU verification
+    // `verifying_key` is the pool's stored key from the trusted setup ceremony,
+    // validated when it was written to the VerifyingKeyAccount
+    verify_proof(
+        proof, 
+        root, 
+        nullifier_hash, 
+        recipient, 
+        &relayer.unwrap_or_default(), 
+        fee, 
+        refund, 
+        verifying_key
+    )
+}
+
+/// Proof-independent checks of every withdrawal kind: pause flag, nullifier encoding,
+/// fee and refund bounds and root history membership
+fn check_withdrawal(
+    tornado_state: &TornadoState,
+    roots: &[[u8; 32]],
+    root: &[u8; 32],
+    nullifier_hash: &[u8; 32],
+    relayer: Option<Pubkey>,
+    fee: u64,
+    refund: u64,
+) -> Result<()> {
+    require!(tornado_state.withdrawals_paused == 0, TornadoError::WithdrawalsPaused);
+    
+    // An aliased nullifier hash (x + r) would derive a second nullifier PDA for the same note
+    require!(
+        is_canonical_field_element(nullifier_hash),
+        TornadoError::NullifierHashNotInField
+    );
+    
+    // Verify fee doesn't exceed denomination, nor the cap the pool promises its users
+    require!(fee <= tornado_state.denomination, TornadoError::FeeExceedsDenomination);
+    require!(fee <= max_fee(tornado_state), TornadoError::FeeExceedsCap);
+    
+    // Refund only makes sense when a relayer is around to fund it
+    validate_refund(relayer, refund)?;
+    
+    // Verify root is in history
+    require!(
+        is_known_root(roots, tornado_state.current_root_index, root),
+        TornadoError::UnknownRoot
+    );
+    
+    Ok(())
+}
+
+/// Pool tree depth must fit the fixed-size MerkleTree arrays and stay cheap enough to be useful
+fn validate_merkle_tree_height(merkle_tree_height: u32) -> Result<()> {
+    require!(
+        (MIN_MERKLE_TREE_HEIGHT..=MAX_MERKLE_TREE_HEIGHT).contains(&merkle_tree_height),
+        TornadoError::InvalidMerkleTreeHeight
+    );
+    Ok(())
+}
+
+/// True if a big-endian 32-byte value is strictly below the BN254 scalar field modulus
+/// Lexicographic order on big-endian bytes is numeric order
+fn is_canonical_field_element(value: &[u8; 32]) -> bool {
+    value < &BN254_SCALAR_FIELD_MODULUS
+}
+
+/// Every pool needs at least the latest root, and the ring must fit in the pool account
+fn validate_root_history_size(root_history_size: u32) -> Result<()> {
+    require!(
+        (1..=MAX_ROOT_HISTORY_SIZE).contains(&root_history_size),
+        TornadoError::InvalidRootHistorySize
+    );
+    Ok(())
+}
+
+/// Update the pool's pause flags unless pausing has been renounced
+fn apply_pause_flags(
+    tornado_state: &mut TornadoState,
+    deposits_paused: bool,
+    withdrawals_paused: bool,
+) -> Result<()> {
+    require!(tornado_state.pause_renounced == 0, TornadoError::PauseRenounced);
+    tornado_state.deposits_paused = deposits_paused as u8;
+    tornado_state.withdrawals_paused = withdrawals_paused as u8;
+    Ok(())
+}
+
+/// Registered-relayers-only pools pay fees solely to active records of this pool
+/// Shared by the SOL and SPL token withdraw paths; `relayer_record` was checked to belong to the pool
+fn validate_relayer_fee(
+    tornado_state: &TornadoState,
+    relayer_record: Option<&RelayerRecord>,
+    relayer: Option<Pubkey>,
+    fee: u64,
+) -> Result<()> {
+    if tornado_state.registered_relayers_only == 0 || fee == 0 {
+        return Ok(());
+    }
+    match relayer {
+        Some(relayer) => relayer_registry::check_registered_relayer(
+            relayer_record,
+            &relayer,
+            fee,
+            tornado_state.denomination,
+        ),
+        None => Ok(()),
+    }
+}
+
+/// Fee caps are a share of the denomination, so at most 100%
+fn validate_max_fee_bps(max_fee_bps: u16) -> Result<()> {
+    require!(max_fee_bps <= MAX_FEE_BPS, TornadoError::InvalidFeeCap);
+    Ok(())
+}
+
+/// Largest relayer fee a withdrawal may pay: the tighter of the relative and absolute caps
+fn max_fee(tornado_state: &TornadoState) -> u64 {
+    // u128 so that large denominations cannot overflow; the result is at most the denomination
+    let relative_cap =
+        u128::from(tornado_state.denomination) * u128::from(tornado_state.max_fee_bps) / u128::from(MAX_FEE_BPS);
+    (relative_cap as u64).min(tornado_state.max_fee_amount)
+}
+
+/// Replace the pool's fee caps, refusing to raise either of them
+fn apply_lower_fee_cap(
+    tornado_state: &mut TornadoState,
+    max_fee_bps: u16,
+    max_fee_amount: u64,
+) -> Result<()> {
+    validate_max_fee_bps(max_fee_bps)?;
+    require!(
+        max_fee_bps <= tornado_state.max_fee_bps && max_fee_amount <= tornado_state.max_fee_amount,
+        TornadoError::FeeCapIncrease
+    );
+    tornado_state.max_fee_bps = max_fee_bps;
+    tornado_state.max_fee_amount = max_fee_amount;
+    Ok(())
+}
+
+/// A pending verifying key may only be applied at or after its activation time
+fn check_timelock_elapsed(activation_time: i64, now: i64) -> Result<()> {
+    require!(now >= activation_time, TornadoError::VkTimelockNotElapsed);
+    Ok(())
+}
+
+/// Refund rule from Tornado Cash: without a relayer nobody can fund a refund, so it must be zero
+fn validate_refund(relayer: Option<Pubkey>, refund: u64) -> Result<()> {
+    if relayer.is_none() {
+        require!(refund == 0, TornadoError::RefundWithoutRelayer);
+    }
+    Ok(())
+}
+
+/// Send the proof-bound `refund` in lamports from the transaction payer to the recipient
+/// The refund never touches the pool vault; the relayer fronts it and recoups it via `fee`
+fn pay_refund<'info>(
+    payer: &Signer<'info>,
+    recipient: &AccountInfo<'info>,
+    system_program: &Program<'info, System>,
+    refund: u64,
+) -> Result<()> {
+    if refund == 0 {
+        return Ok(());
+    }
+    
+    require!(
+        payer.lamports() >= refund,
+        TornadoError::InsufficientFundsForRefund
+    );
+    
+    system_program::transfer(
+        CpiContext::new(
+            system_program.to_account_info(),
+            system_program::Transfer {
+                from: payer.to_account_info(),
+                to: recipient.clone(),
+            },
+        ),
+        refund,
+    )
+}
+
 // Production-ready Groth16 proof verification using Solana's native syscalls
 // This takes less than 200k compute units thanks to alt_bn128 syscalls
+#[allow(clippy::too_many_arguments)]
 fn verify_proof(
     proof: &[u8],
     root: &[u8; 32],
@@ -549,6 +2686,17 @@ fn verify_proof(
     fee: u64,
     refund: u64,
     verifying_key: &Groth16Verifyingkey,
+) -> Result<()> {
+    // Prepare 8 public inputs as required by the circuit
+    let public_inputs = prepare_public_inputs(root, nullifier_hash, recipient, relayer, fee, refund);
+    verify_groth16_proof(proof, &public_inputs, verifying_key)
+}
+
+/// Verify a snarkjs-format proof (A, B, C; 256 bytes) against prepared public inputs
+fn verify_groth16_proof(
+    proof: &[u8],
+    public_inputs: &[[u8; 32]; 8],
+    verifying_key: &Groth16Verifyingkey,
 ) -> Result<()> {
     // Proof should be 256 bytes (64 bytes for A, 128 for B, 64 for C)
     require!(
@@ -578,16 +2726,13 @@ fn verify_proof(
         })?;
     msg!("Proof A negation succeeded!");
     
-    // Prepare 8 public inputs as required by the circuit
-    let public_inputs = prepare_public_inputs(root, nullifier_hash, recipient, relayer, fee, refund);
-    
     // Create and run verifier with correct types
     // Rust will infer Groth16Verifier::<8> from the array type
     let mut verifier = Groth16Verifier::new(
         &proof_a_negated,
         &proof_b_bytes,
         &proof_c_bytes,
-        &public_inputs,
+        public_inputs,
         verifying_key,
     ).map_err(|e| {
         msg!("Failed to create verifier: {:?}", e);
@@ -668,6 +2813,23 @@ fn prepare_public_inputs(
     inputs
 }
 
+/// Public inputs of a multi-output withdrawal: the zero address as recipient and the
+/// outputs hash in the refund slot, which a plain withdrawal's u64 refund cannot reach
+fn prepare_multi_output_public_inputs(
+    root: &[u8; 32],
+    nullifier_hash: &[u8; 32],
+    outputs_hash: &[u8; 32],
+    relayer: &Pubkey,
+    fee: u64,
+) -> [[u8; 32]; 8] {
+    let mut inputs = prepare_public_inputs(root, nullifier_hash, &Pubkey::default(), relayer, fee, 0);
+    
+    // Input 7: outputs hash instead of refund
+    inputs[7] = *outputs_hash;
+    
+    inputs
+}
+
 /// Split a Solana address into high and low parts because they exceed BN254 field size
 /// Addresses are 32 bytes, we split as: high = [0; 16] + [first 16 bytes], low = [0; 16] + [last 16 bytes]
 fn split_address_to_high_low(address: &Pubkey) -> ([u8; 32], [u8; 32]) {
@@ -758,7 +2920,7 @@ fn validate_vault_pda(
 /// **CRITICAL SECURITY FUNCTION**: Safely deserialize stored verifying key from trusted setup
 /// 
 /// This function implements the core fix for the vulnerability where hardcoded verifying keys
-/// were used instead of the verifying key from the trusted setup ceremony stored in `tornado_state.verifying_key`.
+/// were used instead of the verifying key from the trusted setup ceremony passed to `initialize` and stored in the pool's `VerifyingKeyAccount`.
 /// 
 /// # Cryptographic Security Properties:
 /// - Validates all VK components are within BN254 curve parameters
@@ -768,18 +2930,18 @@ fn validate_vault_pda(
 /// - Maintains deterministic verification behavior
 /// 
 /// # Parameters:
-/// - `vk_bytes`: Raw verifying key bytes from `tornado_state.verifying_key`
+/// - `vk_bytes`: Raw verifying key bytes as passed to `initialize` / `propose_verifying_key`
 /// 
 /// # Returns:
-/// - `Ok(Groth16Verifyingkey)`: Successfully deserialized and validated VK
+/// - `Ok(Groth16Verifyingkey)`: Successfully deserialized VK, borrowing its IC points from `vk_bytes`
 /// - `Err(TornadoError::InvalidVerifyingKey)`: Malformed or corrupted VK data
 /// 
 /// # Security Considerations:
-/// - This function MUST be used in production instead of `get_circuit_verifying_key()`
+/// - Only runs when a key is written; withdrawals read the parsed `VerifyingKeyAccount`
 /// - All VK components undergo cryptographic validation
 /// - Protects against VK substitution attacks
 /// - Ensures trusted setup ceremony results are actually used
-fn deserialize_verifying_key(vk_bytes: &[u8]) -> Result<Groth16Verifyingkey> {
+fn deserialize_verifying_key(vk_bytes: &[u8]) -> Result<Groth16Verifyingkey<'_>> {
     // Minimum size validation - VK must contain all required components
     // Structure: nr_pubinputs (4) + alpha_g1 (64) + beta_g2 (128) + gamma_g2 (128) + delta_g2 (128) + IC array
     const MIN_VK_SIZE: usize = 4 + 64 + 128 + 128 + 128 + 64; // At least 1 IC element
@@ -845,23 +3007,14 @@ fn deserialize_verifying_key(vk_bytes: &[u8]) -> Result<Groth16Verifyingkey> {
     let ic_count = (nr_pubinputs + 1) as usize;
     let ic_bytes_needed = ic_count * 64;
     
-    if vk_bytes.len() < offset + ic_bytes_needed {
-        msg!("VK too small for IC array: need {} bytes for {} IC elements", ic_bytes_needed, ic_count);
+    // Trailing bytes would mean nr_pubinputs disagrees with the key that was serialized
+    if vk_bytes.len() != offset + ic_bytes_needed {
+        msg!("VK size does not match IC array: need {} bytes for {} IC elements", ic_bytes_needed, ic_count);
         return Err(TornadoError::InvalidVerifyingKey.into());
     }
     
-    // Allocate IC vector and parse each element
-    let mut vk_ic = Vec::with_capacity(ic_count);
-    for i in 0..ic_count {
-        let ic_offset = offset + (i * 64);
-        let ic_element_bytes = vk_bytes.get(ic_offset..ic_offset + 64)
-            .ok_or_else(|| {
-                msg!("Failed to read IC element {} from VK", i);
-                TornadoError::InvalidVerifyingKey
-            })?;
-        let ic_element: [u8; 64] = ic_element_bytes.try_into().unwrap();
-        vk_ic.push(ic_element);
-    }
+    // Borrow the IC elements in place - no allocation, nothing leaked
+    let vk_ic: &[[u8; 64]] = bytemuck::cast_slice(&vk_bytes[offset..offset + ic_bytes_needed]);
     
     // Additional security validation: Ensure no obvious zero patterns that indicate corruption
     let is_alpha_zero = vk_alpha_g1.iter().all(|&b| b == 0);
@@ -876,12 +3029,12 @@ fn deserialize_verifying_key(vk_bytes: &[u8]) -> Result<Groth16Verifyingkey> {
     
     // Construct and return the validated verifying key
     let verifying_key = Groth16Verifyingkey {
-        nr_pubinputs,
+        nr_pubinputs: nr_pubinputs as usize,
         vk_alpha_g1,
         vk_beta_g2,
         vk_gamme_g2,
         vk_delta_g2,
-        vk_ic: vk_ic.leak(), // Safe to leak as this is long-lived VK data
+        vk_ic,
     };
     
     msg!("Successfully deserialized verifying key with {} public inputs and {} IC elements", 
@@ -890,3 +3043,69 @@ fn deserialize_verifying_key(vk_bytes: &[u8]) -> Result<Groth16Verifyingkey> {
     Ok(verifying_key)
 }
 
+/// Fully validate a verifying key before it is stored in a pool
+/// 
+/// `deserialize_verifying_key` only checks structure, so a key with invalid curve points
+/// would otherwise be accepted at initialization and only fail at the first withdrawal,
+/// locking every deposit made in between. This runs the complete parse up front:
+/// - exact length for `NR_PUBLIC_INPUTS` (and within `MAX_VERIFYING_KEY_SIZE`)
+/// - `nr_pubinputs == NR_PUBLIC_INPUTS`, matching the withdraw circuit
+/// - alpha and every IC element are valid G1 points
+/// - beta, gamma and delta are valid G2 points in the prime-order subgroup
+fn validate_verifying_key(vk_bytes: &[u8]) -> Result<()> {
+    require!(
+        vk_bytes.len() <= MAX_VERIFYING_KEY_SIZE,
+        TornadoError::InvalidVerifyingKey
+    );
+    if vk_bytes.len() != VERIFYING_KEY_SIZE {
+        msg!("VK is {} bytes, expected {}", vk_bytes.len(), VERIFYING_KEY_SIZE);
+        return Err(TornadoError::InvalidVerifyingKey.into());
+    }
+    
+    let vk = deserialize_verifying_key(vk_bytes)?;
+    if vk.nr_pubinputs != NR_PUBLIC_INPUTS as usize {
+        msg!("VK has {} public inputs, circuit requires {}", vk.nr_pubinputs, NR_PUBLIC_INPUTS);
+        return Err(TornadoError::InvalidVerifyingKey.into());
+    }
+    
+    if !is_valid_g1_point(&vk.vk_alpha_g1) {
+        msg!("VK alpha_g1 is not a valid G1 point");
+        return Err(TornadoError::InvalidVerifyingKey.into());
+    }
+    for (name, point) in [
+        ("beta_g2", &vk.vk_beta_g2),
+        ("gamma_g2", &vk.vk_gamme_g2),
+        ("delta_g2", &vk.vk_delta_g2),
+    ] {
+        if !is_valid_g2_point(point) {
+            msg!("VK {} is not a valid G2 point", name);
+            return Err(TornadoError::InvalidVerifyingKey.into());
+        }
+    }
+    for (i, point) in vk.vk_ic.iter().enumerate() {
+        if !is_valid_g1_point(point) {
+            msg!("VK IC element {} is not a valid G1 point", i);
+            return Err(TornadoError::InvalidVerifyingKey.into());
+        }
+    }
+    
+    Ok(())
+}
+
+/// Check a big-endian (x, y) G1 point is on the curve and in the subgroup
+/// BN254 G1 has cofactor 1, so the subgroup check is implied by the curve check
+fn is_valid_g1_point(point_be: &[u8; 64]) -> bool {
+    let point_le = change_endianness(point_be);
+    G1Affine::deserialize_with_mode(&point_le[..], Compress::No, Validate::Yes).is_ok()
+}
+
+/// Check a big-endian G2 point (x.c1, x.c0, y.c1, y.c0) is on the twist and in the subgroup
+/// Reversing each 64-byte coordinate yields ark's little-endian (c0, c1) layout
+fn is_valid_g2_point(point_be: &[u8; 128]) -> bool {
+    let point_le: Vec<u8> = point_be
+        .chunks(64)
+        .flat_map(|coordinate| coordinate.iter().rev().copied())
+        .collect();
+    G2Affine::deserialize_with_mode(&point_le[..], Compress::No, Validate::Yes).is_ok()
+}
+
@@ -1,34 +1,42 @@
 use anchor_lang::prelude::*;
 use light_poseidon::{Poseidon, PoseidonBytesHasher};
 use ark_bn254::Fr;
+use crate::zero_hashes::ZERO_HASHES;
+use crate::{MAX_MERKLE_TREE_HEIGHT, MERKLE_TREE_HEIGHT};
+
+// Poseidon pair hashes performed on this thread, for tests that pin down hashing cost
+#[cfg(test)]
+thread_local! {
+    pub static PAIR_HASHES: std::cell::Cell<usize> = const { std::cell::Cell::new(0) };
+}
 
 /// Direct translation of MerkleTreeWithHistory from Tornado Cash
-#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+/// Plain-old-data so it can live inline in the zero-copy `TornadoState`
+/// Arrays are sized for MAX_MERKLE_TREE_HEIGHT; only the first `levels` entries are used
+/// Empty-subtree hashes come from the constant ZERO_HASHES table, not account storage
+#[zero_copy]
 pub struct MerkleTree {
     pub levels: u32,
-    pub filled_subtrees: [[u8; 32]; 20],
-    pub zeros: [[u8; 32]; 20],
+    pub filled_subtrees: [[u8; 32]; MAX_MERKLE_TREE_HEIGHT as usize],
     pub current_root: [u8; 32],
     pub next_index: u32,
 }
 
 impl MerkleTree {
-    pub const SIZE: usize = 4 + (32 * 20) + (32 * 20) + 32 + 4;
+    pub const SIZE: usize = 4 + 32 * MAX_MERKLE_TREE_HEIGHT as usize + 32 + 4;
     
+    /// Tree with the depth of the bundled withdraw circuit (MERKLE_TREE_HEIGHT)
     pub fn new() -> Self {
-        let zeros = Self::generate_zeros();
-        let mut filled_subtrees = [[0u8; 32]; 20];
-        
-        // Initialize with zero values
-        for i in 0..20 {
-            filled_subtrees[i] = zeros[i];
-        }
-        
+        Self::with_levels(MERKLE_TREE_HEIGHT)
+    }
+    
+    /// Tree of the given depth; callers validate it with `validate_merkle_tree_height`
+    /// No hashing happens here: the empty tree is read straight from ZERO_HASHES
+    pub fn with_levels(levels: u32) -> Self {
         Self {
-            levels: 20,
-            filled_subtrees,
-            zeros,
-            current_root: zeros[19],
+            levels,
+            filled_subtrees: ZERO_HASHES,
+            current_root: ZERO_HASHES[levels as usize - 1],
             next_index: 0,
         }
     }
@@ -47,48 +55,54 @@ impl MerkleTree {
     /// 
     /// Changing this will break ALL existing proofs and make the system incompatible
     /// with previously generated commitments and nullifiers.
-    fn generate_zeros() -> [[u8; 32]; 20] {
-        let mut zeros = [[0u8; 32]; 20];
+    /// Only the first `levels` entries are computed, so zeros[i] is the same at every depth.
+    /// Reference implementation of the ZERO_HASHES table; not called on-chain.
+    pub fn generate_zeros(levels: u32) -> Result<[[u8; 32]; MAX_MERKLE_TREE_HEIGHT as usize]> {
+        let mut zeros = [[0u8; 32]; MAX_MERKLE_TREE_HEIGHT as usize];
         
         // For Poseidon-based circuits, we use the hash of zero
         // This matches what circomlib's MerkleTree expects
         // The first zero is Poseidon(0)
-        zeros[0] = Self::hash_leaf(&[0u8; 32]);
+        zeros[0] = Self::hash_leaf(&[0u8; 32])?;
         
         // Each subsequent zero is the hash of two previous zeros
-        for i in 1..20 {
-            zeros[i] = Self::hash_left_right(&zeros[i - 1], &zeros[i - 1]);
+        for i in 1..levels as usize {
+            zeros[i] = Self::hash_left_right(&zeros[i - 1], &zeros[i - 1])?;
         }
         
-        zeros
+        Ok(zeros)
     }
     
     /// Insert a leaf into the merkle tree
+    /// Either the whole path is hashed and written, or the tree is left untouched
     pub fn insert(&mut self, leaf: [u8; 32]) -> Result<u32> {
+        // u64 so that a 32-level tree does not overflow; next_index itself caps it at u32::MAX leaves
         require!(
-            self.next_index < 2_u32.pow(self.levels),
+            u64::from(self.next_index) < 1_u64 << self.levels && self.next_index < u32::MAX,
             crate::TornadoError::MerkleTreeFull
         );
         
         let mut current_index = self.next_index;
         let mut current_level_hash = leaf;
+        let mut filled_subtrees = self.filled_subtrees;
         let mut left;
         let mut right;
         
-        for i in 0..self.levels as usize {
-            if current_index % 2 == 0 {
+        for (i, filled_subtree) in filled_subtrees.iter_mut().take(self.levels as usize).enumerate() {
+            if current_index & 1 == 0 {
                 left = current_level_hash;
-                right = self.zeros[i];
-                self.filled_subtrees[i] = current_level_hash;
+                right = ZERO_HASHES[i];
+                *filled_subtree = current_level_hash;
             } else {
-                left = self.filled_subtrees[i];
+                left = *filled_subtree;
                 right = current_level_hash;
             }
             
-            current_level_hash = Self::hash_left_right(&left, &right);
+            current_level_hash = Self::hash_left_right(&left, &right)?;
             current_index /= 2;
         }
         
+        self.filled_subtrees = filled_subtrees;
         self.current_root = current_level_hash;
         let inserted_index = self.next_index;
         self.next_index += 1;
@@ -96,6 +110,58 @@ impl MerkleTree {
         Ok(inserted_index)
     }
     
+    /// Leaves that can still be inserted: 2^levels, capped at u32::MAX by `next_index`
+    pub fn remaining_capacity(&self) -> u64 {
+        (1_u64 << self.levels).min(u64::from(u32::MAX)) - u64::from(self.next_index)
+    }
+    
+    /// Insert consecutive leaves, hashing each shared ancestor once
+    /// Level by level, only the parents of the new nodes are recomputed, so N leaves cost
+    /// at most N + 2 * levels - 1 hashes instead of N * levels. The result is identical to
+    /// N calls to `insert`, and like `insert` the tree is untouched on failure.
+    /// Returns the index of the first leaf.
+    pub fn insert_batch(&mut self, leaves: &[[u8; 32]]) -> Result<u32> {
+        require!(!leaves.is_empty(), crate::TornadoError::InvalidBatchSize);
+        require!(
+            u64::from(self.next_index) + leaves.len() as u64 <= 1_u64 << self.levels
+                && u64::from(self.next_index) + leaves.len() as u64 <= u64::from(u32::MAX),
+            crate::TornadoError::MerkleTreeFull
+        );
+        
+        let mut filled_subtrees = self.filled_subtrees;
+        let mut nodes = leaves.to_vec();
+        let mut first_index = self.next_index;
+        
+        for (level, filled_subtree) in filled_subtrees.iter_mut().take(self.levels as usize).enumerate() {
+            let mut parents = Vec::with_capacity(nodes.len() / 2 + 1);
+            let mut i = 0;
+            
+            // A right child first: its left sibling is the completed filled subtree
+            if first_index % 2 == 1 {
+                parents.push(Self::hash_left_right(filled_subtree, &nodes[0])?);
+                i = 1;
+            }
+            
+            while i < nodes.len() {
+                // Left child: remember it, pair it with its new sibling or an empty subtree
+                *filled_subtree = nodes[i];
+                let right = nodes.get(i + 1).unwrap_or(&ZERO_HASHES[level]);
+                parents.push(Self::hash_left_right(&nodes[i], right)?);
+                i += 2;
+            }
+            
+            nodes = parents;
+            first_index /= 2;
+        }
+        
+        self.filled_subtrees = filled_subtrees;
+        self.current_root = nodes[0];
+        let inserted_index = self.next_index;
+        self.next_index += leaves.len() as u32;
+        
+        Ok(inserted_index)
+    }
+    
     /// Get the current merkle root
     pub fn get_root(&self) -> [u8; 32] {
         self.current_root
@@ -103,60 +169,56 @@ impl MerkleTree {
     
     /// Hash two nodes together using Poseidon (ZK-friendly)
     /// This is now using Light Protocol's Poseidon implementation
-    pub fn hash_left_right(left: &[u8; 32], right: &[u8; 32]) -> [u8; 32] {
+    /// Fails with HashFailure instead of producing a bogus node, e.g. for inputs outside the field
+    pub fn hash_left_right(left: &[u8; 32], right: &[u8; 32]) -> Result<[u8; 32]> {
+        #[cfg(test)]
+        PAIR_HASHES.with(|count| count.set(count.get() + 1));
+        
         // Create a Poseidon hasher for 2 inputs
-        let mut hasher = match Poseidon::<Fr>::new_circom(2) {
-            Ok(h) => h,
-            Err(_) => {
-                // Fallback in case of initialization error
-                // This should never happen with valid parameters
-                return [0u8; 32];
-            }
-        };
+        let mut hasher = Poseidon::<Fr>::new_circom(2).map_err(|e| {
+            msg!("Poseidon initialization failed: {}", e);
+            crate::TornadoError::HashFailure
+        })?;
         
         // Hash the two 32-byte inputs
-        match hasher.hash_bytes_be(&[left, right]) {
-            Ok(hash) => hash,
-            Err(_) => {
-                // Fallback in case of hashing error
-                // This should only happen if inputs are invalid
-                [0u8; 32]
-            }
-        }
+        hasher.hash_bytes_be(&[left, right]).map_err(|e| {
+            msg!("Poseidon hash failed: {}", e);
+            crate::TornadoError::HashFailure.into()
+        })
     }
     
     /// Hash a single leaf using Poseidon
-    pub fn hash_leaf(data: &[u8; 32]) -> [u8; 32] {
+    pub fn hash_leaf(data: &[u8; 32]) -> Result<[u8; 32]> {
         // For a single input, we use Poseidon with 1 input
-        let mut hasher = match Poseidon::<Fr>::new_circom(1) {
-            Ok(h) => h,
-            Err(_) => return [0u8; 32],
-        };
-        
-        match hasher.hash_bytes_be(&[data]) {
-            Ok(hash) => hash,
-            Err(_) => [0u8; 32]
-        }
+        let mut hasher = Poseidon::<Fr>::new_circom(1).map_err(|e| {
+            msg!("Poseidon initialization failed: {}", e);
+            crate::TornadoError::HashFailure
+        })?;
+        
+        hasher.hash_bytes_be(&[data]).map_err(|e| {
+            msg!("Poseidon hash failed: {}", e);
+            crate::TornadoError::HashFailure.into()
+        })
     }
     
     /// Generate merkle proof for a given leaf (siblings only)
     /// 
-    /// NOTE: This is a simplified test helper that uses filled_subtrees and zeros.
-    /// Real proofs must be generated off-chain with complete tree data.
+    /// NOTE: This is a simplified test helper that uses filled_subtrees and ZERO_HASHES.
+    /// It is only correct for the latest leaf; clients use `tornado_solana_client::DepositTree`.
     #[cfg(test)]
     pub fn get_proof(&self, leaf_index: u32) -> Vec<[u8; 32]> {
         let mut proof = Vec::new();
         let mut index = leaf_index;
         
-        for i in 0..self.levels as usize {
-            if index % 2 == 0 {
+        for (zero, filled_subtree) in ZERO_HASHES.iter().zip(&self.filled_subtrees).take(self.levels as usize) {
+            if index & 1 == 0 {
                 // If even, sibling is on the right
                 // For even nodes, we need to check if a right sibling exists
                 // This happens when there's another leaf at index + 1
-                proof.push(self.zeros[i]);
+                proof.push(*zero);
             } else {
                 // If odd, sibling is on the left (always exists and is filled)
-                proof.push(self.filled_subtrees[i]);
+                proof.push(*filled_subtree);
             }
             index /= 2;
         }
@@ -167,25 +229,25 @@ impl MerkleTree {
     /// Generate merkle path with siblings and direction bits
     /// Returns (siblings, path_bits) where path_bits[i] = true if going right at level i
     /// 
-    /// NOTE: This is a simplified test helper that uses filled_subtrees and zeros.
-    /// Real proofs must be generated off-chain with complete tree data.
+    /// NOTE: This is a simplified test helper that uses filled_subtrees and ZERO_HASHES.
+    /// It is only correct for the latest leaf; clients use `tornado_solana_client::DepositTree`.
     #[cfg(test)]
     pub fn get_path(&self, leaf_index: u32) -> (Vec<[u8; 32]>, Vec<bool>) {
         let mut siblings = Vec::new();
         let mut path_bits = Vec::new();
         let mut index = leaf_index;
         
-        for i in 0..self.levels as usize {
+        for (zero, filled_subtree) in ZERO_HASHES.iter().zip(&self.filled_subtrees).take(self.levels as usize) {
             // Path bit indicates if we're the right child (1) or left child (0)
             let is_right_child = index % 2 == 1;
             path_bits.push(is_right_child);
             
             if is_right_child {
                 // We're the right child, sibling is on the left
-                siblings.push(self.filled_subtrees[i]);
+                siblings.push(*filled_subtree);
             } else {
                 // We're the left child, sibling is on the right
-                siblings.push(self.zeros[i]);
+                siblings.push(*zero);
             }
             index /= 2;
         }
@@ -199,20 +261,26 @@ impl MerkleTree {
         leaf: &[u8; 32],
         proof: &[[u8; 32]],
         index: u32,
-    ) -> bool {
+    ) -> Result<bool> {
         let mut computed_hash = *leaf;
         let mut current_index = index;
         
         for sibling in proof {
-            if current_index % 2 == 0 {
-                computed_hash = Self::hash_left_right(&computed_hash, sibling);
+            if current_index & 1 == 0 {
+                computed_hash = Self::hash_left_right(&computed_hash, sibling)?;
             } else {
-                computed_hash = Self::hash_left_right(sibling, &computed_hash);
+                computed_hash = Self::hash_left_right(sibling, &computed_hash)?;
             }
             current_index /= 2;
         }
         
-        &computed_hash == root
+        Ok(&computed_hash == root)
+    }
+}
+
+impl Default for MerkleTree {
+    fn default() -> Self {
+        Self::new()
     }
 }
 
@@ -248,7 +316,7 @@ mod tests {
         let root = tree.get_root();
         
         let proof = tree.get_proof(index);
-        assert!(MerkleTree::verify_proof(&root, &leaf, &proof, index));
+        assert!(MerkleTree::verify_proof(&root, &leaf, &proof, index).unwrap());
     }
     
     #[test]
@@ -275,46 +343,103 @@ mod tests {
         // Level 0: index 2 % 2 = 0 (left child)
         // Level 1: index 1 % 2 = 1 (right child)
         // Level 2: index 0 % 2 = 0 (left child)
-        assert_eq!(path_bits[0], false); // left at level 0
-        assert_eq!(path_bits[1], true);  // right at level 1
-        assert_eq!(path_bits[2], false); // left at level 2
+        assert!(!path_bits[0]); // left at level 0
+        assert!(path_bits[1]);  // right at level 1
+        assert!(!path_bits[2]); // left at level 2
     }
     
     #[test]
     fn test_zero_values_poseidon() {
-        let tree = MerkleTree::new();
-        
         // Verify first zero is Poseidon(0)
-        let expected_first_zero = MerkleTree::hash_leaf(&[0u8; 32]);
-        assert_eq!(tree.zeros[0], expected_first_zero);
+        let expected_first_zero = MerkleTree::hash_leaf(&[0u8; 32]).unwrap();
+        assert_eq!(ZERO_HASHES[0], expected_first_zero);
         
         // Verify each subsequent zero is hash of previous two
         for i in 1..20 {
-            let expected = MerkleTree::hash_left_right(&tree.zeros[i-1], &tree.zeros[i-1]);
-            assert_eq!(tree.zeros[i], expected);
+            let expected = MerkleTree::hash_left_right(&ZERO_HASHES[i-1], &ZERO_HASHES[i-1]).unwrap();
+            assert_eq!(ZERO_HASHES[i], expected);
         }
     }
     
     #[test]
-    fn test_proof_verification_multiple_leaves() {
-        let mut tree = MerkleTree::new();
+    fn test_zero_hashes_match_generate_zeros() {
+        let generated = MerkleTree::generate_zeros(MAX_MERKLE_TREE_HEIGHT).unwrap();
+        assert_eq!(ZERO_HASHES, generated);
+        
+        // An empty tree of any depth has the matching zero as its root
+        for levels in crate::MIN_MERKLE_TREE_HEIGHT..=MAX_MERKLE_TREE_HEIGHT {
+            let tree = MerkleTree::with_levels(levels);
+            assert_eq!(tree.get_root(), generated[levels as usize - 1]);
+        }
+    }
+    
+    #[test]
+    fn test_zeros_match_across_depths() {
+        let shallow = MerkleTree::generate_zeros(crate::MIN_MERKLE_TREE_HEIGHT).unwrap();
+        let default = MerkleTree::generate_zeros(MERKLE_TREE_HEIGHT).unwrap();
+        
+        // A shallower pool shares the zero chain prefix of the 20-level circuit tree
+        let n = crate::MIN_MERKLE_TREE_HEIGHT as usize;
+        assert_eq!(shallow[..n], default[..n]);
+        assert_eq!(MerkleTree::with_levels(crate::MIN_MERKLE_TREE_HEIGHT).current_root, default[n - 1]);
+    }
+    
+    #[test]
+    fn test_small_tree_fills_up() {
+        let mut tree = MerkleTree::with_levels(crate::MIN_MERKLE_TREE_HEIGHT);
         
-        // Insert 4 leaves
-        let leaves = [[1u8; 32], [2u8; 32], [3u8; 32], [4u8; 32]];
-        let mut indices = Vec::new();
+        for i in 0..256u32 {
+            let mut leaf = [0u8; 32];
+            leaf[28..].copy_from_slice(&(i + 1).to_be_bytes());
+            assert_eq!(tree.insert(leaf).unwrap(), i);
+        }
+        assert!(tree.insert([1u8; 32]).is_err());
+    }
+    
+    #[test]
+    fn test_max_depth_tree_inserts() {
+        let mut tree = MerkleTree::with_levels(crate::MAX_MERKLE_TREE_HEIGHT);
         
-        for leaf in &leaves {
-            indices.push(tree.insert(*leaf).unwrap());
+        let leaf = [7u8; 32];
+        let index = tree.insert(leaf).unwrap();
+        let (siblings, _) = tree.get_path(index);
+        assert_eq!(siblings.len(), 32);
+        assert!(MerkleTree::verify_proof(&tree.get_root(), &leaf, &siblings, index).unwrap());
+    }
+    
+    #[test]
+    fn test_bad_leaf_aborts_insert_and_leaves_tree_untouched() {
+        let mut tree = MerkleTree::new();
+        tree.insert([1u8; 32]).unwrap();
+        let before = tree;
+        
+        // 2^256 - 1 is not a BN254 field element, Poseidon refuses it
+        match tree.insert([0xff; 32]).unwrap_err() {
+            Error::AnchorError(e) => assert_eq!(
+                e.error_code_number,
+                u32::from(crate::TornadoError::HashFailure)
+            ),
+            other => panic!("unexpected error: {:?}", other),
         }
         
-        let root = tree.get_root();
+        assert_eq!(tree.next_index, before.next_index);
+        assert_eq!(tree.current_root, before.current_root);
+        assert_eq!(tree.filled_subtrees, before.filled_subtrees);
+        assert!(MerkleTree::hash_leaf(&[0xff; 32]).is_err());
+    }
+    
+    #[test]
+    fn test_proof_verification_latest_leaf() {
+        let mut tree = MerkleTree::new();
         
-        // Verify proof for each leaf
-        for (i, leaf) in leaves.iter().enumerate() {
-            let proof = tree.get_proof(indices[i]);
+        // get_proof only knows the frontier, so check each leaf right after inserting it;
+        // proofs for older leaves are covered by the client's DepositTree
+        for leaf in [[1u8; 32], [2u8; 32], [3u8; 32], [4u8; 32]] {
+            let index = tree.insert(leaf).unwrap();
+            let proof = tree.get_proof(index);
             assert!(
-                MerkleTree::verify_proof(&root, leaf, &proof, indices[i]),
-                "Proof verification failed for leaf at index {}", indices[i]
+                MerkleTree::verify_proof(&tree.get_root(), &leaf, &proof, index).unwrap(),
+                "Proof verification failed for leaf at index {}", index
             );
         }
     }
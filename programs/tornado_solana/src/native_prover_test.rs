@@ -0,0 +1,54 @@
+#[cfg(test)]
+mod native_prover_tests {
+    use crate::{deserialize_verifying_key, validate_verifying_key, verify_proof};
+    use anchor_lang::prelude::*;
+    use serde_json::Value;
+
+    /// Proof and verifying key written by tornado-solana-prover (seeded development setup)
+    fn vector() -> Value {
+        serde_json::from_str(include_str!("../../../prover/test_vectors/withdraw_proof.json")).unwrap()
+    }
+
+    fn bytes(value: &Value) -> Vec<u8> {
+        hex::decode(value.as_str().unwrap()).unwrap()
+    }
+
+    fn bytes32(value: &Value) -> [u8; 32] {
+        bytes(value).try_into().unwrap()
+    }
+
+    fn verify(vector: &Value, recipient: &Pubkey, fee: u64) -> Result<()> {
+        let vk_bytes = bytes(&vector["verifying_key"]);
+        let vk = deserialize_verifying_key(&vk_bytes)?;
+        verify_proof(
+            &bytes(&vector["proof"]),
+            &bytes32(&vector["root"]),
+            &bytes32(&vector["nullifier_hash"]),
+            recipient,
+            &Pubkey::new_from_array(bytes32(&vector["relayer"])),
+            fee,
+            vector["refund"].as_u64().unwrap(),
+            &vk,
+        )
+    }
+
+    #[test]
+    fn test_native_prover_output_verifies() {
+        let vector = vector();
+        assert!(validate_verifying_key(&bytes(&vector["verifying_key"])).is_ok());
+
+        let recipient = Pubkey::new_from_array(bytes32(&vector["recipient"]));
+        let fee = vector["fee"].as_u64().unwrap();
+        assert!(verify(&vector, &recipient, fee).is_ok());
+    }
+
+    #[test]
+    fn test_native_prover_output_bound_to_inputs() {
+        let vector = vector();
+        let recipient = Pubkey::new_from_array(bytes32(&vector["recipient"]));
+        let fee = vector["fee"].as_u64().unwrap();
+
+        assert!(verify(&vector, &Pubkey::new_unique(), fee).is_err());
+        assert!(verify(&vector, &recipient, fee + 1).is_err());
+    }
+}
@@ -2,7 +2,7 @@
 mod nullifier_pda_tests {
     use super::*;
     use anchor_lang::prelude::*;
-    use anchor_lang::InstructionData;
+    use crate::Nullifier;
     use solana_program_test::*;
     use solana_sdk::{
         instruction::Instruction,
@@ -25,48 +25,6 @@ mod nullifier_pda_tests {
         // The PDA account model does it all for us
     }
     
-    /// Verify nullifier PDA derivation is deterministic
-    #[test]
-    fn test_nullifier_pda_derivation() {
-        let program_id = crate::id();
-        let nullifier_hash = [42u8; 32];
-        
-        // Derive PDA address with namespace prefix
-        let (pda, bump) = Pubkey::find_program_address(
-            &[b"nullifier", nullifier_hash.as_ref()],
-            &program_id,
-        );
-        
-        // Derive again - should be identical
-        let (pda2, bump2) = Pubkey::find_program_address(
-            &[b"nullifier", nullifier_hash.as_ref()],
-            &program_id,
-        );
-        
-        assert_eq!(pda, pda2, "PDA should be deterministic");
-        assert_eq!(bump, bump2, "Bump should be deterministic");
-    }
-    
-    /// Test that different nullifiers create different PDAs
-    #[test]
-    fn test_different_nullifiers_different_pdas() {
-        let program_id = crate::id();
-        let nullifier1 = [1u8; 32];
-        let nullifier2 = [2u8; 32];
-        
-        let (pda1, _) = Pubkey::find_program_address(
-            &[b"nullifier", nullifier1.as_ref()],
-            &program_id,
-        );
-        
-        let (pda2, _) = Pubkey::find_program_address(
-            &[b"nullifier", nullifier2.as_ref()],
-            &program_id,
-        );
-        
-        assert_ne!(pda1, pda2, "Different nullifiers must create different PDAs");
-    }
-    
     /// Calculate rent cost for nullifier storage
     #[test]
     fn test_nullifier_rent_cost() {
@@ -120,10 +78,7 @@ mod nullifier_pda_tests {
         // PDA lookup (new approach) - just derive address
         let program_id = crate::id();
         let start = std::time::Instant::now();
-        let (_pda, _bump) = Pubkey::find_program_address(
-            &[b"nullifier", target.as_ref()],
-            &program_id,
-        );
+        let (_pda, _bump) = Nullifier::derive_pda(&Pubkey::default(), &target, &program_id);
         let pda_time = start.elapsed();
         
         println!("Vec lookup (10k elements): {:?}", vec_time);
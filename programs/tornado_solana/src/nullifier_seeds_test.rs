@@ -0,0 +1,126 @@
+#[cfg(test)]
+mod nullifier_seeds_tests {
+    use crate::test_accounts::{error_code, validate_with_args, Pool, TestAccount};
+    use crate::{Nullifier, Withdraw};
+    use anchor_lang::error::ErrorCode;
+    use anchor_lang::prelude::*;
+    use anchor_lang::InstructionData;
+
+    const DENOMINATION: u64 = 1_000_000_000;
+
+    /// Verify nullifier PDA derivation is deterministic
+    #[test]
+    fn test_nullifier_pda_derivation() {
+        let program_id = crate::id();
+        let tornado_state = Pubkey::new_unique();
+        let nullifier_hash = [42u8; 32];
+
+        // Derive PDA address with namespace prefix and pool key
+        let (pda, bump) = Pubkey::find_program_address(
+            &[b"nullifier", tornado_state.as_ref(), nullifier_hash.as_ref()],
+            &program_id,
+        );
+
+        // The client helper must match the seeds used by the Withdraw context
+        let (pda2, bump2) = Nullifier::derive_pda(&tornado_state, &nullifier_hash, &program_id);
+
+        assert_eq!(pda, pda2, "PDA should be deterministic");
+        assert_eq!(bump, bump2, "Bump should be deterministic");
+    }
+
+    /// Test that different nullifiers create different PDAs
+    #[test]
+    fn test_different_nullifiers_different_pdas() {
+        let program_id = crate::id();
+        let tornado_state = Pubkey::new_unique();
+
+        let (pda1, _) = Nullifier::derive_pda(&tornado_state, &[1u8; 32], &program_id);
+        let (pda2, _) = Nullifier::derive_pda(&tornado_state, &[2u8; 32], &program_id);
+
+        assert_ne!(pda1, pda2, "Different nullifiers must create different PDAs");
+    }
+
+    /// The same nullifier hash in two pools must never map to the same PDA
+    #[test]
+    fn test_nullifier_pdas_scoped_to_pool() {
+        let program_id = crate::id();
+        let nullifier_hash = [3u8; 32];
+
+        let (pda_a, _) = Nullifier::derive_pda(&Pubkey::new_unique(), &nullifier_hash, &program_id);
+        let (pda_b, _) = Nullifier::derive_pda(&Pubkey::new_unique(), &nullifier_hash, &program_id);
+
+        assert_ne!(pda_a, pda_b, "Pools must not share nullifier PDAs");
+    }
+
+    /// Legacy [b"nullifier", hash] accounts live in a disjoint address space from scoped ones
+    #[test]
+    fn test_legacy_nullifier_pda_distinct_from_scoped() {
+        let program_id = crate::id();
+        let nullifier_hash = [4u8; 32];
+        let pool = Pubkey::new_unique();
+
+        let (legacy, _) = Nullifier::derive_legacy_pda(&nullifier_hash, &program_id);
+        let (expected_legacy, _) = Pubkey::find_program_address(
+            &[b"nullifier", nullifier_hash.as_ref()],
+            &program_id,
+        );
+        let (scoped, _) = Nullifier::derive_pda(&pool, &nullifier_hash, &program_id);
+
+        assert_eq!(legacy, expected_legacy, "Legacy helper must match the old seeds");
+        assert_ne!(legacy, scoped, "Scoped nullifiers must not collide with legacy ones");
+    }
+
+    /// The Withdraw context must seed the nullifier with the third instruction argument,
+    /// not with the leading bytes of the serialized proof
+    #[test]
+    fn test_withdraw_seeds_use_nullifier_hash_argument() {
+        let nullifier_hash = [0x22u8; 32];
+        let args = crate::instruction::Withdraw {
+            proof: vec![0xAAu8; 256],
+            root: [0x11u8; 32],
+            nullifier_hash,
+            recipient: Pubkey::new_unique(),
+            relayer: None,
+            fee: 0,
+            refund: 0,
+        }
+        .data()[8..]
+        .to_vec();
+
+        let mut pool = Pool::new(DENOMINATION, Pubkey::new_unique(), Pubkey::default());
+        let mut recipient = TestAccount::new(Pubkey::new_unique(), Pubkey::default(), vec![]);
+        let mut payer = TestAccount::signer(Pubkey::new_unique());
+        let mut relayer = TestAccount::none();
+        let mut relayer_record = TestAccount::none();
+
+        // What `#[instruction(nullifier_hash: [u8; 32])]` alone would decode: the proof's length prefix and bytes
+        let leading_bytes: [u8; 32] = args[..32].try_into().unwrap();
+        let state_key = pool.state.key;
+
+        for (nullifier_key, accepted) in [
+            (Nullifier::derive_pda(&state_key, &nullifier_hash, &crate::ID).0, true),
+            (Nullifier::derive_pda(&state_key, &leading_bytes, &crate::ID).0, false),
+            (Nullifier::derive_legacy_pda(&nullifier_hash, &crate::ID).0, false),
+        ] {
+            let mut nullifier = TestAccount::uncreated(nullifier_key, crate::ID);
+            let infos = [
+                pool.state.info(),
+                nullifier.info(),
+                pool.verifying_key.info(),
+                pool.vault.info(),
+                recipient.info(),
+                relayer.info(),
+                payer.info(),
+                pool.system_program.info(),
+                relayer_record.info(),
+            ];
+            let result = validate_with_args::<Withdraw>(&infos, &args);
+
+            if accepted {
+                assert!(result.is_ok(), "scoped nullifier PDA rejected: {:?}", result);
+            } else {
+                assert_eq!(error_code(result), u32::from(ErrorCode::ConstraintSeeds));
+            }
+        }
+    }
+}
@@ -0,0 +1,214 @@
+#[cfg(test)]
+mod pool_isolation_tests {
+    use crate::test_accounts::{error_code, validate, validate_with_args, Pool, TestAccount};
+    use crate::{is_known_root, Deposit, MerkleTree, TornadoState, Withdraw, ROOT_HISTORY_SIZE};
+    use anchor_lang::error::ErrorCode;
+    use anchor_lang::prelude::Pubkey;
+    use anchor_lang::InstructionData;
+
+    const POOL_DENOMINATIONS: [u64; 4] = [
+        100_000_000,     // 0.1 SOL
+        1_000_000_000,   // 1 SOL
+        10_000_000_000,  // 10 SOL
+        100_000_000_000, // 100 SOL
+    ];
+
+    /// Derive the pool state PDA exactly as the Initialize/Deposit/Withdraw contexts do
+    fn pool_pda(denomination: u64) -> Pubkey {
+        Pubkey::find_program_address(
+            &[b"tornado", denomination.to_le_bytes().as_ref()],
+            &crate::id(),
+        ).0
+    }
+
+    fn vault_pda(pool: &Pubkey) -> Pubkey {
+        Pubkey::find_program_address(&[b"vault", pool.as_ref()], &crate::id()).0
+    }
+
+    fn nullifier_pda(pool: &Pubkey, nullifier_hash: &[u8; 32]) -> Pubkey {
+        Pubkey::find_program_address(
+            &[b"nullifier", pool.as_ref(), nullifier_hash.as_ref()],
+            &crate::id(),
+        ).0
+    }
+
+    /// Borsh-encoded `withdraw` arguments, as the Withdraw context's `#[instruction(...)]` reads them
+    fn withdraw_args(nullifier_hash: [u8; 32]) -> Vec<u8> {
+        crate::instruction::Withdraw {
+            proof: vec![0u8; 256],
+            root: [0u8; 32],
+            nullifier_hash,
+            recipient: Pubkey::new_unique(),
+            relayer: None,
+            fee: 0,
+            refund: 0,
+        }
+        .data()[8..]
+        .to_vec()
+    }
+
+    /// Minimal stand-in for a pool's tree + root history, mirroring `deposit`
+    struct PoolSim {
+        merkle_tree: MerkleTree,
+        roots: [[u8; 32]; ROOT_HISTORY_SIZE as usize],
+        current_root_index: u32,
+    }
+
+    impl PoolSim {
+        fn new() -> Self {
+            Self {
+                merkle_tree: MerkleTree::new(),
+                roots: [[0u8; 32]; ROOT_HISTORY_SIZE as usize],
+                current_root_index: 0,
+            }
+        }
+
+        fn deposit(&mut self, commitment: [u8; 32]) -> [u8; 32] {
+            self.merkle_tree.insert(commitment).unwrap();
+            let new_root = self.merkle_tree.get_root();
+            self.current_root_index = (self.current_root_index + 1) % ROOT_HISTORY_SIZE;
+            self.roots[self.current_root_index as usize] = new_root;
+            new_root
+        }
+    }
+
+    #[test]
+    fn test_each_denomination_gets_its_own_pool() {
+        let pools: Vec<Pubkey> = POOL_DENOMINATIONS.iter().map(|d| pool_pda(*d)).collect();
+
+        for i in 0..pools.len() {
+            for j in (i + 1)..pools.len() {
+                assert_ne!(pools[i], pools[j], "Pools {} and {} share a state PDA", i, j);
+            }
+        }
+
+        // Derivation must be deterministic so clients can find a pool by denomination alone
+        assert_eq!(pools[1], pool_pda(1_000_000_000));
+    }
+
+    #[test]
+    fn test_pools_cannot_share_vaults() {
+        let vaults: Vec<Pubkey> = POOL_DENOMINATIONS
+            .iter()
+            .map(|d| vault_pda(&pool_pda(*d)))
+            .collect();
+
+        for i in 0..vaults.len() {
+            for j in (i + 1)..vaults.len() {
+                assert_ne!(vaults[i], vaults[j], "Pools {} and {} share a vault", i, j);
+            }
+        }
+    }
+
+    #[test]
+    fn test_state_signing_seeds_reproduce_pool_pda() {
+        // migrate_to_vault signs for the pool with its denomination seed and canonical bump
+        for denomination in POOL_DENOMINATIONS {
+            let denomination_bytes = denomination.to_le_bytes();
+            let (pool, bump) = Pubkey::find_program_address(
+                &[b"tornado", denomination_bytes.as_ref()],
+                &crate::id(),
+            );
+            let signer_seeds: &[&[u8]] = &[b"tornado", denomination_bytes.as_ref(), &[bump]];
+
+            assert_eq!(Pubkey::create_program_address(signer_seeds, &crate::id()).unwrap(), pool);
+            assert_eq!(pool, pool_pda(denomination));
+        }
+    }
+
+    #[test]
+    fn test_pool_seeds_bind_the_stored_denomination() {
+        let mut pool = Pool::new(POOL_DENOMINATIONS[0], Pubkey::new_unique(), Pubkey::default());
+        let mut depositor = TestAccount::signer(Pubkey::new_unique());
+
+        let accepted = {
+            let infos = [pool.state.info(), pool.vault.info(), depositor.info(), pool.system_program.info()];
+            validate::<Deposit>(&infos)
+        };
+        assert!(accepted.is_ok(), "pool rejected at its own PDA: {:?}", accepted);
+
+        // A 0.1 SOL pool claiming to take 100 SOL deposits no longer sits at its own PDA
+        let state: &mut TornadoState =
+            bytemuck::from_bytes_mut(&mut pool.state.data[8..8 + TornadoState::MAX_SIZE]);
+        state.denomination = POOL_DENOMINATIONS[3];
+
+        let infos = [pool.state.info(), pool.vault.info(), depositor.info(), pool.system_program.info()];
+        assert_eq!(error_code(validate::<Deposit>(&infos)), u32::from(ErrorCode::ConstraintSeeds));
+    }
+
+    #[test]
+    fn test_deposit_rejects_another_pools_vault() {
+        let mut pool = Pool::new(POOL_DENOMINATIONS[0], Pubkey::new_unique(), Pubkey::default());
+        let mut other = Pool::new(POOL_DENOMINATIONS[3], Pubkey::new_unique(), Pubkey::default());
+        let mut depositor = TestAccount::signer(Pubkey::new_unique());
+
+        let infos = [pool.state.info(), other.vault.info(), depositor.info(), pool.system_program.info()];
+        assert_eq!(error_code(validate::<Deposit>(&infos)), u32::from(ErrorCode::ConstraintSeeds));
+    }
+
+    #[test]
+    fn test_withdraw_rejects_another_pools_nullifier() {
+        let nullifier_hash = [7u8; 32];
+        let mut pool = Pool::new(POOL_DENOMINATIONS[0], Pubkey::new_unique(), Pubkey::default());
+        let other_pool = pool_pda(POOL_DENOMINATIONS[3]);
+        let mut recipient = TestAccount::new(Pubkey::new_unique(), Pubkey::default(), vec![]);
+        let mut payer = TestAccount::signer(Pubkey::new_unique());
+        let mut relayer = TestAccount::none();
+        let mut relayer_record = TestAccount::none();
+
+        for (nullifier_pool, expected) in [(pool.state.key, Ok(())), (other_pool, Err(ErrorCode::ConstraintSeeds))] {
+            let mut nullifier =
+                TestAccount::uncreated(nullifier_pda(&nullifier_pool, &nullifier_hash), crate::ID);
+            let infos = [
+                pool.state.info(),
+                nullifier.info(),
+                pool.verifying_key.info(),
+                pool.vault.info(),
+                recipient.info(),
+                relayer.info(),
+                payer.info(),
+                pool.system_program.info(),
+                relayer_record.info(),
+            ];
+            let result = validate_with_args::<Withdraw>(&infos, &withdraw_args(nullifier_hash));
+            match expected {
+                Ok(()) => assert!(result.is_ok(), "own nullifier rejected: {:?}", result),
+                Err(e) => assert_eq!(error_code(result), u32::from(e)),
+            }
+        }
+    }
+
+    #[test]
+    fn test_pools_cannot_share_nullifiers() {
+        let nullifier_hash = [7u8; 32];
+        let small_pool = pool_pda(POOL_DENOMINATIONS[0]);
+        let large_pool = pool_pda(POOL_DENOMINATIONS[3]);
+
+        // The same nullifier hash maps to a different PDA in every pool, so spending in
+        // one pool can neither block nor satisfy the double-spend check of another
+        assert_ne!(
+            nullifier_pda(&small_pool, &nullifier_hash),
+            nullifier_pda(&large_pool, &nullifier_hash)
+        );
+    }
+
+    #[test]
+    fn test_pools_cannot_share_roots() {
+        let mut pool_a = PoolSim::new();
+        let mut pool_b = PoolSim::new();
+
+        let root_a = pool_a.deposit([1u8; 32]);
+
+        assert!(is_known_root(&pool_a.roots, pool_a.current_root_index, &root_a));
+        assert!(
+            !is_known_root(&pool_b.roots, pool_b.current_root_index, &root_a),
+            "A root from pool A must not be accepted by pool B"
+        );
+
+        // Even after pool B grows, it only ever knows its own roots
+        let root_b = pool_b.deposit([2u8; 32]);
+        assert_ne!(root_a, root_b);
+        assert!(!is_known_root(&pool_b.roots, pool_b.current_root_index, &root_a));
+        assert!(!is_known_root(&pool_a.roots, pool_a.current_root_index, &root_b));
+    }
+}
@@ -48,7 +48,7 @@ mod real_proof_tests {
             &relayer,
             fee,
             refund,
-            &vk,
+            vk,
         );
         
         match result {
@@ -96,7 +96,7 @@ mod real_proof_tests {
             &relayer,
             1_000_000,
             0,
-            &vk,
+            vk,
         );
         
         assert!(result.is_err(), "Invalid proof should fail verification");
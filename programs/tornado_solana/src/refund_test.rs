@@ -0,0 +1,154 @@
+#[cfg(test)]
+mod refund_tests {
+    use crate::{
+        get_circuit_verifying_key, pay_refund, validate_refund, verify_proof, verify_withdrawal,
+        MerkleTree, TornadoError, TornadoState, MAX_FEE_BPS, ROOT_HISTORY_SIZE,
+    };
+    use crate::test_accounts::{accounts_with_args, error_code, TestAccount};
+    use anchor_lang::prelude::*;
+    use anchor_lang::system_program;
+
+    // Real proof from circuits/test_proof_valid.json (fee = 1_000_000, refund = 0)
+    const PROOF_HEX: &str = "1932c68d13e4e1dce10877fb867b64f4eeb14438acb7d96911c00963ae8892fb1100ad50a064e95082e8d9a4fec8729a0b5f661fd118930934e6f78a0fee3c701da6fa818ef65c4d648ae4f871929d51235c7bc5d5f9218745f5cd0bdea50ad327d5f609d882ae5bbe9872c46866b799dd134dc1734b9cfd2db98ae953975b68102a77cbe32a0714b8a82d59ecebcf6a8caf8ff445b5dca2265e7f35eeb6a8062324a790f811da839b12b02cadb62bcc7fe9e713523c4122c8591ca4cd0111a80ce792e8b41714924c86758605f6403297a9030c424f6c1dd48c0abcfa3fd9c6063e61773609fd0338923bcb58bce991192b83a6c3ab299916982e52fea008e3";
+    const ROOT_HEX: &str = "2ff370c60cf13d3fffa72d1efe3150948a8c84a664c43d427e25b59a01fe3e3c";
+    const NULLIFIER_HASH_HEX: &str = "09ca96f9b5a778899e61078e62a5edfe492398e79db303e0440ee2d6e0e4e7f2";
+    const RECIPIENT_HEX: &str = "e31d835d8657f921fdd87d952db48ec74a949b540a9151fd066c05f7d5c7edd3";
+    const RELAYER_HEX: &str = "c97dda6f4f8d671202378f3843ac899157e5461c0651a0b1cb40541e3397c151";
+    const FEE: u64 = 1_000_000;
+
+    fn bytes32(hex_str: &str) -> [u8; 32] {
+        hex::decode(hex_str).unwrap().try_into().unwrap()
+    }
+
+    fn verify_real_proof_with_refund(refund: u64) -> Result<()> {
+        verify_proof(
+            &hex::decode(PROOF_HEX).unwrap(),
+            &bytes32(ROOT_HEX),
+            &bytes32(NULLIFIER_HASH_HEX),
+            &Pubkey::new_from_array(bytes32(RECIPIENT_HEX)),
+            &Pubkey::new_from_array(bytes32(RELAYER_HEX)),
+            FEE,
+            refund,
+            get_circuit_verifying_key(),
+        )
+    }
+
+    #[test]
+    fn test_zero_refund_without_relayer_allowed() {
+        assert!(validate_refund(None, 0).is_ok());
+    }
+
+    #[test]
+    fn test_refund_without_relayer_rejected() {
+        assert_eq!(
+            error_code(validate_refund(None, 1)),
+            u32::from(TornadoError::RefundWithoutRelayer)
+        );
+        assert_eq!(
+            error_code(validate_refund(None, u64::MAX)),
+            u32::from(TornadoError::RefundWithoutRelayer)
+        );
+    }
+
+    #[test]
+    fn test_refund_with_relayer_allowed() {
+        let relayer = Pubkey::new_unique();
+        assert!(validate_refund(Some(relayer), 0).is_ok());
+        assert!(validate_refund(Some(relayer), 5_000_000).is_ok());
+    }
+
+    #[test]
+    fn test_refund_is_bound_by_the_proof() {
+        // The proof commits to refund = 0; any other refund must fail verification
+        assert!(verify_real_proof_with_refund(0).is_ok());
+        assert_eq!(
+            error_code(verify_real_proof_with_refund(1)),
+            u32::from(TornadoError::InvalidProof)
+        );
+    }
+
+    #[test]
+    fn test_withdrawal_rejects_refund_without_relayer_before_proof_check() {
+        let tornado_state = TornadoState {
+            authority: Pubkey::default(),
+            pending_authority: Pubkey::default(),
+            denomination: 1_000_000_000,
+            mint: Pubkey::default(),
+            merkle_tree: MerkleTree::new(),
+            current_root_index: 0,
+            next_index: 0,
+            root_history_size: ROOT_HISTORY_SIZE,
+            vk_rotation_renounced: 0,
+            deposits_paused: 0,
+            withdrawals_paused: 0,
+            pause_renounced: 0,
+            max_fee_bps: MAX_FEE_BPS,
+            registered_relayers_only: 0,
+            queued_deposits: 0,
+            _padding: [0u8; 4],
+            max_fee_amount: u64::MAX,
+        };
+
+        let result = verify_withdrawal(
+            &tornado_state,
+            &[[0u8; 32]; ROOT_HISTORY_SIZE as usize],
+            get_circuit_verifying_key(),
+            &[0u8; 256],
+            &[1u8; 32],
+            &[2u8; 32],
+            &Pubkey::new_unique(),
+            None,
+            0,
+            1,
+        );
+        assert_eq!(error_code(result), u32::from(TornadoError::RefundWithoutRelayer));
+    }
+
+    /// Payer, recipient and system program, as the withdraw handlers pass them to `pay_refund`
+    #[derive(Accounts)]
+    struct Refund<'info> {
+        #[account(mut)]
+        payer: Signer<'info>,
+        /// CHECK: any account can receive lamports
+        #[account(mut)]
+        recipient: AccountInfo<'info>,
+        system_program: Program<'info, System>,
+    }
+
+    /// Run `pay_refund` from a payer holding `payer_lamports`; returns the payer and recipient balances after
+    fn refund_from(payer_lamports: u64, refund: u64) -> Result<(u64, u64)> {
+        let mut payer = TestAccount { lamports: payer_lamports, ..TestAccount::signer(Pubkey::new_unique()) };
+        let mut recipient = TestAccount { lamports: 0, ..TestAccount::new(Pubkey::new_unique(), system_program::ID, vec![]) };
+        let mut system = TestAccount {
+            executable: true,
+            ..TestAccount::new(system_program::ID, Pubkey::default(), vec![])
+        };
+
+        let infos = [payer.info(), recipient.info(), system.info()];
+        let (accounts, _) = accounts_with_args::<Refund>(&infos, &[])?;
+        pay_refund(&accounts.payer, &accounts.recipient, &accounts.system_program, refund)?;
+        Ok((infos[0].lamports(), infos[1].lamports()))
+    }
+
+    #[test]
+    fn test_refund_is_paid_by_payer() {
+        assert_eq!(refund_from(10_000_000, 5_000_000).unwrap(), (5_000_000, 5_000_000));
+        // The whole balance may go to the recipient
+        assert_eq!(refund_from(5_000_000, 5_000_000).unwrap(), (0, 5_000_000));
+    }
+
+    #[test]
+    fn test_refund_beyond_payer_balance_rejected() {
+        assert_eq!(
+            error_code(refund_from(4_999_999, 5_000_000)),
+            u32::from(TornadoError::InsufficientFundsForRefund)
+        );
+    }
+
+    #[test]
+    fn test_zero_refund_moves_nothing() {
+        // Not even an empty payer is charged or checked
+        assert_eq!(refund_from(0, 0).unwrap(), (0, 0));
+        assert_eq!(refund_from(1_000_000, 0).unwrap(), (1_000_000, 0));
+    }
+}
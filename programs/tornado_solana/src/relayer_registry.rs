@@ -0,0 +1,88 @@
+//! Per-pool relayer registry
+//!
+//! Relayers register with the pools they serve by locking stake in their record PDA
+//! and publishing a fee quote and a metadata URL clients can discover them through.
+//! A pool in registered-relayers-only mode pays fees only to active records whose quote
+//! covers the fee, and its authority can slash the stake of a misbehaving relayer.
+//! Leaving is two-step: `deregister_relayer` stops the record from being accepted and
+//! starts an unbonding period, so stake stays slashable for a while after a relayer quits.
+
+use anchor_lang::prelude::*;
+
+use crate::{TornadoError, MAX_FEE_BPS};
+
+// Minimum stake to be accepted as a registered relayer (1 SOL)
+pub const MIN_RELAYER_STAKE: u64 = 1_000_000_000;
+// Longest metadata URL a record can hold
+pub const MAX_METADATA_URL_LEN: usize = 200;
+// Time between deregistering and reclaiming stake, during which the stake can still be slashed (7 days)
+pub const RELAYER_UNBONDING_PERIOD: i64 = 7 * 24 * 60 * 60;
+
+/// A relayer's registration with one pool
+/// PDA: [b"relayer", tornado_state, relayer]; the account's lamports above rent are the stake
+#[account]
+pub struct RelayerRecord {
+    pub tornado_state: Pubkey,
+    pub relayer: Pubkey,
+    pub stake: u64,
+    pub fee_bps: u16,          // Largest fee the relayer charges, relative to the pool denomination
+    pub unbonding_since: i64,  // 0 while registered, deregistration time afterwards
+    pub metadata_url: String,
+}
+
+impl RelayerRecord {
+    pub const SEED_PREFIX: &'static [u8] = b"relayer";
+
+    // 32 (tornado_state) + 32 (relayer) + 8 (stake) + 2 (fee_bps) + 8 (unbonding_since) + 4 + MAX_METADATA_URL_LEN (metadata_url)
+    pub const MAX_SIZE: usize = 32 + 32 + 8 + 2 + 8 + 4 + MAX_METADATA_URL_LEN;
+
+    /// Registered, not unbonding, and still holding the minimum stake after any slashing
+    pub fn is_active(&self) -> bool {
+        self.unbonding_since == 0 && self.stake >= MIN_RELAYER_STAKE
+    }
+
+    /// Largest fee the relayer quoted for a pool of the given denomination
+    pub fn quoted_fee(&self, denomination: u64) -> u64 {
+        (u128::from(denomination) * u128::from(self.fee_bps) / u128::from(MAX_FEE_BPS)) as u64
+    }
+}
+
+/// Fee quote and metadata URL must fit the record and make sense as a share of the denomination
+pub fn validate_relayer_quote(fee_bps: u16, metadata_url: &str) -> Result<()> {
+    require!(fee_bps <= MAX_FEE_BPS, TornadoError::InvalidFeeCap);
+    require!(
+        metadata_url.len() <= MAX_METADATA_URL_LEN,
+        TornadoError::MetadataUrlTooLong
+    );
+    Ok(())
+}
+
+/// Fee payment rule for registered-relayers-only pools
+/// `record` is the optional RelayerRecord passed to the withdrawal, already checked to belong to the pool
+pub fn check_registered_relayer(
+    record: Option<&RelayerRecord>,
+    relayer: &Pubkey,
+    fee: u64,
+    denomination: u64,
+) -> Result<()> {
+    let record = record.ok_or(TornadoError::RelayerNotRegistered)?;
+    require!(
+        record.relayer == *relayer && record.is_active(),
+        TornadoError::RelayerNotRegistered
+    );
+    require!(
+        fee <= record.quoted_fee(denomination),
+        TornadoError::FeeExceedsRelayerQuote
+    );
+    Ok(())
+}
+
+/// Stake may be reclaimed once the relayer has deregistered and the unbonding period has passed
+pub fn check_unbonded(record: &RelayerRecord, now: i64) -> Result<()> {
+    require!(record.unbonding_since != 0, TornadoError::RelayerStillRegistered);
+    require!(
+        now >= record.unbonding_since + RELAYER_UNBONDING_PERIOD,
+        TornadoError::RelayerStillUnbonding
+    );
+    Ok(())
+}
@@ -0,0 +1,240 @@
+#[cfg(test)]
+mod relayer_registry_tests {
+    use crate::relayer_registry::{
+        check_registered_relayer, check_unbonded, validate_relayer_quote, RelayerRecord,
+        MAX_METADATA_URL_LEN, MIN_RELAYER_STAKE, RELAYER_UNBONDING_PERIOD,
+    };
+    use crate::test_accounts::{
+        accounts_with_args, error_code, new_pool_state, set_unix_timestamp, Pool, TestAccount,
+    };
+    use crate::{validate_relayer_fee, SlashRelayer, TornadoError, TornadoState, WithdrawRelayerStake};
+    use anchor_lang::prelude::*;
+
+    const DENOMINATION: u64 = 1_000_000_000;
+
+    fn new_pool(registered_relayers_only: bool) -> TornadoState {
+        let mut state = new_pool_state();
+        state.registered_relayers_only = registered_relayers_only as u8;
+        state
+    }
+
+    /// Active record quoting 1% (10_000_000 lamports of a 1 SOL note)
+    fn new_record(relayer: Pubkey) -> RelayerRecord {
+        RelayerRecord {
+            tornado_state: Pubkey::new_unique(),
+            relayer,
+            stake: MIN_RELAYER_STAKE,
+            fee_bps: 100,
+            unbonding_since: 0,
+            metadata_url: "https://relayer.example".to_string(),
+        }
+    }
+
+    #[test]
+    fn test_record_fits_reserved_space() {
+        let mut record = new_record(Pubkey::new_unique());
+        record.metadata_url = "x".repeat(MAX_METADATA_URL_LEN);
+
+        let mut data = Vec::new();
+        record.try_serialize(&mut data).unwrap();
+        assert_eq!(data.len(), 8 + RelayerRecord::MAX_SIZE);
+    }
+
+    #[test]
+    fn test_quote_validation() {
+        assert!(validate_relayer_quote(100, "https://relayer.example").is_ok());
+        assert!(validate_relayer_quote(10_000, &"x".repeat(MAX_METADATA_URL_LEN)).is_ok());
+        assert_eq!(
+            error_code(validate_relayer_quote(10_001, "")),
+            u32::from(TornadoError::InvalidFeeCap)
+        );
+        assert_eq!(
+            error_code(validate_relayer_quote(100, &"x".repeat(MAX_METADATA_URL_LEN + 1))),
+            u32::from(TornadoError::MetadataUrlTooLong)
+        );
+    }
+
+    #[test]
+    fn test_only_active_matching_records_are_accepted() {
+        let relayer = Pubkey::new_unique();
+        let record = new_record(relayer);
+        assert!(check_registered_relayer(Some(&record), &relayer, 10_000_000, DENOMINATION).is_ok());
+
+        let not_registered = u32::from(TornadoError::RelayerNotRegistered);
+        assert_eq!(
+            error_code(check_registered_relayer(None, &relayer, 1, DENOMINATION)),
+            not_registered
+        );
+        assert_eq!(
+            error_code(check_registered_relayer(Some(&record), &Pubkey::new_unique(), 1, DENOMINATION)),
+            not_registered
+        );
+
+        let mut unbonding = new_record(relayer);
+        unbonding.unbonding_since = 1_700_000_000;
+        assert_eq!(
+            error_code(check_registered_relayer(Some(&unbonding), &relayer, 1, DENOMINATION)),
+            not_registered
+        );
+
+        let mut slashed = new_record(relayer);
+        slashed.stake = MIN_RELAYER_STAKE - 1;
+        assert_eq!(
+            error_code(check_registered_relayer(Some(&slashed), &relayer, 1, DENOMINATION)),
+            not_registered
+        );
+    }
+
+    #[test]
+    fn test_fee_above_quote_rejected() {
+        let relayer = Pubkey::new_unique();
+        let record = new_record(relayer);
+
+        assert_eq!(
+            error_code(check_registered_relayer(Some(&record), &relayer, 10_000_001, DENOMINATION)),
+            u32::from(TornadoError::FeeExceedsRelayerQuote)
+        );
+    }
+
+    #[test]
+    fn test_mode_only_applies_to_paid_relayers() {
+        let relayer = Pubkey::new_unique();
+
+        // Open pools accept any relayer
+        assert!(validate_relayer_fee(&new_pool(false), None, Some(relayer), 10_000_000).is_ok());
+
+        let pool = new_pool(true);
+        assert_eq!(
+            error_code(validate_relayer_fee(&pool, None, Some(relayer), 1)),
+            u32::from(TornadoError::RelayerNotRegistered)
+        );
+        assert!(validate_relayer_fee(&pool, Some(&new_record(relayer)), Some(relayer), 1).is_ok());
+
+        // Self-relayed and fee-less withdrawals never need a record
+        assert!(validate_relayer_fee(&pool, None, None, 0).is_ok());
+        assert!(validate_relayer_fee(&pool, None, Some(relayer), 0).is_ok());
+    }
+
+    #[test]
+    fn test_stake_locked_until_unbonded() {
+        let mut record = new_record(Pubkey::new_unique());
+        assert_eq!(
+            error_code(check_unbonded(&record, i64::MAX)),
+            u32::from(TornadoError::RelayerStillRegistered)
+        );
+
+        record.unbonding_since = 1_700_000_000;
+        assert_eq!(
+            error_code(check_unbonded(&record, record.unbonding_since + RELAYER_UNBONDING_PERIOD - 1)),
+            u32::from(TornadoError::RelayerStillUnbonding)
+        );
+        assert!(check_unbonded(&record, record.unbonding_since + RELAYER_UNBONDING_PERIOD).is_ok());
+    }
+
+    /// Record PDA of `relayer` holding its rent-exempt minimum plus `stake`
+    fn record_account(tornado_state: Pubkey, relayer: Pubkey, stake: u64, unbonding_since: i64) -> TestAccount {
+        let mut data = Vec::new();
+        RelayerRecord {
+            tornado_state,
+            relayer,
+            stake,
+            unbonding_since,
+            ..new_record(relayer)
+        }
+        .try_serialize(&mut data)
+        .unwrap();
+        data.resize(8 + RelayerRecord::MAX_SIZE, 0);
+
+        let (key, _) = Pubkey::find_program_address(
+            &[RelayerRecord::SEED_PREFIX, tornado_state.as_ref(), relayer.as_ref()],
+            &crate::ID,
+        );
+        TestAccount {
+            lamports: Rent::default().minimum_balance(data.len()) + stake,
+            ..TestAccount::new(key, crate::ID, data)
+        }
+    }
+
+    fn stake_of(record: &TestAccount) -> u64 {
+        RelayerRecord::try_deserialize(&mut &record.data[..]).unwrap().stake
+    }
+
+    fn slash(pool: &mut Pool, record: &mut TestAccount, authority: &mut TestAccount, amount: u64) -> Result<()> {
+        let infos = [pool.state.info(), record.info(), authority.info()];
+        let (mut accounts, bumps) = accounts_with_args::<SlashRelayer>(&infos, &[])?;
+        crate::tornado_solana::slash_relayer(Context::new(&crate::ID, &mut accounts, &[], bumps), amount)?;
+        accounts.exit(&crate::ID)
+    }
+
+    #[test]
+    fn test_slash_moves_stake_to_authority_and_keeps_rent() {
+        let authority = Pubkey::new_unique();
+        let mut pool = Pool::new(DENOMINATION, authority, Pubkey::default());
+        let mut record = record_account(pool.state.key, Pubkey::new_unique(), 3 * MIN_RELAYER_STAKE, 0);
+        let mut authority = TestAccount::signer(authority);
+        let rent = Rent::default().minimum_balance(record.data.len());
+        let authority_before = authority.lamports;
+
+        slash(&mut pool, &mut record, &mut authority, MIN_RELAYER_STAKE).unwrap();
+        assert_eq!(stake_of(&record), 2 * MIN_RELAYER_STAKE);
+        assert_eq!(record.lamports, rent + 2 * MIN_RELAYER_STAKE);
+        assert_eq!(authority.lamports, authority_before + MIN_RELAYER_STAKE);
+
+        // Slashing reaches the stake only, never the rent
+        assert_eq!(
+            error_code(slash(&mut pool, &mut record, &mut authority, 2 * MIN_RELAYER_STAKE + 1)),
+            u32::from(TornadoError::SlashExceedsStake)
+        );
+        assert_eq!(record.lamports, rent + 2 * MIN_RELAYER_STAKE);
+
+        slash(&mut pool, &mut record, &mut authority, 2 * MIN_RELAYER_STAKE).unwrap();
+        assert_eq!(stake_of(&record), 0);
+        assert_eq!(record.lamports, rent);
+        assert_eq!(authority.lamports, authority_before + 3 * MIN_RELAYER_STAKE);
+    }
+
+    fn withdraw_stake(record: &mut TestAccount, relayer: &mut TestAccount) -> Result<()> {
+        // Closing reallocs the record to zero bytes, which rewrites the length word the runtime
+        // serializes in front of account data, so give the data that header
+        let mut serialized = [vec![0u8; 8], record.data.clone()].concat();
+        let record_info = AccountInfo::new(
+            &record.key,
+            false,
+            true,
+            &mut record.lamports,
+            &mut serialized[8..],
+            &record.owner,
+            false,
+            0,
+        );
+        let infos = [record_info, relayer.info()];
+        let (mut accounts, bumps) = accounts_with_args::<WithdrawRelayerStake>(&infos, &[])?;
+        crate::tornado_solana::withdraw_relayer_stake(Context::new(&crate::ID, &mut accounts, &[], bumps))?;
+        // Closes the record, as the generated entrypoint does after the handler returns
+        accounts.exit(&crate::ID)
+    }
+
+    #[test]
+    fn test_stake_returns_to_relayer_only_after_unbonding() {
+        let deregistered_at = 1_700_000_000;
+        let mut relayer = TestAccount::signer(Pubkey::new_unique());
+        let mut record = record_account(Pubkey::new_unique(), relayer.key, MIN_RELAYER_STAKE, deregistered_at);
+        let record_lamports = record.lamports;
+        let relayer_before = relayer.lamports;
+
+        set_unix_timestamp(deregistered_at + RELAYER_UNBONDING_PERIOD - 1);
+        assert_eq!(
+            error_code(withdraw_stake(&mut record, &mut relayer)),
+            u32::from(TornadoError::RelayerStillUnbonding)
+        );
+        assert_eq!(record.lamports, record_lamports);
+        assert_eq!(relayer.lamports, relayer_before);
+
+        // Stake and the record's rent both go back to the relayer
+        set_unix_timestamp(deregistered_at + RELAYER_UNBONDING_PERIOD);
+        withdraw_stake(&mut record, &mut relayer).unwrap();
+        assert_eq!(record.lamports, 0);
+        assert_eq!(record.owner, anchor_lang::system_program::ID);
+        assert_eq!(relayer.lamports, relayer_before + record_lamports);
+    }
+}
@@ -0,0 +1,60 @@
+#[cfg(test)]
+mod root_history_tests {
+    use crate::test_accounts::new_pool_state;
+    use crate::{
+        insert_commitment, is_known_root, validate_root_history_size, MerkleTree, TornadoState,
+        MAX_ROOT_HISTORY_SIZE, MIN_MERKLE_TREE_HEIGHT,
+    };
+
+    /// Pool state with a small tree so many deposits stay cheap in tests
+    fn new_pool(root_history_size: u32) -> (TornadoState, Vec<[u8; 32]>) {
+        let mut state = new_pool_state();
+        state.merkle_tree = MerkleTree::with_levels(MIN_MERKLE_TREE_HEIGHT);
+        state.root_history_size = root_history_size;
+        (state, vec![[0u8; 32]; root_history_size as usize])
+    }
+
+    #[test]
+    fn test_window_slides_across_wraparound() {
+        for size in [1u32, 2, 3, 7, 30, 64] {
+            let (mut state, mut roots) = new_pool(size);
+            let mut history = Vec::new();
+
+            // Wrap the ring at least twice
+            for i in 0..(2 * size + 3) {
+                let mut leaf = [0u8; 32];
+                leaf[28..].copy_from_slice(&(i + 1).to_be_bytes());
+                insert_commitment(&mut state, &mut roots, leaf).unwrap();
+                history.push(state.merkle_tree.get_root());
+            }
+
+            let (stale, window) = history.split_at(history.len() - size as usize);
+            for root in window {
+                assert!(
+                    is_known_root(&roots, state.current_root_index, root),
+                    "root inside a window of {} was rejected", size
+                );
+            }
+            for root in stale {
+                assert!(
+                    !is_known_root(&roots, state.current_root_index, root),
+                    "root outside a window of {} was accepted", size
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_zero_root_never_known() {
+        let (state, roots) = new_pool(5);
+        assert!(!is_known_root(&roots, state.current_root_index, &[0u8; 32]));
+    }
+
+    #[test]
+    fn test_root_history_size_bounds() {
+        assert!(validate_root_history_size(0).is_err());
+        assert!(validate_root_history_size(1).is_ok());
+        assert!(validate_root_history_size(MAX_ROOT_HISTORY_SIZE).is_ok());
+        assert!(validate_root_history_size(MAX_ROOT_HISTORY_SIZE + 1).is_err());
+    }
+}
@@ -1,14 +1,14 @@
 #[cfg(test)]
 mod stored_vk_integration_tests {
     use super::*;
-    use crate::{TornadoState, MerkleTree, deserialize_verifying_key, verify_proof};
+    use crate::{TornadoState, MerkleTree, deserialize_verifying_key, MAX_FEE_BPS};
     use std::fs;
     use std::path::Path;
     
     /// Load the real verifying key from vk_bytes.json
     /// This ensures we test the actual stored-VK path, not a mock
     fn load_real_verifying_key() -> Vec<u8> {
-        let vk_path = Path::new("../../../circuits/build/vk_bytes.json");
+        let vk_path = Path::new(concat!(env!("CARGO_MANIFEST_DIR"), "/../../circuits/build/vk_bytes.json"));
         
         // Fail fast if VK file doesn't exist - no fallback to mock!
         assert!(
@@ -40,18 +40,29 @@ mod stored_vk_integration_tests {
         // Step 2: Simulate initialize instruction
         let mut tornado_state = TornadoState {
             authority: Default::default(),
+            pending_authority: Default::default(),
             denomination: 1_000_000_000, // 1 SOL
+            mint: Default::default(),
             merkle_tree: MerkleTree::new(),
-            roots: [[0u8; 32]; 30],
             current_root_index: 0,
             next_index: 0,
-            verifying_key: vk_bytes.clone(),
+            root_history_size: 30,
+            vk_rotation_renounced: 0,
+            deposits_paused: 0,
+            withdrawals_paused: 0,
+            pause_renounced: 0,
+            max_fee_bps: MAX_FEE_BPS,
+            registered_relayers_only: 0,
+            queued_deposits: 0,
+            _padding: [0u8; 4],
+            max_fee_amount: u64::MAX,
         };
+        let mut roots = [[0u8; 32]; 30];
         
-        println!("Initialized with {} byte VK", tornado_state.verifying_key.len());
+        println!("Initialized with {} byte VK", vk_bytes.len());
         
         // Step 3: Test that stored VK can be deserialized
-        let deserialized_vk = deserialize_verifying_key(&tornado_state.verifying_key);
+        let deserialized_vk = deserialize_verifying_key(&vk_bytes);
         assert!(
             deserialized_vk.is_ok(),
             "Failed to deserialize stored VK: {:?}",
@@ -60,14 +71,19 @@ mod stored_vk_integration_tests {
         
         println!("✅ Stored VK deserialized successfully");
         
+        // The fixture must be the key the real proofs verify against, in verifier G2 order
+        assert!(crate::validate_verifying_key(&vk_bytes).is_ok(), "vk_bytes.json fails full validation");
+        assert_eq!(vk_bytes, crate::test_accounts::circuit_verifying_key_bytes());
+        
         // Step 4: Simulate a deposit (add commitment to tree)
         let commitment = [42u8; 32]; // Test commitment
         let leaf_index = tornado_state.merkle_tree.insert(commitment)
             .expect("Failed to insert commitment");
             
         let new_root = tornado_state.merkle_tree.get_root();
-        tornado_state.roots[0] = new_root;
+        roots[0] = new_root;
         tornado_state.current_root_index = 0;
+        assert!(crate::is_known_root(&roots, tornado_state.current_root_index, &new_root));
         
         println!("✅ Deposited commitment at index {}, root: {:?}", 
                  leaf_index, hex::encode(&new_root[0..8]));
@@ -81,7 +97,7 @@ mod stored_vk_integration_tests {
         
         // Log actual VK size for documentation
         println!("\n📊 VK Metrics:");
-        println!("  - Total size: {} bytes", tornado_state.verifying_key.len());
+        println!("  - Total size: {} bytes", vk_bytes.len());
         println!("  - Public inputs: {}", vk.nr_pubinputs);
         println!("  - IC points: 9 (for 8 public inputs)");
     }
@@ -108,13 +124,11 @@ mod stored_vk_integration_tests {
         println!("✅ Initial roots match");
         
         // Insert same leaves in both trees
-        let test_leaves = vec![
-            [1u8; 32],
+        let test_leaves = [[1u8; 32],
             [2u8; 32],
             [3u8; 32],
             [4u8; 32],
-            [5u8; 32],
-        ];
+            [5u8; 32]];
         
         for (i, leaf) in test_leaves.iter().enumerate() {
             let onchain_idx = onchain_tree.insert(*leaf).unwrap();
@@ -151,13 +165,11 @@ mod stored_vk_integration_tests {
         println!("=== Testing Double-Spend Prevention ===");
         
         let program_id = crate::id();
+        let tornado_state = anchor_lang::prelude::Pubkey::new_unique();
         let nullifier_hash = [99u8; 32];
         
         // First withdrawal - PDA doesn't exist yet
-        let (pda_address, bump) = anchor_lang::prelude::Pubkey::find_program_address(
-            &[b"nullifier", nullifier_hash.as_ref()],
-            &program_id,
-        );
+        let (pda_address, bump) = crate::Nullifier::derive_pda(&tornado_state, &nullifier_hash, &program_id);
         
         println!("First withdrawal:");
         println!("  Nullifier PDA: {}", pda_address);
@@ -173,10 +185,7 @@ mod stored_vk_integration_tests {
         println!("  Result: ❌ Double-spend prevented!");
         
         // Verify PDA is deterministic
-        let (pda_address2, bump2) = anchor_lang::prelude::Pubkey::find_program_address(
-            &[b"nullifier", nullifier_hash.as_ref()],
-            &program_id,
-        );
+        let (pda_address2, bump2) = crate::Nullifier::derive_pda(&tornado_state, &nullifier_hash, &program_id);
         
         assert_eq!(pda_address, pda_address2, "PDA not deterministic!");
         assert_eq!(bump, bump2, "Bump not deterministic!");
@@ -190,13 +199,6 @@ mod stored_vk_integration_tests {
         println!("  This is the relayer or recipient");
         println!("  Cost: ~0.00089 SOL per nullifier PDA");
     }
-    
-    /// Helper to convert bytes to hex for logging
-    fn hex_encode(bytes: &[u8]) -> String {
-        bytes.iter()
-            .map(|b| format!("{:02x}", b))
-            .collect::<String>()
-    }
 }
 
 // Re-export hex encoding for tests
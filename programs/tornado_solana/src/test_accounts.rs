@@ -0,0 +1,275 @@
+//! Native harness for running Anchor account validation (`try_accounts`) in unit tests
+
+use crate::deposit_queue::DepositQueue;
+use crate::{
+    get_circuit_verifying_key, MerkleTree, PendingVerifyingKey, RelayerRecord, TornadoState, VerifyingKeyAccount,
+    ROOT_HISTORY_SIZE,
+};
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::entrypoint::{ProgramResult, SUCCESS};
+use anchor_lang::solana_program::instruction::Instruction;
+use anchor_lang::solana_program::program_stubs::{set_syscall_stubs, SyscallStubs};
+use anchor_lang::solana_program::program_utils::limited_deserialize;
+use anchor_lang::solana_program::system_instruction::SystemInstruction;
+use anchor_lang::{system_program, Bumps, Discriminator};
+use std::cell::{Cell, RefCell};
+use std::collections::BTreeSet;
+use std::sync::Once;
+
+/// Owned backing storage for an AccountInfo handed to `try_accounts`
+pub(crate) struct TestAccount {
+    pub key: Pubkey,
+    pub owner: Pubkey,
+    pub lamports: u64,
+    pub data: Vec<u8>,
+    pub is_signer: bool,
+    pub executable: bool,
+}
+
+impl TestAccount {
+    pub fn new(key: Pubkey, owner: Pubkey, data: Vec<u8>) -> Self {
+        Self { key, owner, lamports: 1_000_000_000, data, is_signer: false, executable: false }
+    }
+
+    pub fn signer(key: Pubkey) -> Self {
+        Self { is_signer: true, ..Self::new(key, system_program::ID, vec![]) }
+    }
+
+    /// Account an `init` constraint is about to create
+    /// The stubbed create CPI only funds it, so it is pre-assigned to `owner` with room for the discriminator
+    pub fn uncreated(key: Pubkey, owner: Pubkey) -> Self {
+        Self { lamports: 0, ..Self::new(key, owner, vec![0u8; 8]) }
+    }
+
+    /// Stand-in for an omitted `Option<...>` account, which Anchor reads as the program ID
+    pub fn none() -> Self {
+        Self::new(crate::ID, Pubkey::default(), vec![])
+    }
+
+    pub fn info(&mut self) -> AccountInfo<'_> {
+        AccountInfo::new(
+            &self.key,
+            self.is_signer,
+            true,
+            &mut self.lamports,
+            &mut self.data,
+            &self.owner,
+            self.executable,
+            0,
+        )
+    }
+}
+
+/// Pool of `denomination` whose authority field is `authority`, at its canonical PDA
+pub(crate) struct Pool {
+    pub state: TestAccount,
+    pub verifying_key: TestAccount,
+    pub pending_verifying_key: TestAccount,
+    pub relayer_record: TestAccount,
+    pub deposit_queue: TestAccount,
+    pub vault: TestAccount,
+    pub system_program: TestAccount,
+}
+
+impl Pool {
+    pub fn new(denomination: u64, authority: Pubkey, pending_authority: Pubkey) -> Self {
+        let (state_key, _) = Pubkey::find_program_address(
+            &[b"tornado", denomination.to_le_bytes().as_ref()],
+            &crate::ID,
+        );
+
+        let mut state_data = vec![0u8; TornadoState::space(ROOT_HISTORY_SIZE)];
+        state_data[..8].copy_from_slice(&TornadoState::discriminator());
+        let state: &mut TornadoState =
+            bytemuck::from_bytes_mut(&mut state_data[8..8 + TornadoState::MAX_SIZE]);
+        state.authority = authority;
+        state.pending_authority = pending_authority;
+        state.denomination = denomination;
+        state.merkle_tree = MerkleTree::new();
+        state.root_history_size = ROOT_HISTORY_SIZE;
+
+        let (vk_key, _) = Pubkey::find_program_address(
+            &[VerifyingKeyAccount::SEED_PREFIX, state_key.as_ref()],
+            &crate::ID,
+        );
+        let mut vk_data = vec![0u8; 8 + VerifyingKeyAccount::SIZE];
+        vk_data[..8].copy_from_slice(&VerifyingKeyAccount::discriminator());
+
+        let (pending_key, _) =
+            Pubkey::find_program_address(&[b"pending_vk", state_key.as_ref()], &crate::ID);
+        let mut pending_data = Vec::new();
+        PendingVerifyingKey {
+            tornado_state: state_key,
+            verifying_key: vec![],
+            merkle_tree_height: 0,
+            activation_time: 0,
+        }
+        .try_serialize(&mut pending_data)
+        .unwrap();
+
+        let mut record_data = Vec::new();
+        RelayerRecord {
+            tornado_state: state_key,
+            relayer: Pubkey::new_unique(),
+            stake: 0,
+            fee_bps: 0,
+            unbonding_since: 0,
+            metadata_url: String::new(),
+        }
+        .try_serialize(&mut record_data)
+        .unwrap();
+
+        let (queue_key, _) = Pubkey::find_program_address(
+            &[DepositQueue::SEED_PREFIX, state_key.as_ref()],
+            &crate::ID,
+        );
+        let mut queue_data = vec![0u8; 8 + DepositQueue::SIZE];
+        queue_data[..8].copy_from_slice(&DepositQueue::discriminator());
+
+        let (vault_key, _) =
+            Pubkey::find_program_address(&[b"vault", state_key.as_ref()], &crate::ID);
+
+        Self {
+            state: TestAccount::new(state_key, crate::ID, state_data),
+            verifying_key: TestAccount::new(vk_key, crate::ID, vk_data),
+            pending_verifying_key: TestAccount::new(pending_key, crate::ID, pending_data),
+            relayer_record: TestAccount::new(Pubkey::new_unique(), crate::ID, record_data),
+            deposit_queue: TestAccount::new(queue_key, crate::ID, queue_data),
+            vault: TestAccount::new(vault_key, system_program::ID, vec![]),
+            system_program: TestAccount {
+                executable: true,
+                ..TestAccount::new(system_program::ID, Pubkey::default(), vec![])
+            },
+        }
+    }
+
+    pub fn renounced(denomination: u64) -> Self {
+        Self::new(denomination, Pubkey::default(), Pubkey::default())
+    }
+}
+
+/// Bare 1 SOL pool state: full-height tree, default root history window, every switch off
+pub(crate) fn new_pool_state() -> TornadoState {
+    let mut state: TornadoState = bytemuck::Zeroable::zeroed();
+    state.denomination = 1_000_000_000;
+    state.merkle_tree = MerkleTree::new();
+    state.root_history_size = ROOT_HISTORY_SIZE;
+    state
+}
+
+/// `new_pool_state` on a tree of `levels`, with the root history ring the instructions take beside it
+pub(crate) fn new_pool(levels: u32) -> (TornadoState, [[u8; 32]; ROOT_HISTORY_SIZE as usize]) {
+    let mut state = new_pool_state();
+    state.merkle_tree = MerkleTree::with_levels(levels);
+    (state, [[0u8; 32]; ROOT_HISTORY_SIZE as usize])
+}
+
+/// Just enough of the runtime for `init` constraints and handlers: the rent and clock sysvars and the
+/// lamports of a system CreateAccount or Transfer. Every other CPI is only recorded, see `take_other_cpis`
+struct RuntimeStubs;
+
+thread_local! {
+    static OTHER_CPIS: RefCell<Vec<Instruction>> = const { RefCell::new(Vec::new()) };
+    static UNIX_TIMESTAMP: Cell<i64> = const { Cell::new(0) };
+}
+
+/// Make the stubbed Clock sysvar report `unix_timestamp` on this test thread
+pub(crate) fn set_unix_timestamp(unix_timestamp: i64) {
+    UNIX_TIMESTAMP.with(|now| now.set(unix_timestamp));
+}
+
+/// Non-system CPIs (token transfers and the like) made on this test thread since the last call, oldest first
+pub(crate) fn take_other_cpis() -> Vec<Instruction> {
+    OTHER_CPIS.with(|cpis| cpis.take())
+}
+
+impl SyscallStubs for RuntimeStubs {
+    fn sol_invoke_signed(
+        &self,
+        instruction: &Instruction,
+        account_infos: &[AccountInfo],
+        _signers_seeds: &[&[&[u8]]],
+    ) -> ProgramResult {
+        if instruction.program_id != system_program::ID {
+            OTHER_CPIS.with(|cpis| cpis.borrow_mut().push(instruction.clone()));
+            return Ok(());
+        }
+        let lamports = match limited_deserialize(&instruction.data, 1024) {
+            Ok(SystemInstruction::CreateAccount { lamports, .. }) | Ok(SystemInstruction::Transfer { lamports }) => lamports,
+            _ => return Ok(()),
+        };
+        let account = |index: usize| {
+            account_infos.iter().find(|info| *info.key == instruction.accounts[index].pubkey).unwrap()
+        };
+        **account(0).try_borrow_mut_lamports()? -= lamports;
+        **account(1).try_borrow_mut_lamports()? += lamports;
+        Ok(())
+    }
+
+    fn sol_get_rent_sysvar(&self, var_addr: *mut u8) -> u64 {
+        unsafe { *(var_addr as *mut Rent) = Rent::default() };
+        SUCCESS
+    }
+
+    fn sol_get_clock_sysvar(&self, var_addr: *mut u8) -> u64 {
+        let clock = Clock { unix_timestamp: UNIX_TIMESTAMP.with(Cell::get), ..Clock::default() };
+        unsafe { *(var_addr as *mut Clock) = clock };
+        SUCCESS
+    }
+}
+
+/// Run Anchor's account validation for an instruction context, as the program entrypoint would
+pub(crate) fn validate<'info, T>(infos: &'info [AccountInfo<'info>]) -> Result<()>
+where
+    T: Bumps + Accounts<'info, T::Bumps>,
+    T::Bumps: Default,
+{
+    validate_with_args::<T>(infos, &[])
+}
+
+/// Same as `validate`, with the Borsh-encoded instruction arguments that `#[instruction(...)]` seeds read
+pub(crate) fn validate_with_args<'info, T>(infos: &'info [AccountInfo<'info>], ix_data: &[u8]) -> Result<()>
+where
+    T: Bumps + Accounts<'info, T::Bumps>,
+    T::Bumps: Default,
+{
+    accounts_with_args::<T>(infos, ix_data).map(|_| ())
+}
+
+/// Validated accounts and bumps, ready to build the `Context` an instruction handler takes
+pub(crate) fn accounts_with_args<'info, T>(infos: &'info [AccountInfo<'info>], ix_data: &[u8]) -> Result<(T, T::Bumps)>
+where
+    T: Bumps + Accounts<'info, T::Bumps>,
+    T::Bumps: Default,
+{
+    static STUBS: Once = Once::new();
+    STUBS.call_once(|| {
+        set_syscall_stubs(Box::new(RuntimeStubs));
+    });
+
+    let mut accounts = infos;
+    let mut bumps = T::Bumps::default();
+    let validated = T::try_accounts(&crate::ID, &mut accounts, ix_data, &mut bumps, &mut BTreeSet::new())?;
+    Ok((validated, bumps))
+}
+
+pub(crate) fn error_code(result: Result<impl std::fmt::Debug>) -> u32 {
+    match result.unwrap_err() {
+        Error::AnchorError(e) => e.error_code_number,
+        other => panic!("unexpected error: {:?}", other),
+    }
+}
+
+/// Circuit verifying key (the one the real proofs verify against) in the on-chain layout
+pub(crate) fn circuit_verifying_key_bytes() -> Vec<u8> {
+    let vk = get_circuit_verifying_key();
+    let mut bytes = (vk.nr_pubinputs as u32).to_le_bytes().to_vec();
+    bytes.extend_from_slice(&vk.vk_alpha_g1);
+    bytes.extend_from_slice(&vk.vk_beta_g2);
+    bytes.extend_from_slice(&vk.vk_gamme_g2);
+    bytes.extend_from_slice(&vk.vk_delta_g2);
+    for ic in vk.vk_ic {
+        bytes.extend_from_slice(ic);
+    }
+    bytes
+}
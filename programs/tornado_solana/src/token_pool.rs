@@ -0,0 +1,180 @@
+//! SPL token pool support
+//!
+//! Token pools reuse the SOL pool's Merkle tree, root history and Groth16 verification;
+//! only the asset movement differs. Both the legacy Token program and Token-2022 are
+//! accepted, but the mint must move exactly `denomination` base units on every transfer
+//! and must not leave the pool's token vault frozen.
+
+use anchor_lang::prelude::*;
+use anchor_spl::token_2022::spl_token_2022::{
+    extension::{
+        default_account_state::DefaultAccountState, BaseStateWithExtensions, ExtensionType,
+        StateWithExtensions,
+    },
+    state::{AccountState, Mint},
+};
+
+use crate::TornadoError;
+
+/// Validate that a mint can back a fixed-denomination pool
+///
+/// Mint extensions are allowlisted; only these pass:
+/// - `MintCloseAuthority`: a mint can only be closed at zero supply, which a funded vault prevents
+/// - `MetadataPointer`, `TokenMetadata`: descriptive only
+/// - `DefaultAccountState` unless Frozen (`MintDefaultFrozen`): the vault would be created frozen
+///
+/// Everything else is rejected, `TransferFeeConfig` with its own error since the vault would receive
+/// less than `denomination`. Extensions this list has not been reviewed against, including any added
+/// to Token-2022 later, fail with `UnsupportedMintExtension`.
+///
+/// Legacy SPL Token mints carry no extensions and always pass.
+pub fn validate_pool_mint(mint: &AccountInfo) -> Result<()> {
+    let data = mint.try_borrow_data()?;
+    check_mint_data(&data)
+}
+
+/// Byte-level check behind `validate_pool_mint`
+pub fn check_mint_data(data: &[u8]) -> Result<()> {
+    let mint = StateWithExtensions::<Mint>::unpack(data)
+        .map_err(|_| error!(TornadoError::UnsupportedMintExtension))?;
+
+    for extension in mint.get_extension_types()? {
+        match extension {
+            ExtensionType::TransferFeeConfig => {
+                return Err(TornadoError::MintHasTransferFee.into());
+            }
+            ExtensionType::DefaultAccountState => {
+                let default_state = mint.get_extension::<DefaultAccountState>()?;
+                if default_state.state == AccountState::Frozen as u8 {
+                    return Err(TornadoError::MintDefaultFrozen.into());
+                }
+            }
+            ExtensionType::MintCloseAuthority
+            | ExtensionType::MetadataPointer
+            | ExtensionType::TokenMetadata => {}
+            _ => {
+                msg!("Unsupported mint extension: {:?}", extension);
+                return Err(TornadoError::UnsupportedMintExtension.into());
+            }
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_accounts::error_code;
+    use anchor_lang::solana_program::program_option::COption;
+    use anchor_lang::solana_program::program_pack::Pack;
+    use anchor_spl::token_2022::spl_token_2022::extension::{
+        interest_bearing_mint::InterestBearingConfig, metadata_pointer::MetadataPointer,
+        mint_close_authority::MintCloseAuthority, permanent_delegate::PermanentDelegate,
+        transfer_fee::TransferFeeConfig, transfer_hook::TransferHook, StateWithExtensionsMut,
+    };
+
+    fn base_mint() -> Mint {
+        Mint {
+            mint_authority: COption::Some(Pubkey::new_unique()),
+            supply: 0,
+            decimals: 6,
+            is_initialized: true,
+            freeze_authority: COption::None,
+        }
+    }
+
+    /// Build Token-2022 mint bytes carrying the given extensions
+    fn mint_with_extensions(
+        extensions: &[ExtensionType],
+        configure: impl FnOnce(&mut StateWithExtensionsMut<Mint>),
+    ) -> Vec<u8> {
+        let len = ExtensionType::try_calculate_account_len::<Mint>(extensions).unwrap();
+        let mut data = vec![0u8; len];
+        let mut state = StateWithExtensionsMut::<Mint>::unpack_uninitialized(&mut data).unwrap();
+        configure(&mut state);
+        state.base = base_mint();
+        state.pack_base();
+        state.init_account_type().unwrap();
+        data
+    }
+
+    #[test]
+    fn test_legacy_mint_accepted() {
+        let mut data = vec![0u8; Mint::LEN];
+        base_mint().pack_into_slice(&mut data);
+        assert!(check_mint_data(&data).is_ok());
+    }
+
+    #[test]
+    fn test_harmless_extension_accepted() {
+        let data = mint_with_extensions(&[ExtensionType::MintCloseAuthority], |state| {
+            state.init_extension::<MintCloseAuthority>(true).unwrap();
+        });
+        assert!(check_mint_data(&data).is_ok());
+
+        let data = mint_with_extensions(&[ExtensionType::MetadataPointer], |state| {
+            state.init_extension::<MetadataPointer>(true).unwrap();
+        });
+        assert!(check_mint_data(&data).is_ok());
+    }
+
+    #[test]
+    fn test_unlisted_extensions_rejected() {
+        // Dangerous ones and merely unreviewed ones alike
+        let datas = [
+            mint_with_extensions(&[ExtensionType::TransferHook], |state| {
+                state.init_extension::<TransferHook>(true).unwrap();
+            }),
+            mint_with_extensions(&[ExtensionType::PermanentDelegate], |state| {
+                state.init_extension::<PermanentDelegate>(true).unwrap();
+            }),
+            mint_with_extensions(&[ExtensionType::InterestBearingConfig], |state| {
+                state.init_extension::<InterestBearingConfig>(true).unwrap();
+            }),
+        ];
+        for data in datas {
+            assert_eq!(
+                error_code(check_mint_data(&data)),
+                u32::from(TornadoError::UnsupportedMintExtension)
+            );
+        }
+    }
+
+    #[test]
+    fn test_transfer_fee_mint_rejected() {
+        let data = mint_with_extensions(&[ExtensionType::TransferFeeConfig], |state| {
+            state.init_extension::<TransferFeeConfig>(true).unwrap();
+        });
+        assert_eq!(
+            error_code(check_mint_data(&data)),
+            u32::from(TornadoError::MintHasTransferFee)
+        );
+    }
+
+    #[test]
+    fn test_default_frozen_mint_rejected() {
+        let data = mint_with_extensions(&[ExtensionType::DefaultAccountState], |state| {
+            let ext = state.init_extension::<DefaultAccountState>(true).unwrap();
+            ext.state = AccountState::Frozen as u8;
+        });
+        assert_eq!(
+            error_code(check_mint_data(&data)),
+            u32::from(TornadoError::MintDefaultFrozen)
+        );
+    }
+
+    #[test]
+    fn test_default_initialized_mint_accepted() {
+        let data = mint_with_extensions(&[ExtensionType::DefaultAccountState], |state| {
+            let ext = state.init_extension::<DefaultAccountState>(true).unwrap();
+            ext.state = AccountState::Initialized as u8;
+        });
+        assert!(check_mint_data(&data).is_ok());
+    }
+
+    #[test]
+    fn test_garbage_mint_rejected() {
+        assert!(check_mint_data(&[0u8; 10]).is_err());
+    }
+}
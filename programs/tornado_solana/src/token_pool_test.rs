@@ -0,0 +1,239 @@
+#[cfg(test)]
+mod token_pool_tests {
+    use crate::test_accounts::{
+        accounts_with_args, circuit_verifying_key_bytes, error_code, new_pool_state, take_other_cpis, TestAccount,
+    };
+    use crate::{
+        DepositToken, Nullifier, TornadoError, TornadoState, VerifyingKeyAccount, WithdrawToken, MAX_FEE_BPS,
+        MERKLE_TREE_HEIGHT, ROOT_HISTORY_SIZE,
+    };
+    use anchor_lang::prelude::*;
+    use anchor_lang::solana_program::instruction::Instruction;
+    use anchor_lang::solana_program::program_option::COption;
+    use anchor_lang::solana_program::program_pack::Pack;
+    use anchor_lang::{system_program, Discriminator, InstructionData};
+    use anchor_spl::token_2022::spl_token_2022::{
+        self,
+        instruction::TokenInstruction,
+        state::{Account as TokenAccountState, AccountState, Mint as MintState},
+    };
+
+    // Real proof from circuits/test_proof_valid.json (fee = 1_000_000, refund = 0)
+    const PROOF_HEX: &str = "1932c68d13e4e1dce10877fb867b64f4eeb14438acb7d96911c00963ae8892fb1100ad50a064e95082e8d9a4fec8729a0b5f661fd118930934e6f78a0fee3c701da6fa818ef65c4d648ae4f871929d51235c7bc5d5f9218745f5cd0bdea50ad327d5f609d882ae5bbe9872c46866b799dd134dc1734b9cfd2db98ae953975b68102a77cbe32a0714b8a82d59ecebcf6a8caf8ff445b5dca2265e7f35eeb6a8062324a790f811da839b12b02cadb62bcc7fe9e713523c4122c8591ca4cd0111a80ce792e8b41714924c86758605f6403297a9030c424f6c1dd48c0abcfa3fd9c6063e61773609fd0338923bcb58bce991192b83a6c3ab299916982e52fea008e3";
+    const ROOT_HEX: &str = "2ff370c60cf13d3fffa72d1efe3150948a8c84a664c43d427e25b59a01fe3e3c";
+    const NULLIFIER_HASH_HEX: &str = "09ca96f9b5a778899e61078e62a5edfe492398e79db303e0440ee2d6e0e4e7f2";
+    const RECIPIENT_HEX: &str = "e31d835d8657f921fdd87d952db48ec74a949b540a9151fd066c05f7d5c7edd3";
+    const RELAYER_HEX: &str = "c97dda6f4f8d671202378f3843ac899157e5461c0651a0b1cb40541e3397c151";
+    const FEE: u64 = 1_000_000;
+
+    // 10 tokens of a 6-decimal Token-2022 mint
+    const DENOMINATION: u64 = 10_000_000;
+    const DECIMALS: u8 = 6;
+
+    fn bytes32(hex_str: &str) -> [u8; 32] {
+        hex::decode(hex_str).unwrap().try_into().unwrap()
+    }
+
+    fn token_account(key: Pubkey, mint: Pubkey, owner: Pubkey) -> TestAccount {
+        let mut data = vec![0u8; TokenAccountState::LEN];
+        TokenAccountState {
+            mint,
+            owner,
+            amount: DENOMINATION,
+            state: AccountState::Initialized,
+            ..Default::default()
+        }
+        .pack_into_slice(&mut data);
+        TestAccount::new(key, spl_token_2022::ID, data)
+    }
+
+    /// Token pool whose root history holds the real proof's root, with the circuit key stored
+    struct TokenPool {
+        state: TestAccount,
+        mint: TestAccount,
+        token_vault: TestAccount,
+        verifying_key: TestAccount,
+        token_program: TestAccount,
+        system_program: TestAccount,
+    }
+
+    impl TokenPool {
+        fn new() -> Self {
+            let mint = Pubkey::new_unique();
+            let (state_key, _) = Pubkey::find_program_address(
+                &[b"tornado", mint.as_ref(), DENOMINATION.to_le_bytes().as_ref()],
+                &crate::ID,
+            );
+
+            let mut state_data = vec![0u8; TornadoState::space(ROOT_HISTORY_SIZE)];
+            state_data[..8].copy_from_slice(&TornadoState::discriminator());
+            let mut state = new_pool_state();
+            state.denomination = DENOMINATION;
+            state.mint = mint;
+            state.max_fee_bps = MAX_FEE_BPS;
+            state.max_fee_amount = u64::MAX;
+            state_data[8..8 + TornadoState::MAX_SIZE].copy_from_slice(bytemuck::bytes_of(&state));
+            TornadoState::split_mut(&mut state_data).unwrap().1[0] = bytes32(ROOT_HEX);
+
+            let mut mint_data = vec![0u8; MintState::LEN];
+            MintState {
+                mint_authority: COption::Some(Pubkey::new_unique()),
+                supply: 3 * DENOMINATION,
+                decimals: DECIMALS,
+                is_initialized: true,
+                freeze_authority: COption::None,
+            }
+            .pack_into_slice(&mut mint_data);
+
+            let (vault_key, _) = Pubkey::find_program_address(&[b"token_vault", state_key.as_ref()], &crate::ID);
+
+            let (vk_key, _) = Pubkey::find_program_address(
+                &[VerifyingKeyAccount::SEED_PREFIX, state_key.as_ref()],
+                &crate::ID,
+            );
+            let mut vk_data = vec![0u8; 8 + VerifyingKeyAccount::SIZE];
+            vk_data[..8].copy_from_slice(&VerifyingKeyAccount::discriminator());
+            let verifying_key: &mut VerifyingKeyAccount = bytemuck::from_bytes_mut(&mut vk_data[8..]);
+            verifying_key.tornado_state = state_key;
+            verifying_key.store(&circuit_verifying_key_bytes(), MERKLE_TREE_HEIGHT).unwrap();
+
+            Self {
+                state: TestAccount::new(state_key, crate::ID, state_data),
+                mint: TestAccount::new(mint, spl_token_2022::ID, mint_data),
+                token_vault: token_account(vault_key, mint, vault_key),
+                verifying_key: TestAccount::new(vk_key, crate::ID, vk_data),
+                token_program: TestAccount {
+                    executable: true,
+                    ..TestAccount::new(spl_token_2022::ID, Pubkey::default(), vec![])
+                },
+                system_program: TestAccount {
+                    executable: true,
+                    ..TestAccount::new(system_program::ID, Pubkey::default(), vec![])
+                },
+            }
+        }
+    }
+
+    /// Amount, decimals and source / destination / authority of a recorded transfer_checked
+    fn transfer_checked(instruction: &Instruction) -> (u64, u8, [Pubkey; 3]) {
+        assert_eq!(instruction.program_id, spl_token_2022::ID);
+        match TokenInstruction::unpack(&instruction.data).unwrap() {
+            TokenInstruction::TransferChecked { amount, decimals } => (
+                amount,
+                decimals,
+                [instruction.accounts[0].pubkey, instruction.accounts[2].pubkey, instruction.accounts[3].pubkey],
+            ),
+            other => panic!("unexpected token instruction: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_deposit_token_moves_denomination_into_vault() {
+        let mut pool = TokenPool::new();
+        let depositor = Pubkey::new_unique();
+        let mut depositor_token_account = token_account(Pubkey::new_unique(), pool.mint.key, depositor);
+        let mut depositor = TestAccount::signer(depositor);
+        let expected_accounts = [depositor_token_account.key, pool.token_vault.key, depositor.key];
+        let commitment = [7u8; 32];
+
+        let infos = [
+            pool.state.info(),
+            pool.mint.info(),
+            pool.token_vault.info(),
+            depositor_token_account.info(),
+            depositor.info(),
+            pool.token_program.info(),
+        ];
+        take_other_cpis();
+        let (mut accounts, bumps) = accounts_with_args::<DepositToken>(&infos, &[]).unwrap();
+        crate::tornado_solana::deposit_token(Context::new(&crate::ID, &mut accounts, &[], bumps), commitment).unwrap();
+
+        let transfers: Vec<_> = take_other_cpis().iter().map(transfer_checked).collect();
+        assert_eq!(transfers, vec![(DENOMINATION, DECIMALS, expected_accounts)]);
+        assert_eq!(accounts.tornado_state.load().unwrap().merkle_tree.next_index, 1);
+    }
+
+    /// Run withdraw_token with the real proof, paying its fee to the relayer's token account
+    fn withdraw_token(pool: &mut TokenPool, recipient_token_account_owner: Pubkey) -> Result<[Pubkey; 3]> {
+        let recipient = Pubkey::new_from_array(bytes32(RECIPIENT_HEX));
+        let relayer = Pubkey::new_from_array(bytes32(RELAYER_HEX));
+        let nullifier_hash = bytes32(NULLIFIER_HASH_HEX);
+
+        let (nullifier_key, _) = Nullifier::derive_pda(&pool.state.key, &nullifier_hash, &crate::ID);
+        let mut nullifier = TestAccount::uncreated(nullifier_key, crate::ID);
+        let mut recipient_wallet = TestAccount::new(recipient, system_program::ID, vec![]);
+        let mut recipient_token_account =
+            token_account(Pubkey::new_unique(), pool.mint.key, recipient_token_account_owner);
+        let mut relayer_token_account = token_account(Pubkey::new_unique(), pool.mint.key, relayer);
+        let mut payer = TestAccount::signer(relayer);
+        let mut relayer_record = TestAccount::none();
+        let token_accounts = [pool.token_vault.key, recipient_token_account.key, relayer_token_account.key];
+
+        let args = crate::instruction::WithdrawToken {
+            proof: hex::decode(PROOF_HEX).unwrap(),
+            root: bytes32(ROOT_HEX),
+            nullifier_hash,
+            recipient,
+            relayer: Some(relayer),
+            fee: FEE,
+            refund: 0,
+        };
+        let infos = [
+            pool.state.info(),
+            nullifier.info(),
+            pool.verifying_key.info(),
+            pool.mint.info(),
+            pool.token_vault.info(),
+            recipient_wallet.info(),
+            recipient_token_account.info(),
+            relayer_token_account.info(),
+            payer.info(),
+            pool.token_program.info(),
+            pool.system_program.info(),
+            relayer_record.info(),
+        ];
+        let (mut accounts, bumps) = accounts_with_args::<WithdrawToken>(&infos, &args.data()[8..])?;
+        crate::tornado_solana::withdraw_token(
+            Context::new(&crate::ID, &mut accounts, &[], bumps),
+            args.proof,
+            args.root,
+            args.nullifier_hash,
+            args.recipient,
+            args.relayer,
+            args.fee,
+            args.refund,
+        )?;
+        Ok(token_accounts)
+    }
+
+    #[test]
+    fn test_withdraw_token_splits_denomination_between_recipient_and_relayer() {
+        let mut pool = TokenPool::new();
+        take_other_cpis();
+        let [vault, recipient_token_account, relayer_token_account] =
+            withdraw_token(&mut pool, Pubkey::new_from_array(bytes32(RECIPIENT_HEX))).unwrap();
+
+        // The vault signs both transfers as its own authority
+        let transfers: Vec<_> = take_other_cpis().iter().map(transfer_checked).collect();
+        assert_eq!(
+            transfers,
+            vec![
+                (DENOMINATION - FEE, DECIMALS, [vault, recipient_token_account, vault]),
+                (FEE, DECIMALS, [vault, relayer_token_account, vault]),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_withdraw_token_rejects_token_account_of_another_owner() {
+        let mut pool = TokenPool::new();
+        take_other_cpis();
+
+        // The proof names the recipient wallet; its tokens must not land in someone else's account
+        assert_eq!(
+            error_code(withdraw_token(&mut pool, Pubkey::new_unique())),
+            u32::from(TornadoError::TokenAccountOwnerMismatch)
+        );
+        assert!(take_other_cpis().is_empty());
+    }
+}
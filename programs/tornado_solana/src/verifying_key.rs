@@ -1,3 +1,4 @@
+#[cfg(test)]
 use groth16_solana::groth16::Groth16Verifyingkey;
 
 // IMPORTANT: This verifying key is for testing only!
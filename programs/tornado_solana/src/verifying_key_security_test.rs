@@ -14,15 +14,14 @@
 
 use super::*;
 use crate::verifying_key::get_circuit_verifying_key;
-use hex;
 
 /// Helper function to serialize a Groth16Verifyingkey to bytes in our expected format
-/// This simulates how a verifying key would be stored in tornado_state.verifying_key
+/// This simulates how a verifying key would be passed to `initialize`
 fn serialize_verifying_key(vk: &Groth16Verifyingkey) -> Vec<u8> {
     let mut bytes = Vec::new();
     
     // Serialize nr_pubinputs as little-endian u32
-    bytes.extend_from_slice(&vk.nr_pubinputs.to_le_bytes());
+    bytes.extend_from_slice(&(vk.nr_pubinputs as u32).to_le_bytes());
     
     // Serialize curve elements
     bytes.extend_from_slice(&vk.vk_alpha_g1);
@@ -276,10 +275,11 @@ mod integration_tests {
         // Change nr_pubinputs from original to a different value
         let original_pubinputs = original_vk.nr_pubinputs;
         let modified_pubinputs = if original_pubinputs > 1 { original_pubinputs - 1 } else { original_pubinputs + 1 };
-        modified_vk_data[0..4].copy_from_slice(&modified_pubinputs.to_le_bytes());
+        modified_vk_data[0..4].copy_from_slice(&(modified_pubinputs as u32).to_le_bytes());
         
         // Both should deserialize successfully
-        let original_deserialized = deserialize_verifying_key(&serialize_verifying_key(original_vk))
+        let original_vk_data = serialize_verifying_key(original_vk);
+        let original_deserialized = deserialize_verifying_key(&original_vk_data)
             .expect("Original VK should deserialize");
         let modified_deserialized = deserialize_verifying_key(&modified_vk_data);
         
@@ -357,7 +357,7 @@ mod performance_tests {
         assert_eq!(deserialized_vk.vk_ic.len(), hardcoded_vk.vk_ic.len());
         
         // Memory usage is proportional to number of IC elements
-        let expected_ic_count = (hardcoded_vk.nr_pubinputs + 1) as usize;
+        let expected_ic_count = hardcoded_vk.nr_pubinputs + 1;
         assert_eq!(deserialized_vk.vk_ic.len(), expected_ic_count);
         
         println!("✅ Memory usage test passed - deserialized VK uses expected memory");
@@ -392,7 +392,7 @@ mod security_tests {
         }
         
         // This should still pass basic validation (it's not all zeros)
-        let result = deserialize_verifying_key(&suspicious_vk);
+        let _result = deserialize_verifying_key(&suspicious_vk);
         // Note: This might succeed because we only check for zero patterns
         // In a production system, you might add more sophisticated validation
         
@@ -404,7 +404,7 @@ mod security_tests {
     fn test_malformed_data_resilience() {
         // Test resilience against various malformed data attacks
         
-        let test_cases = vec![
+        let test_cases = [
             vec![0xFF; 1000], // All 0xFF bytes
             vec![0x00; 1000], // All zero bytes (should fail zero validation)
             {
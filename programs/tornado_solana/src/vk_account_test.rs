@@ -0,0 +1,166 @@
+#[cfg(test)]
+mod vk_account_tests {
+    use crate::test_accounts::{accounts_with_args, circuit_verifying_key_bytes, TestAccount};
+    use crate::{
+        get_circuit_verifying_key, verify_proof, Initialize, TornadoError, TornadoState, VerifyingKeyAccount,
+        MAX_FEE_BPS, MAX_MERKLE_TREE_HEIGHT, MERKLE_TREE_HEIGHT, MIN_MERKLE_TREE_HEIGHT, NR_PUBLIC_INPUTS,
+        ROOT_HISTORY_SIZE,
+    };
+    use anchor_lang::prelude::*;
+    use anchor_lang::{system_program, InstructionData};
+    use bytemuck::Zeroable;
+
+    // Real proof from circuits/test_proof_valid.json (fee = 1_000_000, refund = 0)
+    const PROOF_HEX: &str = "1932c68d13e4e1dce10877fb867b64f4eeb14438acb7d96911c00963ae8892fb1100ad50a064e95082e8d9a4fec8729a0b5f661fd118930934e6f78a0fee3c701da6fa818ef65c4d648ae4f871929d51235c7bc5d5f9218745f5cd0bdea50ad327d5f609d882ae5bbe9872c46866b799dd134dc1734b9cfd2db98ae953975b68102a77cbe32a0714b8a82d59ecebcf6a8caf8ff445b5dca2265e7f35eeb6a8062324a790f811da839b12b02cadb62bcc7fe9e713523c4122c8591ca4cd0111a80ce792e8b41714924c86758605f6403297a9030c424f6c1dd48c0abcfa3fd9c6063e61773609fd0338923bcb58bce991192b83a6c3ab299916982e52fea008e3";
+    const ROOT_HEX: &str = "2ff370c60cf13d3fffa72d1efe3150948a8c84a664c43d427e25b59a01fe3e3c";
+    const NULLIFIER_HASH_HEX: &str = "09ca96f9b5a778899e61078e62a5edfe492398e79db303e0440ee2d6e0e4e7f2";
+    const RECIPIENT_HEX: &str = "e31d835d8657f921fdd87d952db48ec74a949b540a9151fd066c05f7d5c7edd3";
+    const RELAYER_HEX: &str = "c97dda6f4f8d671202378f3843ac899157e5461c0651a0b1cb40541e3397c151";
+    const FEE: u64 = 1_000_000;
+
+    fn bytes32(hex_str: &str) -> [u8; 32] {
+        hex::decode(hex_str).unwrap().try_into().unwrap()
+    }
+
+    /// Run `initialize` for a new 1 SOL pool: account validation, then the handler
+    /// Returns the depth of the pool's tree and the depth recorded with its key
+    fn initialize(merkle_tree_height: u32) -> Result<(u32, u32)> {
+        let denomination = 1_000_000_000u64;
+        let (state_key, _) =
+            Pubkey::find_program_address(&[b"tornado", denomination.to_le_bytes().as_ref()], &crate::ID);
+        let (vk_key, _) = Pubkey::find_program_address(
+            &[VerifyingKeyAccount::SEED_PREFIX, state_key.as_ref()],
+            &crate::ID,
+        );
+        let (vault_key, _) = Pubkey::find_program_address(&[b"vault", state_key.as_ref()], &crate::ID);
+
+        let mut state = TestAccount {
+            data: vec![0u8; TornadoState::space(ROOT_HISTORY_SIZE)],
+            ..TestAccount::uncreated(state_key, crate::ID)
+        };
+        let mut verifying_key = TestAccount {
+            data: vec![0u8; 8 + VerifyingKeyAccount::SIZE],
+            ..TestAccount::uncreated(vk_key, crate::ID)
+        };
+        let mut vault = TestAccount::new(vault_key, system_program::ID, vec![]);
+        let mut authority = TestAccount::signer(Pubkey::new_unique());
+        let mut system = TestAccount {
+            executable: true,
+            ..TestAccount::new(system_program::ID, Pubkey::default(), vec![])
+        };
+
+        let args = crate::instruction::Initialize {
+            denomination,
+            verifying_key: circuit_verifying_key_bytes(),
+            merkle_tree_height,
+            root_history_size: ROOT_HISTORY_SIZE,
+            max_fee_bps: MAX_FEE_BPS,
+            max_fee_amount: u64::MAX,
+        };
+        let infos = [state.info(), verifying_key.info(), vault.info(), authority.info(), system.info()];
+        let (mut accounts, bumps) = accounts_with_args::<Initialize>(&infos, &args.data()[8..])?;
+
+        crate::tornado_solana::initialize(
+            Context::new(&crate::ID, &mut accounts, &[], bumps),
+            args.denomination,
+            args.verifying_key,
+            args.merkle_tree_height,
+            args.root_history_size,
+            args.max_fee_bps,
+            args.max_fee_amount,
+        )?;
+        // Writes the discriminators, as the generated entrypoint does after the handler returns
+        accounts.exit(&crate::ID)?;
+
+        let tree_levels = accounts.tornado_state.load()?.merkle_tree.levels;
+        let key_levels = accounts.verifying_key_account.load()?.merkle_tree_height;
+        Ok((tree_levels, key_levels))
+    }
+
+    #[test]
+    fn test_stored_key_matches_circuit_key() {
+        let mut account = VerifyingKeyAccount::zeroed();
+        account.store(&circuit_verifying_key_bytes(), MERKLE_TREE_HEIGHT).unwrap();
+
+        let stored = account.as_groth16_verifying_key();
+        let expected = get_circuit_verifying_key();
+        assert_eq!(stored.nr_pubinputs, NR_PUBLIC_INPUTS as usize);
+        assert_eq!(stored.vk_alpha_g1, expected.vk_alpha_g1);
+        assert_eq!(stored.vk_beta_g2, expected.vk_beta_g2);
+        assert_eq!(stored.vk_gamme_g2, expected.vk_gamme_g2);
+        assert_eq!(stored.vk_delta_g2, expected.vk_delta_g2);
+        assert_eq!(stored.vk_ic, expected.vk_ic);
+    }
+
+    #[test]
+    fn test_real_proof_verifies_against_stored_key() {
+        let mut account = VerifyingKeyAccount::zeroed();
+        account.store(&circuit_verifying_key_bytes(), MERKLE_TREE_HEIGHT).unwrap();
+
+        let result = verify_proof(
+            &hex::decode(PROOF_HEX).unwrap(),
+            &bytes32(ROOT_HEX),
+            &bytes32(NULLIFIER_HASH_HEX),
+            &Pubkey::new_from_array(bytes32(RECIPIENT_HEX)),
+            &Pubkey::new_from_array(bytes32(RELAYER_HEX)),
+            FEE,
+            0,
+            &account.as_groth16_verifying_key(),
+        );
+        assert!(result.is_ok());
+    }
+
+    #[test]
+    fn test_invalid_key_leaves_account_untouched() {
+        let mut account = VerifyingKeyAccount::zeroed();
+        account.store(&circuit_verifying_key_bytes(), MERKLE_TREE_HEIGHT).unwrap();
+        let before = account;
+
+        let mut corrupted = circuit_verifying_key_bytes();
+        corrupted[4 + 63] ^= 1; // alpha_g1 no longer on the curve
+        match account.store(&corrupted, MERKLE_TREE_HEIGHT).unwrap_err() {
+            Error::AnchorError(e) => assert_eq!(
+                e.error_code_number,
+                u32::from(TornadoError::InvalidVerifyingKey)
+            ),
+            other => panic!("unexpected error: {:?}", other),
+        }
+
+        assert_eq!(account.vk_alpha_g1, before.vk_alpha_g1);
+        assert_eq!(account.vk_ic, before.vk_ic);
+    }
+
+    #[test]
+    fn test_key_records_circuit_tree_height() {
+        let mut account = VerifyingKeyAccount::zeroed();
+        account.store(&circuit_verifying_key_bytes(), MERKLE_TREE_HEIGHT).unwrap();
+        assert_eq!(account.merkle_tree_height, MERKLE_TREE_HEIGHT);
+
+        for height in [MIN_MERKLE_TREE_HEIGHT - 1, MAX_MERKLE_TREE_HEIGHT + 1] {
+            match account.store(&circuit_verifying_key_bytes(), height).unwrap_err() {
+                Error::AnchorError(e) => assert_eq!(
+                    e.error_code_number,
+                    u32::from(TornadoError::InvalidMerkleTreeHeight)
+                ),
+                other => panic!("unexpected error: {:?}", other),
+            }
+        }
+        assert_eq!(account.merkle_tree_height, MERKLE_TREE_HEIGHT);
+    }
+
+    #[test]
+    fn test_initialize_records_pool_height_with_key() {
+        // The key carries no depth of its own: it is recorded as the pool's height
+        for height in [MIN_MERKLE_TREE_HEIGHT, MERKLE_TREE_HEIGHT, MAX_MERKLE_TREE_HEIGHT] {
+            assert_eq!(initialize(height).unwrap(), (height, height));
+        }
+
+        match initialize(MAX_MERKLE_TREE_HEIGHT + 1).unwrap_err() {
+            Error::AnchorError(e) => assert_eq!(
+                e.error_code_number,
+                u32::from(TornadoError::InvalidMerkleTreeHeight)
+            ),
+            other => panic!("unexpected error: {:?}", other),
+        }
+    }
+}
@@ -0,0 +1,85 @@
+#[cfg(test)]
+mod vk_rotation_tests {
+    use crate::{
+        change_endianness, check_timelock_elapsed, get_circuit_verifying_key,
+        validate_verifying_key, PendingVerifyingKey, TornadoError, VerifyingKeyAccount,
+        MAX_VERIFYING_KEY_SIZE, MERKLE_TREE_HEIGHT, VK_ROTATION_DELAY,
+    };
+    use crate::test_accounts::{circuit_verifying_key_bytes, error_code};
+    use bytemuck::Zeroable;
+    use anchor_lang::prelude::*;
+    use ark_bn254::G1Affine;
+    use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
+
+    #[test]
+    fn test_timelock_blocks_early_apply() {
+        let proposed_at = 1_700_000_000;
+        let activation_time = proposed_at + VK_ROTATION_DELAY;
+
+        assert_eq!(
+            error_code(check_timelock_elapsed(activation_time, proposed_at)),
+            u32::from(TornadoError::VkTimelockNotElapsed)
+        );
+        assert_eq!(
+            error_code(check_timelock_elapsed(activation_time, activation_time - 1)),
+            u32::from(TornadoError::VkTimelockNotElapsed)
+        );
+    }
+
+    #[test]
+    fn test_timelock_allows_apply_once_elapsed() {
+        let activation_time = 1_700_000_000 + VK_ROTATION_DELAY;
+
+        assert!(check_timelock_elapsed(activation_time, activation_time).is_ok());
+        assert!(check_timelock_elapsed(activation_time, activation_time + 1).is_ok());
+    }
+
+    #[test]
+    fn test_rotation_delay_is_meaningful() {
+        // Users need time to exit before a new key takes effect
+        const _: () = assert!(VK_ROTATION_DELAY >= 24 * 60 * 60);
+    }
+
+    #[test]
+    fn test_proposed_key_must_validate() {
+        let vk_bytes = circuit_verifying_key_bytes();
+        assert!(vk_bytes.len() <= MAX_VERIFYING_KEY_SIZE);
+        assert!(validate_verifying_key(&vk_bytes).is_ok());
+
+        // A truncated key is rejected before it can be stored as a proposal
+        assert!(validate_verifying_key(&vk_bytes[..vk_bytes.len() / 2]).is_err());
+    }
+
+    #[test]
+    fn test_pending_account_fits_max_key() {
+        let pending = PendingVerifyingKey {
+            tornado_state: Pubkey::new_unique(),
+            verifying_key: vec![1u8; MAX_VERIFYING_KEY_SIZE],
+            merkle_tree_height: MERKLE_TREE_HEIGHT,
+            activation_time: VK_ROTATION_DELAY,
+        };
+
+        let serialized = pending.try_to_vec().unwrap();
+        assert!(serialized.len() <= PendingVerifyingKey::MAX_SIZE);
+    }
+
+    #[test]
+    fn test_applied_key_replaces_stored_key() {
+        let mut account = VerifyingKeyAccount::zeroed();
+        account.store(&circuit_verifying_key_bytes(), MERKLE_TREE_HEIGHT).unwrap();
+
+        // A proposal for a different (but valid) key: same circuit, alpha negated
+        let mut rotated = circuit_verifying_key_bytes();
+        let alpha = G1Affine::deserialize_uncompressed(
+            &*[change_endianness(&rotated[4..68]), vec![0u8]].concat(),
+        )
+        .unwrap();
+        let mut negated = [0u8; 65];
+        (-alpha).serialize_uncompressed(&mut negated[..]).unwrap();
+        rotated[4..68].copy_from_slice(&change_endianness(&negated[..64]));
+
+        account.store(&rotated, MERKLE_TREE_HEIGHT).unwrap();
+        assert_eq!(account.vk_alpha_g1[..], rotated[4..68]);
+        assert_ne!(account.vk_alpha_g1, get_circuit_verifying_key().vk_alpha_g1);
+    }
+}
@@ -0,0 +1,115 @@
+#[cfg(test)]
+mod vk_validation_tests {
+    use crate::{
+        validate_verifying_key, TornadoError, MAX_VERIFYING_KEY_SIZE, NR_PUBLIC_INPUTS, VERIFYING_KEY_SIZE,
+    };
+    use crate::test_accounts::{circuit_verifying_key_bytes, error_code};
+    use ark_bn254::{Fq, Fq2, G2Affine};
+    use ark_serialize::CanonicalSerialize;
+
+    // Offsets into the on-chain layout
+    const ALPHA_OFFSET: usize = 4;
+    const BETA_OFFSET: usize = ALPHA_OFFSET + 64;
+    const DELTA_OFFSET: usize = BETA_OFFSET + 2 * 128;
+    const IC_OFFSET: usize = DELTA_OFFSET + 128;
+
+    fn assert_rejected(vk_bytes: &[u8]) {
+        assert_eq!(
+            error_code(validate_verifying_key(vk_bytes)),
+            u32::from(TornadoError::InvalidVerifyingKey)
+        );
+    }
+
+    /// A point on the G2 twist that lies outside the prime-order subgroup,
+    /// encoded big-endian as (x.c1, x.c0, y.c1, y.c0)
+    fn g2_point_outside_subgroup() -> [u8; 128] {
+        let point = (1u64..)
+            .filter_map(|i| G2Affine::get_point_from_x_unchecked(Fq2::new(Fq::from(i), Fq::from(0u64)), true))
+            .find(|p| p.is_on_curve() && !p.is_in_correct_subgroup_assuming_on_curve())
+            .unwrap();
+
+        let mut point_le = Vec::new();
+        point.serialize_uncompressed(&mut point_le).unwrap();
+        let point_be: Vec<u8> = point_le
+            .chunks(64)
+            .flat_map(|coordinate| coordinate.iter().rev().copied())
+            .collect();
+        point_be.try_into().unwrap()
+    }
+
+    #[test]
+    fn test_circuit_verifying_key_accepted() {
+        let vk_bytes = circuit_verifying_key_bytes();
+        assert_eq!(vk_bytes.len(), VERIFYING_KEY_SIZE);
+        assert!(validate_verifying_key(&vk_bytes).is_ok());
+    }
+
+    #[test]
+    fn test_snarkjs_g2_ordering_rejected() {
+        // snarkjs lists G2 coordinates as (c0, c1); groth16-solana expects (c1, c0).
+        // A key copied through in snarkjs order deserializes fine but can never verify a proof.
+        let mut vk_bytes = circuit_verifying_key_bytes();
+        for g2 in vk_bytes[BETA_OFFSET..IC_OFFSET].chunks_mut(128) {
+            for coordinate in g2.chunks_mut(64) {
+                let (c1, c0) = coordinate.split_at_mut(32);
+                c1.swap_with_slice(c0);
+            }
+        }
+
+        assert_eq!(vk_bytes.len(), VERIFYING_KEY_SIZE);
+        assert!(crate::deserialize_verifying_key(&vk_bytes).is_ok());
+        assert_rejected(&vk_bytes);
+    }
+
+    #[test]
+    fn test_wrong_public_input_count_rejected() {
+        // A well-formed key for a 7-input circuit still can't verify withdrawals
+        let mut vk_bytes = circuit_verifying_key_bytes();
+        vk_bytes[..4].copy_from_slice(&7u32.to_le_bytes());
+        vk_bytes.truncate(vk_bytes.len() - 64);
+        assert_rejected(&vk_bytes);
+    }
+
+    #[test]
+    fn test_length_must_match_exactly() {
+        let vk_bytes = circuit_verifying_key_bytes();
+
+        assert_rejected(&vk_bytes[..vk_bytes.len() - 1]);
+        assert_rejected(&[vk_bytes.clone(), vec![0u8; 64]].concat());
+        assert_rejected(&[vk_bytes, vec![0u8; MAX_VERIFYING_KEY_SIZE]].concat());
+    }
+
+    #[test]
+    fn test_off_curve_g1_rejected() {
+        let mut vk_bytes = circuit_verifying_key_bytes();
+        vk_bytes[ALPHA_OFFSET + 63] ^= 1;
+        assert_rejected(&vk_bytes);
+
+        // Corrupting the last IC element must be caught as well
+        let mut vk_bytes = circuit_verifying_key_bytes();
+        let last_ic = IC_OFFSET + NR_PUBLIC_INPUTS as usize * 64;
+        vk_bytes[last_ic + 63] ^= 1;
+        assert_rejected(&vk_bytes);
+    }
+
+    #[test]
+    fn test_off_curve_g2_rejected() {
+        let mut vk_bytes = circuit_verifying_key_bytes();
+        vk_bytes[DELTA_OFFSET + 127] ^= 1;
+        assert_rejected(&vk_bytes);
+    }
+
+    #[test]
+    fn test_g2_outside_subgroup_rejected() {
+        let mut vk_bytes = circuit_verifying_key_bytes();
+        vk_bytes[BETA_OFFSET..BETA_OFFSET + 128].copy_from_slice(&g2_point_outside_subgroup());
+        assert_rejected(&vk_bytes);
+    }
+
+    #[test]
+    fn test_identity_points_rejected() {
+        let mut vk_bytes = circuit_verifying_key_bytes();
+        vk_bytes[IC_OFFSET..IC_OFFSET + 64].fill(0);
+        assert_rejected(&vk_bytes);
+    }
+}
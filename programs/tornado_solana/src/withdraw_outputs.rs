@@ -0,0 +1,78 @@
+//! Multi-output withdrawals
+//!
+//! `withdraw_multi` splits one note between several accounts, e.g. a recipient and a
+//! fee-payer top-up. It reuses the withdraw circuit: the proof binds the Poseidon hash of
+//! the outputs list in the `refund` public input and the zero address in the recipient
+//! inputs. The circuit leaves `refund` unconstrained beyond binding it, and a plain
+//! withdrawal can only put a u64 there, so outputs hashes are kept at or above 2^64 and a
+//! proof made for one kind of withdrawal can never be replayed as the other.
+//!
+//! outputsHash = h_n where h_0 = 0 and h_i = Poseidon(h_{i-1}, recipientHigh_i, recipientLow_i, amount_i)
+
+use anchor_lang::prelude::*;
+use ark_bn254::Fr;
+use light_poseidon::{Poseidon, PoseidonBytesHasher};
+
+use crate::{encode_u64_as_32_bytes, split_address_to_high_low, TornadoError};
+
+// Most accounts a single withdrawal can pay, besides the relayer
+pub const MAX_WITHDRAW_OUTPUTS: usize = 4;
+
+/// One payment of a multi-output withdrawal
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct WithdrawOutput {
+    pub recipient: Pubkey,
+    pub amount: u64,
+}
+
+/// Hash bound by the proof in place of `refund`; see the module docs for the layout
+pub fn outputs_hash(outputs: &[WithdrawOutput]) -> Result<[u8; 32]> {
+    let mut hasher = Poseidon::<Fr>::new_circom(4).map_err(|e| {
+        msg!("Poseidon initialization failed: {}", e);
+        TornadoError::HashFailure
+    })?;
+
+    let mut hash = [0u8; 32];
+    for output in outputs {
+        let (recipient_high, recipient_low) = split_address_to_high_low(&output.recipient);
+        let mut amount = [0u8; 32];
+        encode_u64_as_32_bytes(output.amount, &mut amount);
+
+        hash = hasher
+            .hash_bytes_be(&[&hash, &recipient_high, &recipient_low, &amount])
+            .map_err(|e| {
+                msg!("Poseidon hash failed: {}", e);
+                TornadoError::HashFailure
+            })?;
+    }
+    Ok(hash)
+}
+
+/// Outputs must be non-empty, bounded, pay out exactly `denomination - fee`, and hash
+/// to a value a plain withdrawal's u64 refund cannot take
+pub fn validate_outputs(
+    outputs: &[WithdrawOutput],
+    denomination: u64,
+    fee: u64,
+    outputs_hash: &[u8; 32],
+) -> Result<()> {
+    require!(
+        !outputs.is_empty() && outputs.len() <= MAX_WITHDRAW_OUTPUTS,
+        TornadoError::InvalidOutputCount
+    );
+
+    let total = outputs
+        .iter()
+        .try_fold(0u64, |total, output| total.checked_add(output.amount))
+        .ok_or(TornadoError::OutputsAmountMismatch)?;
+    require!(
+        Some(total) == denomination.checked_sub(fee),
+        TornadoError::OutputsAmountMismatch
+    );
+
+    require!(
+        outputs_hash[..24].iter().any(|&byte| byte != 0),
+        TornadoError::OutputsHashCollidesWithRefund
+    );
+    Ok(())
+}
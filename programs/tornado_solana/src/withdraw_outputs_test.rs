@@ -0,0 +1,75 @@
+#[cfg(test)]
+mod withdraw_outputs_tests {
+    use crate::test_accounts::error_code;
+    use crate::withdraw_outputs::{outputs_hash, validate_outputs, WithdrawOutput, MAX_WITHDRAW_OUTPUTS};
+    use crate::{encode_u64_as_32_bytes, split_address_to_high_low, TornadoError};
+    use anchor_lang::prelude::*;
+    use ark_bn254::Fr;
+    use light_poseidon::{Poseidon, PoseidonBytesHasher};
+
+    const DENOMINATION: u64 = 1_000_000_000;
+    const FEE: u64 = 10_000_000;
+
+    fn output(amount: u64) -> WithdrawOutput {
+        WithdrawOutput { recipient: Pubkey::new_unique(), amount }
+    }
+
+    fn validate(outputs: &[WithdrawOutput], fee: u64) -> Result<()> {
+        validate_outputs(outputs, DENOMINATION, fee, &outputs_hash(outputs).unwrap())
+    }
+
+    #[test]
+    fn test_outputs_hash_chains_poseidon() {
+        let outputs = [output(1), output(2)];
+
+        let mut hasher = Poseidon::<Fr>::new_circom(4).unwrap();
+        let mut expected = [0u8; 32];
+        for output in &outputs {
+            let (high, low) = split_address_to_high_low(&output.recipient);
+            let mut amount = [0u8; 32];
+            encode_u64_as_32_bytes(output.amount, &mut amount);
+            expected = hasher.hash_bytes_be(&[&expected, &high, &low, &amount]).unwrap();
+        }
+
+        assert_eq!(outputs_hash(&outputs).unwrap(), expected);
+        assert_ne!(outputs_hash(&outputs[..1]).unwrap(), expected);
+    }
+
+    #[test]
+    fn test_outputs_must_pay_out_exactly() {
+        assert!(validate(&[output(DENOMINATION - FEE)], FEE).is_ok());
+        assert!(validate(&[output(DENOMINATION - FEE - 1), output(1)], FEE).is_ok());
+        assert!(validate(&[output(DENOMINATION), output(0)], 0).is_ok());
+
+        let mismatch = u32::from(TornadoError::OutputsAmountMismatch);
+        assert_eq!(error_code(validate(&[output(DENOMINATION - FEE - 1)], FEE)), mismatch);
+        assert_eq!(error_code(validate(&[output(DENOMINATION - FEE + 1)], FEE)), mismatch);
+        assert_eq!(error_code(validate(&[output(u64::MAX), output(2)], FEE)), mismatch);
+        assert_eq!(error_code(validate(&[output(0)], DENOMINATION + 1)), mismatch);
+    }
+
+    #[test]
+    fn test_output_count_bounds() {
+        let invalid_count = u32::from(TornadoError::InvalidOutputCount);
+        assert_eq!(error_code(validate(&[], DENOMINATION)), invalid_count);
+
+        let mut outputs = vec![output(0); MAX_WITHDRAW_OUTPUTS];
+        outputs[0].amount = DENOMINATION;
+        assert!(validate(&outputs, 0).is_ok());
+
+        outputs.push(output(0));
+        assert_eq!(error_code(validate(&outputs, 0)), invalid_count);
+    }
+
+    #[test]
+    fn test_hash_in_refund_range_rejected() {
+        let outputs = [output(DENOMINATION)];
+        let mut small = [0u8; 32];
+        encode_u64_as_32_bytes(u64::MAX, &mut small);
+
+        assert_eq!(
+            error_code(validate_outputs(&outputs, DENOMINATION, 0, &small)),
+            u32::from(TornadoError::OutputsHashCollidesWithRefund)
+        );
+    }
+}
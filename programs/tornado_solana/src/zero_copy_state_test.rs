@@ -0,0 +1,58 @@
+#[cfg(test)]
+mod zero_copy_state_tests {
+    use crate::{insert_commitment, is_known_root, MerkleTree, TornadoState, ROOT_HISTORY_SIZE};
+    use anchor_lang::Discriminator;
+
+    /// Raw account data as the runtime hands it to `AccountLoader`: discriminator + state + roots
+    fn new_account_data() -> Vec<u8> {
+        let mut data = vec![0u8; TornadoState::space(ROOT_HISTORY_SIZE)];
+        data[..8].copy_from_slice(&TornadoState::discriminator());
+
+        let state: &mut TornadoState = bytemuck::from_bytes_mut(&mut data[8..8 + TornadoState::MAX_SIZE]);
+        state.denomination = 1_000_000_000;
+        state.merkle_tree = MerkleTree::new();
+        state.root_history_size = ROOT_HISTORY_SIZE;
+        data
+    }
+
+    #[test]
+    fn test_deposit_updates_account_data_in_place() {
+        let mut data = new_account_data();
+        let empty_root = MerkleTree::new().get_root();
+
+        let leaf_index = {
+            let (state, roots) = TornadoState::split_mut(&mut data).unwrap();
+            insert_commitment(state, roots, [42u8; 32]).unwrap()
+        };
+        assert_eq!(leaf_index, 0);
+
+        // Re-borrowing the same bytes sees the insertion without any decode step
+        let (state, roots) = TornadoState::split(&data).unwrap();
+        let new_root = state.merkle_tree.get_root();
+        assert_ne!(new_root, empty_root);
+        assert_eq!(state.merkle_tree.next_index, 1);
+        assert_eq!(state.current_root_index, 1);
+        assert!(is_known_root(roots, state.current_root_index, &new_root));
+        assert_eq!(state.denomination, 1_000_000_000);
+    }
+
+    #[test]
+    fn test_state_layout_matches_reserved_space() {
+        let data = new_account_data();
+        let (state, roots) = TornadoState::split(&data).unwrap();
+
+        assert_eq!(std::mem::size_of::<TornadoState>(), TornadoState::MAX_SIZE);
+        assert_eq!(roots.len(), ROOT_HISTORY_SIZE as usize);
+        assert_eq!(state.merkle_tree.levels, 20);
+        assert_eq!(state.vk_rotation_renounced, 0);
+    }
+
+    #[test]
+    fn test_truncated_account_rejected() {
+        let mut data = new_account_data();
+        data.truncate(data.len() - 32);
+
+        assert!(TornadoState::split(&data).is_err());
+        assert!(TornadoState::split_mut(&mut data[..8 + TornadoState::MAX_SIZE - 1]).is_err());
+    }
+}
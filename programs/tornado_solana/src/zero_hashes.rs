@@ -0,0 +1,142 @@
+use crate::MAX_MERKLE_TREE_HEIGHT;
+
+/// Precomputed circomlib zero chain for every supported tree depth
+///
+/// ZERO_HASHES[0] = Poseidon(0)
+/// ZERO_HASHES[i] = Poseidon(ZERO_HASHES[i-1], ZERO_HASHES[i-1])
+///
+/// ZERO_HASHES[i] is the root of an empty subtree of height i + 1, so a tree of
+/// any depth up to MAX_MERKLE_TREE_HEIGHT reads its first `levels` entries from here
+/// instead of hashing them at `initialize` or keeping a copy in the pool account.
+/// Must stay identical to `MerkleTree::generate_zeros(MAX_MERKLE_TREE_HEIGHT)`,
+/// which `merkle_tree::tests::test_zero_hashes_match_generate_zeros` checks.
+pub const ZERO_HASHES: [[u8; 32]; MAX_MERKLE_TREE_HEIGHT as usize] = [
+    [
+        0x2a, 0x09, 0xa9, 0xfd, 0x93, 0xc5, 0x90, 0xc2, 0x6b, 0x91, 0xef, 0xfb, 0xb2, 0x49, 0x9f, 0x07,
+        0xe8, 0xf7, 0xaa, 0x12, 0xe2, 0xb4, 0x94, 0x0a, 0x3a, 0xed, 0x24, 0x11, 0xcb, 0x65, 0xe1, 0x1c,
+    ],
+    [
+        0x17, 0x19, 0x2e, 0x62, 0xa1, 0x57, 0x55, 0x68, 0x49, 0xd9, 0x3b, 0x3c, 0x6b, 0xe1, 0xe2, 0xbd,
+        0x1f, 0x3f, 0x16, 0x60, 0xd1, 0x0d, 0xd9, 0xb1, 0xff, 0xc4, 0x29, 0xaa, 0x90, 0x21, 0x25, 0x2c,
+    ],
+    [
+        0x04, 0xd5, 0xab, 0xb4, 0xc7, 0xf7, 0x7e, 0x3b, 0x5d, 0x8b, 0xc7, 0xa0, 0x49, 0xd5, 0xba, 0x6e,
+        0x79, 0xf2, 0x9c, 0x5c, 0x5a, 0x9e, 0xdf, 0x0a, 0x58, 0x72, 0x6e, 0x65, 0x3e, 0x8b, 0xc0, 0xc7,
+    ],
+    [
+        0x0e, 0xa5, 0x59, 0xa9, 0x0b, 0xea, 0xc7, 0xd4, 0x8c, 0xc7, 0x0d, 0xfa, 0xd2, 0xfe, 0xa2, 0x76,
+        0x21, 0xb7, 0x6f, 0x14, 0x04, 0x46, 0x32, 0x9b, 0x29, 0x3a, 0x04, 0x45, 0x4c, 0xcb, 0x0e, 0xc3,
+    ],
+    [
+        0x26, 0xf5, 0x2f, 0x9b, 0x31, 0xef, 0x80, 0x78, 0x27, 0x98, 0xf2, 0xae, 0x44, 0x65, 0x9d, 0xc1,
+        0xbe, 0xdf, 0x53, 0xac, 0x38, 0x36, 0x6d, 0x4d, 0xfe, 0xd7, 0x4c, 0xe7, 0xd9, 0x5a, 0xd1, 0xd5,
+    ],
+    [
+        0x2f, 0xa2, 0x7c, 0x5c, 0xf0, 0x18, 0x56, 0x54, 0xd6, 0xdc, 0xf1, 0x0d, 0xf1, 0xb3, 0x82, 0x32,
+        0x4a, 0xbd, 0xf6, 0x2d, 0x73, 0xd3, 0x95, 0xbe, 0x1c, 0xc9, 0x35, 0xab, 0x47, 0x03, 0x54, 0xf0,
+    ],
+    [
+        0x01, 0xc0, 0x8b, 0x39, 0x62, 0x1c, 0x26, 0x23, 0x50, 0xbc, 0x2d, 0xdc, 0xa3, 0x69, 0xa9, 0x68,
+        0xa6, 0x87, 0x50, 0xda, 0xcb, 0x26, 0x9e, 0x7a, 0xa9, 0x91, 0x52, 0x45, 0xeb, 0x0e, 0xc3, 0xf1,
+    ],
+    [
+        0x2a, 0x39, 0xb3, 0xa3, 0x55, 0xf8, 0x05, 0x0d, 0xb5, 0x18, 0x18, 0x06, 0x4c, 0xf8, 0xca, 0xa6,
+        0xf1, 0x71, 0x48, 0x53, 0x5e, 0xdf, 0xf5, 0x09, 0x86, 0x25, 0xbc, 0x53, 0x9f, 0xd4, 0xc0, 0x38,
+    ],
+    [
+        0x02, 0xf8, 0x47, 0x4b, 0x5f, 0xdf, 0x6c, 0xfc, 0xdb, 0x20, 0x6e, 0x08, 0xca, 0x30, 0xa6, 0x9d,
+        0x65, 0x9f, 0xf1, 0xaa, 0x27, 0x4f, 0x19, 0x51, 0xb9, 0xa2, 0x40, 0xa4, 0x15, 0x04, 0xa8, 0x97,
+    ],
+    [
+        0x25, 0x5c, 0x85, 0x88, 0xa2, 0x60, 0x94, 0x72, 0xe1, 0x54, 0x7d, 0x54, 0x07, 0xc2, 0x5f, 0x8f,
+        0x33, 0x91, 0x70, 0x34, 0x30, 0x2b, 0x40, 0x76, 0xd7, 0x8c, 0xf0, 0x7f, 0x60, 0xd6, 0x95, 0x46,
+    ],
+    [
+        0x0b, 0x01, 0xab, 0x30, 0x90, 0xcb, 0xdc, 0x90, 0x0f, 0xab, 0x5c, 0x56, 0x94, 0x5a, 0xe0, 0x60,
+        0xc3, 0xc4, 0x34, 0x71, 0xa6, 0xc4, 0x21, 0x23, 0x5e, 0x5c, 0x9f, 0xb7, 0xd9, 0xd0, 0x83, 0x82,
+    ],
+    [
+        0x15, 0x95, 0x09, 0x47, 0xde, 0xae, 0x80, 0x04, 0x6b, 0x47, 0xad, 0x93, 0x6c, 0x2b, 0xe2, 0xf9,
+        0xa5, 0x94, 0xf9, 0x0c, 0x28, 0x64, 0x5a, 0x61, 0xbd, 0x41, 0x8a, 0x5b, 0xd1, 0x45, 0x97, 0x8d,
+    ],
+    [
+        0x1d, 0xf9, 0xf6, 0x8e, 0xf2, 0x45, 0xa8, 0x6b, 0x3e, 0x8c, 0x13, 0xa0, 0xfb, 0xfc, 0xc4, 0xb5,
+        0x9a, 0x1f, 0x26, 0x4d, 0x88, 0xf9, 0x95, 0x8b, 0xc9, 0x76, 0x06, 0x9b, 0x2d, 0xef, 0x72, 0xad,
+    ],
+    [
+        0x21, 0x5e, 0x5f, 0x11, 0xc3, 0xf9, 0x14, 0xdb, 0xa3, 0xad, 0xd7, 0x30, 0x3a, 0x38, 0x9a, 0xaa,
+        0x6a, 0x98, 0x94, 0xc9, 0xbf, 0x42, 0x7c, 0x71, 0xdc, 0xdf, 0x08, 0x22, 0x49, 0x80, 0x53, 0x11,
+    ],
+    [
+        0x12, 0xdf, 0x9d, 0x7e, 0xb4, 0x3f, 0xe6, 0x6c, 0x3d, 0x91, 0x69, 0x02, 0x1a, 0x80, 0x93, 0x9d,
+        0x04, 0xe9, 0xa3, 0xc3, 0xd5, 0x14, 0xee, 0xf6, 0xa2, 0x69, 0xa1, 0xa6, 0x88, 0x57, 0xd8, 0xcd,
+    ],
+    [
+        0x27, 0x33, 0xef, 0x21, 0xe2, 0xd2, 0x90, 0xbd, 0xea, 0xdf, 0x2f, 0x63, 0x13, 0x99, 0xf9, 0x0c,
+        0x04, 0x21, 0x7e, 0x95, 0x00, 0x09, 0xf2, 0xa3, 0xfa, 0xe9, 0xf4, 0x45, 0x93, 0x47, 0x92, 0xaf,
+    ],
+    [
+        0x1b, 0x5d, 0xe3, 0xd4, 0xaa, 0x8b, 0x60, 0x17, 0x5a, 0x79, 0x85, 0xcc, 0x5a, 0x92, 0x9c, 0xe2,
+        0x94, 0x15, 0x4e, 0xa3, 0x5f, 0x85, 0x4e, 0xb5, 0xcd, 0xf3, 0xf9, 0xe7, 0xf1, 0x56, 0x61, 0xe2,
+    ],
+    [
+        0x22, 0xd0, 0x21, 0x4e, 0xc4, 0x26, 0x23, 0xdf, 0x8d, 0x4d, 0x65, 0xe3, 0xc6, 0x7a, 0x0a, 0x08,
+        0xfe, 0x9c, 0x51, 0x32, 0x5f, 0xe5, 0x5b, 0x33, 0x76, 0xf6, 0x57, 0x5a, 0x51, 0x97, 0xaf, 0x19,
+    ],
+    [
+        0x1c, 0x5f, 0x64, 0x9d, 0xea, 0x85, 0xdf, 0x27, 0x6a, 0x31, 0x2d, 0x15, 0x16, 0xd9, 0x53, 0xb4,
+        0x90, 0x9d, 0xad, 0x74, 0x2b, 0x3b, 0x31, 0x2b, 0xa4, 0x60, 0xd0, 0x20, 0x0a, 0x61, 0xd1, 0x58,
+    ],
+    [
+        0x2b, 0x80, 0x17, 0x3d, 0xe4, 0x3b, 0x19, 0x7a, 0x0b, 0xda, 0xda, 0x09, 0xd8, 0xd4, 0x9c, 0x79,
+        0xc1, 0x10, 0xb1, 0xdb, 0x98, 0xdd, 0x14, 0xef, 0x96, 0xf9, 0x43, 0x2f, 0xc7, 0x46, 0x20, 0xad,
+    ],
+    [
+        0x2d, 0x3c, 0x07, 0xbe, 0xa6, 0x88, 0x34, 0x28, 0xed, 0xd2, 0xd8, 0x0d, 0x07, 0xce, 0xc4, 0xb9,
+        0x11, 0x30, 0x9f, 0xed, 0x96, 0x74, 0x38, 0x22, 0xd6, 0xaa, 0xde, 0xa0, 0x63, 0x13, 0xa9, 0x51,
+    ],
+    [
+        0x04, 0x4b, 0x60, 0x5a, 0xcb, 0x7c, 0x3b, 0xca, 0x11, 0xcc, 0x99, 0x2e, 0xd3, 0x0d, 0xf8, 0xdd,
+        0x16, 0x3e, 0xaa, 0x4c, 0xe5, 0xcc, 0xb6, 0x67, 0x3c, 0x55, 0xa2, 0xf5, 0xc3, 0x7d, 0x8e, 0x33,
+    ],
+    [
+        0x08, 0x22, 0xdf, 0xe9, 0x0c, 0x9a, 0x51, 0x97, 0x84, 0x00, 0x67, 0x48, 0x82, 0xda, 0x60, 0x59,
+        0x5f, 0xe6, 0x6e, 0x40, 0xe6, 0xe0, 0x65, 0xde, 0xcf, 0x65, 0x92, 0x6c, 0x11, 0xdb, 0xdb, 0xc5,
+    ],
+    [
+        0x26, 0x5e, 0xff, 0x44, 0x00, 0xe9, 0xbf, 0xda, 0xe0, 0x62, 0xc4, 0x39, 0x1a, 0xe6, 0x4c, 0xcc,
+        0x0d, 0x21, 0x8f, 0x9c, 0x81, 0xc9, 0x86, 0x15, 0x69, 0x1a, 0x31, 0xb5, 0xcf, 0xee, 0x38, 0xd7,
+    ],
+    [
+        0x11, 0x46, 0xce, 0xd2, 0x74, 0xf2, 0x51, 0xbb, 0x95, 0x5a, 0x51, 0x50, 0x33, 0xce, 0x70, 0x39,
+        0xc1, 0x93, 0x5f, 0x9a, 0xd4, 0xd3, 0x20, 0xf5, 0x77, 0xfd, 0x1a, 0x63, 0xd0, 0x03, 0xb3, 0x5f,
+    ],
+    [
+        0x2d, 0x53, 0x1e, 0xfb, 0x23, 0x44, 0xc3, 0xa3, 0x04, 0x1c, 0x90, 0x14, 0x51, 0x32, 0x2d, 0xd8,
+        0x2e, 0x46, 0x5e, 0xfc, 0x2c, 0x2a, 0x9c, 0xec, 0x7b, 0x60, 0xe3, 0x5b, 0x49, 0x71, 0x37, 0x46,
+    ],
+    [
+        0x1c, 0x9d, 0xc4, 0xd7, 0x5d, 0x7c, 0xcb, 0x25, 0xde, 0x67, 0x38, 0x79, 0x68, 0xdb, 0x68, 0xe5,
+        0x81, 0x10, 0xd7, 0xb1, 0x19, 0x15, 0xbd, 0x8f, 0x2c, 0xe8, 0x63, 0xef, 0x7a, 0xce, 0xe1, 0xda,
+    ],
+    [
+        0x14, 0xeb, 0xf8, 0xfc, 0xe4, 0x58, 0x88, 0xef, 0x14, 0xe6, 0xba, 0xfa, 0x70, 0xf4, 0x63, 0x57,
+        0x32, 0x5c, 0xbb, 0x29, 0xc3, 0x0d, 0xdb, 0x67, 0x86, 0x9f, 0x2c, 0xb9, 0x14, 0xa7, 0xa3, 0x92,
+    ],
+    [
+        0x24, 0x8d, 0x0b, 0xf1, 0x88, 0x57, 0x46, 0x49, 0xb1, 0x04, 0x75, 0x3c, 0x86, 0xf2, 0x38, 0xbd,
+        0x04, 0x6e, 0x87, 0xa1, 0x3a, 0xe2, 0x2a, 0x0d, 0xf5, 0x60, 0x99, 0x53, 0x4f, 0x22, 0xba, 0x02,
+    ],
+    [
+        0x0f, 0x36, 0xd1, 0x66, 0x43, 0xf3, 0x9c, 0x14, 0x97, 0x87, 0x05, 0xb0, 0x1f, 0xe4, 0xc3, 0x7e,
+        0xc2, 0x8e, 0xfc, 0x1e, 0x43, 0xfd, 0x6f, 0x52, 0x34, 0xf8, 0x9d, 0x60, 0xc4, 0x93, 0xb4, 0x0f,
+    ],
+    [
+        0x19, 0x02, 0x43, 0x18, 0x5a, 0x55, 0xd5, 0x1d, 0x40, 0x08, 0x18, 0x32, 0x70, 0x87, 0x18, 0x3c,
+        0xe7, 0x18, 0x31, 0x50, 0x6d, 0xde, 0x09, 0x05, 0x0d, 0x86, 0x89, 0x27, 0xf4, 0xc9, 0xed, 0x00,
+    ],
+    [
+        0x0d, 0x7b, 0xb3, 0xac, 0x70, 0x92, 0x6c, 0xca, 0x19, 0xd0, 0xf8, 0x6c, 0xe2, 0xbc, 0x7b, 0x34,
+        0x54, 0xda, 0x6a, 0xa5, 0x34, 0xd7, 0x2a, 0xb4, 0xc0, 0xb7, 0xc3, 0x9e, 0xe9, 0xcf, 0x42, 0xdd,
+    ],
+];
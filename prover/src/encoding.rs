@@ -0,0 +1,140 @@
+//! Byte layouts the on-chain verifier reads
+//!
+//! All field elements are 32-byte big-endian. G2 coordinates are written (c1, c0), the
+//! EIP-197 order groth16-solana and the alt_bn128 syscalls expect; snarkjs JSON lists
+//! them (c0, c1).
+
+use ark_bn254::{Bn254, Fq, Fq2, G1Affine, G2Affine};
+use ark_ec::AffineRepr;
+use ark_ff::{BigInteger, PrimeField};
+use ark_groth16::{Proof, VerifyingKey};
+
+/// Size of a proof as passed to `withdraw`
+pub const PROOF_SIZE: usize = 256;
+
+/// Proof bytes for `withdraw`: A (64) | B (128) | C (64)
+///
+/// A is written as proven, not negated: `verify_proof` negates it on-chain, as it does
+/// for proofs exported from snarkjs.
+pub fn encode_proof(proof: &Proof<Bn254>) -> [u8; PROOF_SIZE] {
+    let mut bytes = [0u8; PROOF_SIZE];
+    bytes[..64].copy_from_slice(&encode_g1(&proof.a));
+    bytes[64..192].copy_from_slice(&encode_g2(&proof.b));
+    bytes[192..].copy_from_slice(&encode_g1(&proof.c));
+    bytes
+}
+
+/// Verifying key bytes for `initialize` / `propose_verifying_key`:
+/// nr_pubinputs (u32 LE) | alpha_g1 | beta_g2 | gamma_g2 | delta_g2 | IC
+pub fn encode_verifying_key(vk: &VerifyingKey<Bn254>) -> Vec<u8> {
+    let nr_pubinputs = vk.gamma_abc_g1.len().saturating_sub(1) as u32;
+    let mut bytes = Vec::with_capacity(4 + 64 + 3 * 128 + vk.gamma_abc_g1.len() * 64);
+
+    bytes.extend_from_slice(&nr_pubinputs.to_le_bytes());
+    bytes.extend_from_slice(&encode_g1(&vk.alpha_g1));
+    bytes.extend_from_slice(&encode_g2(&vk.beta_g2));
+    bytes.extend_from_slice(&encode_g2(&vk.gamma_g2));
+    bytes.extend_from_slice(&encode_g2(&vk.delta_g2));
+    for point in &vk.gamma_abc_g1 {
+        bytes.extend_from_slice(&encode_g1(point));
+    }
+    bytes
+}
+
+/// x | y; the point at infinity is all zeros
+pub fn encode_g1(point: &G1Affine) -> [u8; 64] {
+    let mut bytes = [0u8; 64];
+    if let Some((x, y)) = point.xy() {
+        bytes[..32].copy_from_slice(&encode_fq(x));
+        bytes[32..].copy_from_slice(&encode_fq(y));
+    }
+    bytes
+}
+
+/// x.c1 | x.c0 | y.c1 | y.c0; the point at infinity is all zeros
+pub fn encode_g2(point: &G2Affine) -> [u8; 128] {
+    let mut bytes = [0u8; 128];
+    if let Some((x, y)) = point.xy() {
+        bytes[..64].copy_from_slice(&encode_fq2(x));
+        bytes[64..].copy_from_slice(&encode_fq2(y));
+    }
+    bytes
+}
+
+fn encode_fq2(value: &Fq2) -> [u8; 64] {
+    let mut bytes = [0u8; 64];
+    bytes[..32].copy_from_slice(&encode_fq(&value.c1));
+    bytes[32..].copy_from_slice(&encode_fq(&value.c0));
+    bytes
+}
+
+fn encode_fq(value: &Fq) -> [u8; 32] {
+    let mut bytes = [0u8; 32];
+    bytes.copy_from_slice(&value.into_bigint().to_bytes_be());
+    bytes
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn be(value: u64) -> [u8; 32] {
+        let mut bytes = [0u8; 32];
+        bytes[24..].copy_from_slice(&value.to_be_bytes());
+        bytes
+    }
+
+    #[test]
+    fn test_g1_generator_encoding() {
+        // G1 generator is (1, 2)
+        let bytes = encode_g1(&G1Affine::generator());
+        assert_eq!(bytes[..32], be(1));
+        assert_eq!(bytes[32..], be(2));
+        assert_eq!(encode_g1(&G1Affine::zero()), [0u8; 64]);
+    }
+
+    #[test]
+    fn test_g2_generator_encoding() {
+        // EIP-197 lists the G2 generator as x.c1, x.c0, y.c1, y.c0
+        let expected = hex::decode(concat!(
+            "198e9393920d483a7260bfb731fb5d25f1aa493335a9e71297e485b7aef312c2",
+            "1800deef121f1e76426a00665e5c4479674322d4f75edadd46debd5cd992f6ed",
+            "090689d0585ff075ec9e99ad690c3395bc4b313370b38ef355acdadcd122975b",
+            "12c85ea5db8c6deb4aab71808dcb408fe3d1e7690c43d37b4ce6cc0166fa7daa",
+        ))
+        .unwrap();
+        assert_eq!(encode_g2(&G2Affine::generator())[..], expected[..]);
+    }
+
+    #[test]
+    fn test_proof_layout() {
+        let proof = Proof::<Bn254> {
+            a: G1Affine::generator(),
+            b: G2Affine::generator(),
+            c: (G1Affine::generator() + G1Affine::generator()).into(),
+        };
+        let bytes = encode_proof(&proof);
+
+        assert_eq!(bytes[..64], encode_g1(&proof.a));
+        assert_eq!(bytes[64..192], encode_g2(&proof.b));
+        assert_eq!(bytes[192..], encode_g1(&proof.c));
+    }
+
+    #[test]
+    fn test_verifying_key_layout() {
+        let vk = VerifyingKey::<Bn254> {
+            alpha_g1: G1Affine::generator(),
+            beta_g2: G2Affine::generator(),
+            gamma_g2: G2Affine::generator(),
+            delta_g2: G2Affine::generator(),
+            gamma_abc_g1: vec![G1Affine::generator(); 9],
+        };
+        let bytes = encode_verifying_key(&vk);
+
+        assert_eq!(bytes.len(), 4 + 64 + 3 * 128 + 9 * 64);
+        assert_eq!(bytes[..4], 8u32.to_le_bytes());
+        assert_eq!(bytes[4..68], encode_g1(&vk.alpha_g1));
+        assert_eq!(bytes[68..196], encode_g2(&vk.beta_g2));
+        assert_eq!(bytes[bytes.len() - 64..], encode_g1(&vk.gamma_abc_g1[8]));
+    }
+}
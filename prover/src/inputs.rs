@@ -0,0 +1,209 @@
+//! Withdraw circuit inputs
+//!
+//! Values use the program's encodings: 32-byte big-endian field elements and raw
+//! 32-byte addresses, split into 16-byte halves exactly as `prepare_public_inputs` does.
+
+use ark_bn254::Fr;
+use ark_ff::PrimeField;
+use num_bigint::{BigInt, BigUint, Sign};
+
+use crate::ProverError;
+
+/// Depth of the tree the compiled circuit checks (`Withdraw(20)`)
+pub const CIRCUIT_LEVELS: usize = 20;
+
+/// Public inputs of the circuit, in the order `verify_proof` passes them
+pub const NR_PUBLIC_INPUTS: usize = 8;
+
+/// Everything needed to prove one withdrawal
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct WithdrawInputs {
+    pub root: [u8; 32],
+    pub nullifier_hash: [u8; 32],
+    pub recipient: [u8; 32],
+    /// All zeros when withdrawing without a relayer
+    pub relayer: [u8; 32],
+    pub fee: u64,
+    pub refund: u64,
+    pub nullifier: [u8; 32],
+    pub secret: [u8; 32],
+    /// Sibling hashes, leaf level first
+    pub path_elements: Vec<[u8; 32]>,
+    /// 1 where the node at that level is a right child
+    pub path_indices: Vec<u8>,
+}
+
+impl WithdrawInputs {
+    /// Public inputs as the program prepares them:
+    /// root, nullifierHash, recipientHigh, recipientLow, relayerHigh, relayerLow, fee, refund
+    pub fn public_inputs(&self) -> [[u8; 32]; NR_PUBLIC_INPUTS] {
+        let (recipient_high, recipient_low) = split_address(&self.recipient);
+        let (relayer_high, relayer_low) = split_address(&self.relayer);
+        [
+            self.root,
+            self.nullifier_hash,
+            recipient_high,
+            recipient_low,
+            relayer_high,
+            relayer_low,
+            encode_u64(self.fee),
+            encode_u64(self.refund),
+        ]
+    }
+
+    /// Signal values keyed by their name in `withdraw_fixed.circom`
+    pub(crate) fn to_circom(&self) -> Result<Vec<(String, Vec<BigInt>)>, ProverError> {
+        if self.path_elements.len() != CIRCUIT_LEVELS || self.path_indices.len() != CIRCUIT_LEVELS {
+            return Err(ProverError::InvalidPathLength);
+        }
+        if self.path_indices.iter().any(|&index| index > 1) {
+            return Err(ProverError::InvalidPathIndex);
+        }
+
+        let field_elements = [
+            ("root", &self.root),
+            ("nullifierHash", &self.nullifier_hash),
+            ("nullifier", &self.nullifier),
+            ("secret", &self.secret),
+        ];
+        for (name, value) in field_elements {
+            check_in_field(name, value)?;
+        }
+        for element in &self.path_elements {
+            check_in_field("pathElements", element)?;
+        }
+
+        let public = self.public_inputs();
+        let names = [
+            "root",
+            "nullifierHash",
+            "recipientHigh",
+            "recipientLow",
+            "relayerHigh",
+            "relayerLow",
+            "fee",
+            "refund",
+        ];
+        let mut signals: Vec<(String, Vec<BigInt>)> = names
+            .iter()
+            .zip(&public)
+            .map(|(name, value)| (name.to_string(), vec![to_bigint(value)]))
+            .collect();
+
+        signals.push(("nullifier".to_string(), vec![to_bigint(&self.nullifier)]));
+        signals.push(("secret".to_string(), vec![to_bigint(&self.secret)]));
+        signals.push(("pathElements".to_string(), self.path_elements.iter().map(to_bigint).collect()));
+        signals.push((
+            "pathIndices".to_string(),
+            self.path_indices.iter().map(|&index| BigInt::from(index)).collect(),
+        ));
+        Ok(signals)
+    }
+}
+
+/// high = [0; 16] + first 16 bytes, low = [0; 16] + last 16 bytes
+fn split_address(address: &[u8; 32]) -> ([u8; 32], [u8; 32]) {
+    let mut high = [0u8; 32];
+    let mut low = [0u8; 32];
+    high[16..].copy_from_slice(&address[..16]);
+    low[16..].copy_from_slice(&address[16..]);
+    (high, low)
+}
+
+fn encode_u64(value: u64) -> [u8; 32] {
+    let mut bytes = [0u8; 32];
+    bytes[24..].copy_from_slice(&value.to_be_bytes());
+    bytes
+}
+
+fn to_bigint(value: &[u8; 32]) -> BigInt {
+    BigInt::from_bytes_be(Sign::Plus, value)
+}
+
+// The witness generator silently reduces modulo p; the program rejects such values
+fn check_in_field(name: &'static str, value: &[u8; 32]) -> Result<(), ProverError> {
+    if BigUint::from_bytes_be(value) < BigUint::from(Fr::MODULUS) {
+        Ok(())
+    } else {
+        Err(ProverError::NotInField(name))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn inputs() -> WithdrawInputs {
+        let mut recipient = [0u8; 32];
+        recipient[..16].copy_from_slice(&[0xaa; 16]);
+        recipient[16..].copy_from_slice(&[0xbb; 16]);
+
+        WithdrawInputs {
+            root: [1u8; 32],
+            nullifier_hash: [2u8; 32],
+            recipient,
+            relayer: [0u8; 32],
+            fee: 5_000,
+            refund: 7,
+            nullifier: [3u8; 32],
+            secret: [4u8; 32],
+            path_elements: vec![[5u8; 32]; CIRCUIT_LEVELS],
+            path_indices: vec![0; CIRCUIT_LEVELS],
+        }
+    }
+
+    #[test]
+    fn test_public_inputs_match_program_layout() {
+        let public = inputs().public_inputs();
+
+        assert_eq!(public[0], [1u8; 32]);
+        assert_eq!(public[1], [2u8; 32]);
+        assert_eq!(public[2][..16], [0u8; 16]);
+        assert_eq!(public[2][16..], [0xaa; 16]);
+        assert_eq!(public[3][16..], [0xbb; 16]);
+        assert_eq!(public[4], [0u8; 32]);
+        assert_eq!(public[6][24..], 5_000u64.to_be_bytes());
+        assert_eq!(public[7][24..], 7u64.to_be_bytes());
+    }
+
+    #[test]
+    fn test_circom_signals() {
+        let signals = inputs().to_circom().unwrap();
+        let names: Vec<&str> = signals.iter().map(|(name, _)| name.as_str()).collect();
+        assert_eq!(
+            names,
+            [
+                "root",
+                "nullifierHash",
+                "recipientHigh",
+                "recipientLow",
+                "relayerHigh",
+                "relayerLow",
+                "fee",
+                "refund",
+                "nullifier",
+                "secret",
+                "pathElements",
+                "pathIndices",
+            ]
+        );
+        assert_eq!(signals[2].1, vec![BigInt::from(u128::from_be_bytes([0xaa; 16]))]);
+        assert_eq!(signals[6].1, vec![BigInt::from(5_000u64)]);
+        assert_eq!(signals[10].1.len(), CIRCUIT_LEVELS);
+    }
+
+    #[test]
+    fn test_invalid_inputs_rejected() {
+        let mut short_path = inputs();
+        short_path.path_elements.pop();
+        assert!(matches!(short_path.to_circom(), Err(ProverError::InvalidPathLength)));
+
+        let mut bad_index = inputs();
+        bad_index.path_indices[3] = 2;
+        assert!(matches!(bad_index.to_circom(), Err(ProverError::InvalidPathIndex)));
+
+        let mut out_of_field = inputs();
+        out_of_field.path_elements[0] = [0xff; 32];
+        assert!(matches!(out_of_field.to_circom(), Err(ProverError::NotInField("pathElements"))));
+    }
+}
@@ -0,0 +1,65 @@
+//! Native Groth16 prover for the tornado-solana withdraw circuit
+//!
+//! Generates the witness with the circuit's compiled wasm, proves with arkworks and
+//! writes the 256-byte proof `withdraw` expects, so relayers and wallets written in
+//! Rust do not need Node and snarkjs.
+//!
+//! - [`inputs`]: the circuit's public and private inputs in the program's encodings
+//! - [`prover`]: load a zkey (or run a development setup) and prove
+//! - [`encoding`]: proof and verifying key byte layouts of the on-chain verifier
+
+use std::fmt;
+
+use ark_relations::r1cs::SynthesisError;
+
+pub mod encoding;
+pub mod inputs;
+pub mod prover;
+
+pub use encoding::{encode_proof, encode_verifying_key, PROOF_SIZE};
+pub use inputs::{WithdrawInputs, CIRCUIT_LEVELS};
+pub use prover::{development_setup, WithdrawProver};
+
+#[derive(Debug)]
+pub enum ProverError {
+    Io(std::io::Error),
+    /// The wasm, zkey or r1cs could not be parsed
+    InvalidArtifact(String),
+    /// The artifacts are not the 8-public-input withdraw circuit
+    CircuitMismatch,
+    /// Not exactly `CIRCUIT_LEVELS` path elements and indices
+    InvalidPathLength,
+    /// A path index other than 0 or 1
+    InvalidPathIndex,
+    /// A field element input is not below the BN254 scalar field modulus
+    NotInField(&'static str),
+    /// The wasm witness generator returned an error
+    Witness(String),
+    /// The inputs violate the circuit, e.g. a path that does not lead to `root`
+    UnsatisfiedConstraints,
+    Synthesis(SynthesisError),
+}
+
+impl fmt::Display for ProverError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Io(error) => write!(f, "{}", error),
+            Self::InvalidArtifact(error) => write!(f, "invalid circuit artifact: {}", error),
+            Self::CircuitMismatch => write!(f, "artifacts do not match the withdraw circuit"),
+            Self::InvalidPathLength => write!(f, "Merkle path must have {} levels", CIRCUIT_LEVELS),
+            Self::InvalidPathIndex => write!(f, "path indices must be 0 or 1"),
+            Self::NotInField(name) => write!(f, "{} is outside the BN254 scalar field", name),
+            Self::Witness(error) => write!(f, "witness generation failed: {}", error),
+            Self::UnsatisfiedConstraints => write!(f, "inputs do not satisfy the withdraw circuit"),
+            Self::Synthesis(error) => write!(f, "proving failed: {}", error),
+        }
+    }
+}
+
+impl std::error::Error for ProverError {}
+
+impl From<std::io::Error> for ProverError {
+    fn from(error: std::io::Error) -> Self {
+        Self::Io(error)
+    }
+}
@@ -0,0 +1,234 @@
+//! Witness generation and Groth16 proving
+
+use std::fs::File;
+use std::io::BufReader;
+use std::path::Path;
+
+use ark_bn254::{Bn254, Fr};
+use ark_circom::circom::{ConstraintVec, R1CSFile, R1CS};
+use ark_circom::{read_zkey, CircomCircuit, CircomReduction, WitnessCalculator};
+use ark_ff::{PrimeField, UniformRand};
+use ark_groth16::{prepare_verifying_key, Groth16, PreparedVerifyingKey, Proof, ProvingKey, VerifyingKey};
+use ark_relations::r1cs::ConstraintMatrices;
+use ark_std::rand::{CryptoRng, RngCore};
+
+use crate::encoding::{encode_proof, PROOF_SIZE};
+use crate::inputs::{WithdrawInputs, NR_PUBLIC_INPUTS};
+use crate::ProverError;
+
+/// Proves withdrawals for one verifying key
+///
+/// Load once and reuse: parsing the zkey and compiling the wasm dominate start-up.
+pub struct WithdrawProver {
+    proving_key: ProvingKey<Bn254>,
+    prepared_vk: PreparedVerifyingKey<Bn254>,
+    matrices: ConstraintMatrices<Fr>,
+    witness_calculator: WitnessCalculator,
+}
+
+impl WithdrawProver {
+    /// Circuit wasm (`withdraw_fixed_js/withdraw_fixed.wasm`) and the snarkjs zkey whose
+    /// verifying key the pool stores
+    pub fn from_zkey(wasm: impl AsRef<Path>, zkey: impl AsRef<Path>) -> Result<Self, ProverError> {
+        let mut reader = BufReader::new(File::open(zkey)?);
+        let (proving_key, matrices) =
+            read_zkey(&mut reader).map_err(|e| ProverError::InvalidArtifact(e.to_string()))?;
+        Self::new(wasm, proving_key, matrices)
+    }
+
+    /// Circuit wasm and an already loaded proving key with its constraint matrices
+    pub fn new(
+        wasm: impl AsRef<Path>,
+        proving_key: ProvingKey<Bn254>,
+        matrices: ConstraintMatrices<Fr>,
+    ) -> Result<Self, ProverError> {
+        let witness_calculator =
+            WitnessCalculator::new(wasm).map_err(|e| ProverError::InvalidArtifact(e.to_string()))?;
+        if matrices.num_instance_variables != NR_PUBLIC_INPUTS + 1 {
+            return Err(ProverError::CircuitMismatch);
+        }
+
+        let prepared_vk = prepare_verifying_key(&proving_key.vk);
+        Ok(Self { proving_key, prepared_vk, matrices, witness_calculator })
+    }
+
+    /// Verifying key to store in the pool, e.g. via `encoding::encode_verifying_key`
+    pub fn verifying_key(&self) -> &VerifyingKey<Bn254> {
+        &self.proving_key.vk
+    }
+
+    /// 256-byte proof for `withdraw`
+    pub fn prove<R: RngCore + CryptoRng>(
+        &mut self,
+        inputs: &WithdrawInputs,
+        rng: &mut R,
+    ) -> Result<[u8; PROOF_SIZE], ProverError> {
+        self.prove_groth16(inputs, rng).map(|proof| encode_proof(&proof))
+    }
+
+    /// Same as `prove`, before encoding
+    pub fn prove_groth16<R: RngCore + CryptoRng>(
+        &mut self,
+        inputs: &WithdrawInputs,
+        rng: &mut R,
+    ) -> Result<Proof<Bn254>, ProverError> {
+        let signals = inputs.to_circom()?;
+        let witness = self
+            .witness_calculator
+            .calculate_witness_element::<Bn254, _>(signals, true)
+            .map_err(|e| ProverError::Witness(e.to_string()))?;
+
+        let num_variables = self.matrices.num_instance_variables + self.matrices.num_witness_variables;
+        if witness.len() != num_variables {
+            return Err(ProverError::CircuitMismatch);
+        }
+        // Public signals follow the constant 1 wire; a wasm for another circuit disagrees here
+        let public = inputs.public_inputs();
+        let public: Vec<Fr> = public.iter().map(|value| Fr::from_be_bytes_mod_order(value)).collect();
+        if witness[1..=NR_PUBLIC_INPUTS] != public[..] {
+            return Err(ProverError::CircuitMismatch);
+        }
+
+        let r = Fr::rand(rng);
+        let s = Fr::rand(rng);
+        let proof = Groth16::<Bn254, CircomReduction>::create_proof_with_reduction_and_matrices(
+            &self.proving_key,
+            r,
+            s,
+            &self.matrices,
+            self.matrices.num_instance_variables,
+            self.matrices.num_constraints,
+            &witness,
+        )
+        .map_err(ProverError::Synthesis)?;
+
+        // The wasm reports failed `===` checks through a handler ark-circom ignores, so a
+        // witness for e.g. a path that does not lead to `root` still proves; catch it here
+        // rather than in a rejected transaction
+        match Groth16::<Bn254>::verify_proof(&self.prepared_vk, &proof, &public) {
+            Ok(true) => Ok(proof),
+            Ok(false) => Err(ProverError::UnsatisfiedConstraints),
+            Err(error) => Err(ProverError::Synthesis(error)),
+        }
+    }
+}
+
+/// Single-party Groth16 setup from the circuit's r1cs
+///
+/// Whoever runs this knows the toxic waste and can forge proofs: only for tests and
+/// local clusters. Real pools use a zkey from a multi-party ceremony.
+pub fn development_setup<R: RngCore + CryptoRng>(
+    r1cs: impl AsRef<Path>,
+    rng: &mut R,
+) -> Result<(ProvingKey<Bn254>, ConstraintMatrices<Fr>), ProverError> {
+    let reader = BufReader::new(File::open(r1cs)?);
+    let file = R1CSFile::<Bn254>::new(reader).map_err(|e| ProverError::InvalidArtifact(e.to_string()))?;
+    let mut r1cs = R1CS::from(file);
+    // The wasm witness is already in wire order, as snarkjs and the zkey assume
+    r1cs.wire_mapping = None;
+
+    let matrices = constraint_matrices(&r1cs);
+    let circuit = CircomCircuit::<Bn254> { r1cs, witness: None };
+    let proving_key = Groth16::<Bn254, CircomReduction>::generate_random_parameters_with_reduction(circuit, rng)
+        .map_err(ProverError::Synthesis)?;
+    Ok((proving_key, matrices))
+}
+
+// Wire i is instance variable i below num_inputs and witness variable i - num_inputs above,
+// so matrix columns are the wire indices themselves
+fn constraint_matrices(r1cs: &R1CS<Bn254>) -> ConstraintMatrices<Fr> {
+    let row = |lc: &ConstraintVec<Bn254>| lc.iter().map(|&(wire, coeff)| (coeff, wire)).collect::<Vec<_>>();
+    let a: Vec<_> = r1cs.constraints.iter().map(|(a, _, _)| row(a)).collect();
+    let b: Vec<_> = r1cs.constraints.iter().map(|(_, b, _)| row(b)).collect();
+    let c: Vec<_> = r1cs.constraints.iter().map(|(_, _, c)| row(c)).collect();
+
+    ConstraintMatrices {
+        num_instance_variables: r1cs.num_inputs,
+        num_witness_variables: r1cs.num_aux,
+        num_constraints: r1cs.constraints.len(),
+        a_num_non_zero: a.iter().map(Vec::len).sum(),
+        b_num_non_zero: b.iter().map(Vec::len).sum(),
+        c_num_non_zero: c.iter().map(Vec::len).sum(),
+        a,
+        b,
+        c,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::encoding::encode_verifying_key;
+    use ark_std::rand::rngs::StdRng;
+    use ark_std::rand::SeedableRng;
+    use serde_json::Value;
+    use std::fs;
+
+    const WASM: &str = "../circuits/build/withdraw_fixed_js/withdraw_fixed.wasm";
+    const R1CS: &str = "../circuits/build/withdraw_fixed.r1cs";
+    // Shared with the client (inputs) and the program (on-chain verification)
+    const VECTOR: &str = "test_vectors/withdraw_proof.json";
+
+    fn bytes32(value: &Value) -> [u8; 32] {
+        hex::decode(value.as_str().unwrap()).unwrap().try_into().unwrap()
+    }
+
+    fn vector_inputs(vector: &Value) -> WithdrawInputs {
+        WithdrawInputs {
+            root: bytes32(&vector["root"]),
+            nullifier_hash: bytes32(&vector["nullifier_hash"]),
+            recipient: bytes32(&vector["recipient"]),
+            relayer: bytes32(&vector["relayer"]),
+            fee: vector["fee"].as_u64().unwrap(),
+            refund: vector["refund"].as_u64().unwrap(),
+            nullifier: bytes32(&vector["nullifier"]),
+            secret: bytes32(&vector["secret"]),
+            path_elements: vector["path_elements"].as_array().unwrap().iter().map(bytes32).collect(),
+            path_indices: vector["path_indices"]
+                .as_array()
+                .unwrap()
+                .iter()
+                .map(|index| index.as_u64().unwrap() as u8)
+                .collect(),
+        }
+    }
+
+    /// Seeded setup and proof reproduce the vector's bytes exactly;
+    /// run with UPDATE_TEST_VECTORS=1 to rewrite them
+    #[test]
+    fn test_proof_matches_vector_and_verifies() {
+        let mut vector: Value = serde_json::from_str(&fs::read_to_string(VECTOR).unwrap()).unwrap();
+        let inputs = vector_inputs(&vector);
+
+        let mut setup_rng = StdRng::seed_from_u64(vector["setup_seed"].as_u64().unwrap());
+        let (proving_key, matrices) = development_setup(R1CS, &mut setup_rng).unwrap();
+        let mut prover = WithdrawProver::new(WASM, proving_key, matrices).unwrap();
+
+        let mut proof_rng = StdRng::seed_from_u64(vector["proof_seed"].as_u64().unwrap());
+        let proof = prover.prove_groth16(&inputs, &mut proof_rng).unwrap();
+
+        let public: Vec<Fr> = inputs.public_inputs().iter().map(|value| Fr::from_be_bytes_mod_order(value)).collect();
+        let pvk = prepare_verifying_key(prover.verifying_key());
+        assert!(Groth16::<Bn254>::verify_proof(&pvk, &proof, &public).unwrap());
+
+        let verifying_key = hex::encode(encode_verifying_key(prover.verifying_key()));
+        let proof_bytes = hex::encode(encode_proof(&proof));
+        if std::env::var_os("UPDATE_TEST_VECTORS").is_some() {
+            vector["verifying_key"] = Value::from(verifying_key.clone());
+            vector["proof"] = Value::from(proof_bytes.clone());
+            fs::write(VECTOR, serde_json::to_string_pretty(&vector).unwrap() + "\n").unwrap();
+        }
+        assert_eq!(vector["verifying_key"], verifying_key);
+        assert_eq!(vector["proof"], proof_bytes);
+
+        // The proof is bound to its public inputs
+        let mut other_fee = public.clone();
+        other_fee[6] = Fr::from(inputs.fee + 1);
+        assert!(!Groth16::<Bn254>::verify_proof(&pvk, &proof, &other_fee).unwrap());
+
+        // A root the path does not lead to cannot be proven
+        let mut wrong_root = inputs.clone();
+        wrong_root.root = inputs.nullifier_hash;
+        assert!(matches!(prover.prove(&wrong_root, &mut proof_rng), Err(ProverError::UnsatisfiedConstraints)));
+    }
+}
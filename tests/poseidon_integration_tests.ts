@@ -44,7 +44,7 @@ describe("Poseidon Hash Integration Tests", () => {
         
         // Derive tornado state PDA
         [tornadoState] = anchor.web3.PublicKey.findProgramAddressSync(
-            [Buffer.from("tornado")],
+            [Buffer.from("tornado"), denomination.toArrayLike(Buffer, "le", 8)],
             program.programId
         );
         
@@ -37,7 +37,7 @@ describe("tornado_solana", () => {
     
     // Derive PDA for tornado state
     [tornadoState] = anchor.web3.PublicKey.findProgramAddressSync(
-      [Buffer.from("tornado")],
+      [Buffer.from("tornado"), denomination.toArrayLike(Buffer, "le", 8)],
       program.programId
     );
     